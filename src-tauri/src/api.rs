@@ -1,24 +1,33 @@
 use async_trait::async_trait;
+use futures::StreamExt;
 use json_patch::Patch;
+use log::error;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use crate::app::TigerApp;
 use crate::document::{Command, Document, DocumentResult};
 use crate::dto::{self, StateTrim, ToFileName};
-use crate::export::{export_sheet, ExportOutput};
-use crate::sheet::{Absolute, Sheet};
+use crate::export::{
+    export_sheet, preview_sheet, run_post_export_commands, CancellationToken, ExportError,
+    ExportOutput, ExportProgress, POST_EXPORT_COMMAND_TIMEOUT,
+};
+use crate::features::backups;
+use crate::features::command_line::{self, CommandLine};
+use crate::sheet::{Absolute, ExportSettings, Sheet};
 
 struct DocumentToSave {
     sheet: Sheet<Absolute>,
     source: PathBuf,
     destination: PathBuf,
     version: i32,
+    export_on_save: bool,
 }
 
 #[async_trait]
 pub trait Api {
     fn acknowledge_error(&self) -> Result<Patch, ()>;
+    fn allow_exporter<P: Into<PathBuf>>(&self, exporter: P) -> Result<Patch, ()>;
     fn apply_direction_preset(&self, preset: dto::DirectionPreset) -> Result<Patch, ()>;
     fn begin_drag_and_drop_frame<P: Into<PathBuf>>(&self, frame: P) -> Result<Patch, ()>;
     fn begin_drag_and_drop_keyframe(
@@ -32,6 +41,7 @@ pub trait Api {
         index: usize,
     ) -> Result<Patch, ()>;
     fn begin_export_as(&self) -> Result<Patch, ()>;
+    fn begin_macro_recording(&self) -> Result<Patch, ()>;
     fn begin_nudge_hitbox<S: Into<String>>(&self, name: S) -> Result<Patch, ()>;
     fn begin_nudge_keyframe(&self, direction: dto::Direction, index: usize) -> Result<Patch, ()>;
     fn begin_relocate_frames(&self) -> Result<Patch, ()>;
@@ -47,7 +57,9 @@ pub trait Api {
     fn browse_to_end(&self, shift: bool) -> Result<Patch, ()>;
     fn browse_to_start(&self, shift: bool) -> Result<Patch, ()>;
     fn cancel_close_document(&self) -> Result<Patch, ()>;
+    fn cancel_export<P: AsRef<Path>>(&self, document: P) -> Result<Patch, ()>;
     fn cancel_export_as(&self) -> Result<Patch, ()>;
+    fn cancel_macro_recording(&self) -> Result<Patch, ()>;
     fn cancel_relocate_frames(&self) -> Result<Patch, ()>;
     fn cancel_rename(&self) -> Result<Patch, ()>;
     fn center_workbench(&self) -> Result<Patch, ()>;
@@ -59,17 +71,21 @@ pub trait Api {
     fn close_without_saving(&self) -> Result<Patch, ()>;
     fn copy(&self) -> Result<Patch, ()>;
     fn create_animation(&self) -> Result<Patch, ()>;
+    fn create_export_template_variable(&self) -> Result<Patch, ()>;
     fn create_hitbox(&self) -> Result<Patch, ()>;
     fn cut(&self) -> Result<Patch, ()>;
     fn delete_animation<S: Into<String>>(&self, name: S) -> Result<Patch, ()>;
+    fn delete_export_template_variable<S: Into<String>>(&self, name: S) -> Result<Patch, ()>;
     fn delete_frame<P: Into<PathBuf>>(&self, path: P) -> Result<Patch, ()>;
     fn delete_hitbox<S: Into<String>>(&self, name: S) -> Result<Patch, ()>;
+    fn delete_macro<S: AsRef<str>>(&self, name: S) -> Result<Patch, ()>;
     fn delete_selected_animations(&self) -> Result<Patch, ()>;
     fn delete_selected_frames(&self) -> Result<Patch, ()>;
     fn delete_selected_hitboxes(&self) -> Result<Patch, ()>;
     fn delete_selected_keyframes(&self) -> Result<Patch, ()>;
     fn delete_selection(&self) -> Result<Patch, ()>;
     fn disable_sprite_darkening(&self) -> Result<Patch, ()>;
+    fn discard_recoverable_document<P: AsRef<Path>>(&self, path: P) -> Result<Patch, ()>;
     fn drop_frame_on_timeline(&self, direction: dto::Direction, index: usize) -> Result<Patch, ()>;
     fn drop_keyframe_on_timeline(
         &self,
@@ -82,6 +98,7 @@ pub trait Api {
     fn end_drag_and_drop_keyframe(&self) -> Result<Patch, ()>;
     fn end_drag_keyframe_duration(&self) -> Result<Patch, ()>;
     async fn end_export_as(&self) -> Result<Patch, ()>;
+    fn end_macro_recording<S: Into<String>>(&self, name: S) -> Result<Patch, ()>;
     fn end_nudge_hitbox(&self) -> Result<Patch, ()>;
     fn end_nudge_keyframe(&self) -> Result<Patch, ()>;
     fn end_relocate_frames(&self) -> Result<Patch, ()>;
@@ -96,14 +113,18 @@ pub trait Api {
     fn focus_next_document(&self) -> Result<Patch, ()>;
     fn focus_previous_document(&self) -> Result<Patch, ()>;
     fn get_state(&self) -> Result<dto::State, ()>;
+    fn get_texture_cache_stats(&self) -> Result<dto::TextureCacheStats, ()>;
     fn hide_hitboxes(&self) -> Result<Patch, ()>;
     fn hide_origin(&self) -> Result<Patch, ()>;
     fn hide_sprite(&self) -> Result<Patch, ()>;
     fn import_frames<P: Into<PathBuf>>(&self, paths: Vec<P>) -> Result<Patch, ()>;
     fn jump_to_animation_end(&self) -> Result<Patch, ()>;
     fn jump_to_animation_start(&self) -> Result<Patch, ()>;
+    fn jump_to_history_index(&self, index: usize) -> Result<Patch, ()>;
     fn jump_to_next_frame(&self) -> Result<Patch, ()>;
     fn jump_to_previous_frame(&self) -> Result<Patch, ()>;
+    fn keep_local_changes<P: AsRef<Path>>(&self, path: P) -> Result<Patch, ()>;
+    fn list_backups<P: AsRef<Path>>(&self, document: P) -> Result<Vec<dto::Backup>, ()>;
     fn lock_hitboxes(&self) -> Result<Patch, ()>;
     fn new_document<P: Into<PathBuf>>(&self, path: P) -> Result<Patch, ()>;
     fn nudge_selection(
@@ -122,16 +143,35 @@ pub trait Api {
     fn paste(&self) -> Result<Patch, ()>;
     fn pause(&self) -> Result<Patch, ()>;
     fn play(&self) -> Result<Patch, ()>;
+    fn play_macro<S: Into<String>>(&self, name: S) -> Result<Patch, ()>;
+    fn play_macro_on_animations<S: Into<String>, T: Into<String>>(
+        &self,
+        name: S,
+        animations: Vec<T>,
+    ) -> Result<Patch, ()>;
+    async fn preview_export(&self) -> Result<dto::ExportPreview, ()>;
+    async fn recover_document<P: Into<PathBuf> + Send + Sync>(&self, path: P) -> Result<Patch, ()>;
     fn redo(&self) -> Result<Patch, ()>;
+    fn reload_document<P: AsRef<Path>>(&self, path: P) -> Result<Patch, ()>;
     fn relocate_frame<F: Into<PathBuf>, T: Into<PathBuf>>(
         &self,
         from: F,
         to: T,
     ) -> Result<Patch, ()>;
+    fn rename_export_template_variable<S: Into<String>, T: Into<String>>(
+        &self,
+        old_name: S,
+        new_name: T,
+    ) -> Result<Patch, ()>;
     fn request_exit(&self) -> Result<Patch, ()>;
     fn request_install_update(&self) -> Result<Patch, ()>;
     fn reset_timeline_zoom(&self) -> Result<Patch, ()>;
     fn reset_workbench_zoom(&self) -> Result<Patch, ()>;
+    async fn restore_backup<P: Into<PathBuf> + Send + Sync, Q: Into<PathBuf> + Send + Sync>(
+        &self,
+        document: P,
+        backup: Q,
+    ) -> Result<Patch, ()>;
     async fn save(&self) -> Result<Patch, ()>;
     async fn save_all(&self) -> Result<Patch, ()>;
     async fn save_as<P: Into<PathBuf> + Send + Sync>(&self, path: P) -> Result<Patch, ()>;
@@ -157,12 +197,25 @@ pub trait Api {
     ) -> Result<Patch, ()>;
     fn set_animation_looping(&self, is_looping: bool) -> Result<Patch, ()>;
     fn set_animations_list_offset(&self, offset: f64) -> Result<Patch, ()>;
+    fn set_automation_server(&self, enabled: bool) -> Result<Patch, ()>;
     fn set_export_atlas_image_file<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()>;
+    fn set_export_exporter_file<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()>;
+    fn set_export_format(&self, format: dto::ExportFormat) -> Result<Patch, ()>;
     fn set_export_metadata_file<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()>;
     fn set_export_metadata_paths_root<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()>;
+    fn set_export_on_save(&self, enabled: bool) -> Result<Patch, ()>;
+    fn set_export_origin(&self, origin: dto::ExportOrigin) -> Result<Patch, ()>;
     fn set_export_template_file<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()>;
+    fn set_export_template_variable_value<S: Into<String>, T: Into<String>>(
+        &self,
+        name: S,
+        value: T,
+    ) -> Result<Patch, ()>;
+    fn set_export_uv_coordinates(&self, enabled: bool) -> Result<Patch, ()>;
+    fn set_export_y_axis(&self, y_axis: dto::ExportYAxis) -> Result<Patch, ()>;
     fn set_frames_list_mode(&self, list_mode: dto::ListMode) -> Result<Patch, ()>;
     fn set_frames_list_offset(&self, offset: f64) -> Result<Patch, ()>;
+    fn set_history_limit(&self, history_limit: usize) -> Result<Patch, ()>;
     fn set_hitbox_height(&self, height: u32) -> Result<Patch, ()>;
     fn set_hitbox_position_x(&self, x: i32) -> Result<Patch, ()>;
     fn set_hitbox_position_y(&self, y: i32) -> Result<Patch, ()>;
@@ -172,7 +225,12 @@ pub trait Api {
     fn set_keyframe_offset_x(&self, x: i32) -> Result<Patch, ()>;
     fn set_keyframe_offset_y(&self, y: i32) -> Result<Patch, ()>;
     fn set_keyframe_snapping_base_duration(&self, duration_millis: u64) -> Result<Patch, ()>;
+    fn set_max_backups(&self, max_backups: usize) -> Result<Patch, ()>;
+    fn set_persist_history(&self, persist_history: bool) -> Result<Patch, ()>;
+    fn set_post_export_commands(&self, commands: Vec<String>) -> Result<Patch, ()>;
+    fn set_restore_views(&self, restore_views: bool) -> Result<Patch, ()>;
     fn set_snap_keyframe_durations(&self, snap: bool) -> Result<Patch, ()>;
+    fn set_texture_cache_budget_mb(&self, texture_cache_budget_mb: usize) -> Result<Patch, ()>;
     fn set_snap_keyframes_to_multiples_of_duration(&self, snap: bool) -> Result<Patch, ()>;
     fn set_snap_keyframes_to_other_keyframes(&self, snap: bool) -> Result<Patch, ()>;
     fn set_timeline_offset(&self, offset_millis: f32) -> Result<Patch, ()>;
@@ -218,6 +276,12 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn allow_exporter<P: Into<PathBuf>>(&self, exporter: P) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.allow_exporter(exporter.into());
+        }))
+    }
+
     fn apply_direction_preset(&self, preset: dto::DirectionPreset) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -280,6 +344,14 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn begin_macro_recording(&self) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document.begin_macro_recording();
+            }
+        }))
+    }
+
     fn begin_nudge_hitbox<S: Into<String>>(&self, name: S) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -391,6 +463,14 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn cancel_export<P: AsRef<Path>>(&self, document: P) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.document_mut(document.as_ref()) {
+                document.cancel_export();
+            }
+        }))
+    }
+
     fn cancel_export_as(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -399,6 +479,14 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn cancel_macro_recording(&self) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document.cancel_macro_recording();
+            }
+        }))
+    }
+
     fn cancel_relocate_frames(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -491,6 +579,16 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn create_export_template_variable(&self) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::CreateExportTemplateVariable)
+                    .ok();
+            }
+        }))
+    }
+
     fn create_hitbox(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -523,6 +621,16 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn delete_export_template_variable<S: Into<String>>(&self, name: S) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::DeleteExportTemplateVariable(name.into()))
+                    .ok();
+            }
+        }))
+    }
+
     fn delete_frame<P: Into<PathBuf>>(&self, path: P) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -543,6 +651,12 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn delete_macro<S: AsRef<str>>(&self, name: S) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.delete_macro(name);
+        }))
+    }
+
     fn delete_selected_animations(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -599,6 +713,12 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn discard_recoverable_document<P: AsRef<Path>>(&self, path: P) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.discard_recoverable_document(path);
+        }))
+    }
+
     fn drop_frame_on_timeline(&self, direction: dto::Direction, index: usize) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -683,10 +803,21 @@ impl<A: TigerApp + Sync> Api for A {
                 document.process_command(Command::EndExportAs).ok();
             }
         });
-        export_document(self).await;
+        export_document(self).await.ok();
         Ok(patch)
     }
 
+    fn end_macro_recording<S: Into<String>>(&self, name: S) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(recorded_macro) = state
+                .current_document_mut()
+                .and_then(|d| d.end_macro_recording())
+            {
+                state.save_macro(name, recorded_macro);
+            }
+        }))
+    }
+
     fn end_nudge_hitbox(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -749,7 +880,7 @@ impl<A: TigerApp + Sync> Api for A {
                 .unwrap_or_default()
         };
         if has_export_settings {
-            export_document(self).await;
+            export_document(self).await.ok();
             Ok(Patch(Vec::new()))
         } else {
             self.begin_export_as()
@@ -798,6 +929,10 @@ impl<A: TigerApp + Sync> Api for A {
         Ok(self.state().lock().to_dto(StateTrim::Full))
     }
 
+    fn get_texture_cache_stats(&self) -> Result<dto::TextureCacheStats, ()> {
+        Ok(self.texture_cache().lock().stats().into())
+    }
+
     fn hide_hitboxes(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -850,6 +985,16 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn jump_to_history_index(&self, index: usize) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::JumpToHistoryIndex(index))
+                    .ok();
+            }
+        }))
+    }
+
     fn jump_to_next_frame(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -866,6 +1011,25 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn keep_local_changes<P: AsRef<Path>>(&self, path: P) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.document_mut(path.as_ref()) {
+                document.keep_local_version();
+            }
+        }))
+    }
+
+    fn list_backups<P: AsRef<Path>>(&self, document: P) -> Result<Vec<dto::Backup>, ()> {
+        let backups_directory = self.paths().lock().backups_directory.clone();
+        match backups::list_backups(document, backups_directory) {
+            Ok(backups) => Ok(backups.iter().map(|b| b.into()).collect()),
+            Err(e) => {
+                error!("Error while listing backups: {e}");
+                Err(())
+            }
+        }
+    }
+
     fn lock_hitboxes(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -910,11 +1074,25 @@ impl<A: TigerApp + Sync> Api for A {
         let mut documents: Vec<(PathBuf, DocumentResult<Document>)> = Vec::new();
         for path in paths {
             let open_path: PathBuf = path.into();
+            let (saved_view, saved_history) = {
+                let state_handle = self.state();
+                let state = state_handle.lock();
+                (
+                    state.saved_view(&open_path).cloned(),
+                    state.saved_history(&open_path).cloned(),
+                )
+            };
             documents.push((
                 open_path.clone(),
-                tauri::async_runtime::spawn_blocking(move || Document::open(open_path))
-                    .await
-                    .unwrap(),
+                tauri::async_runtime::spawn_blocking(move || {
+                    Document::open_with_saved_state(
+                        open_path,
+                        saved_view.as_ref(),
+                        saved_history.as_ref(),
+                    )
+                })
+                .await
+                .unwrap(),
             ));
         }
 
@@ -946,9 +1124,25 @@ impl<A: TigerApp + Sync> Api for A {
         if self.state().lock().opened_startup_documents() {
             Ok(Patch(Vec::new()))
         } else {
-            let startup_documents = self.command_line_arguments();
             self.state().lock().set_opened_startup_documents(true);
-            self.open_documents(startup_documents).await
+            let command_line = match CommandLine::parse(self.command_line_arguments()) {
+                Ok(command_line) => command_line,
+                Err(e) => {
+                    error!("Invalid command line: {e}");
+                    return Ok(Patch(Vec::new()));
+                }
+            };
+            let (patch, result) = command_line::run(self, &command_line).await;
+            if let Err(e) = &result {
+                error!("Error while running command line: {e}");
+            }
+            if command_line.export {
+                match result {
+                    Ok(()) => self.close_window(),
+                    Err(_) => self.exit(1),
+                }
+            }
+            Ok(patch)
         }
     }
 
@@ -1004,6 +1198,125 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn play_macro<S: Into<String>>(&self, name: S) -> Result<Patch, ()> {
+        let name = name.into();
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(recorded_macro) = state.macro_named(&name).cloned() {
+                if let Some(document) = state.current_document_mut() {
+                    let animations = document.macro_targets();
+                    document.play_macro(name, &recorded_macro, animations).ok();
+                }
+            }
+        }))
+    }
+
+    fn play_macro_on_animations<S: Into<String>, T: Into<String>>(
+        &self,
+        name: S,
+        animations: Vec<T>,
+    ) -> Result<Patch, ()> {
+        let name = name.into();
+        let animations = animations.into_iter().map(|a| a.into()).collect();
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(recorded_macro) = state.macro_named(&name).cloned() {
+                if let Some(document) = state.current_document_mut() {
+                    document.play_macro(name, &recorded_macro, animations).ok();
+                }
+            }
+        }))
+    }
+
+    async fn preview_export(&self) -> Result<dto::ExportPreview, ()> {
+        let (sheet, sheet_path, export_settings, cancellation_token) = {
+            let state_handle = self.state();
+            let mut state = state_handle.lock();
+            let document = state.current_document_mut().ok_or(())?;
+            // Settings being edited take precedence so the preview reflects the Export dialog
+            let export_settings = match document.export_settings_edit() {
+                Ok(settings) => Some(settings.clone()),
+                Err(_) => document
+                    .sheet()
+                    .export_settings()
+                    .clone()
+                    .map(|s| s.with_any_paths()),
+            };
+            let export_settings = match export_settings {
+                None => Err(ExportError::NoExportSettings.to_string()),
+                Some(settings) if !document.validate_settings(&settings).is_valid() => {
+                    Err(ExportError::InvalidExportSettings.to_string())
+                }
+                Some(settings) => settings.with_absolute_paths().map_err(|e| e.to_string()),
+            };
+            (
+                document.sheet().clone(),
+                document.path().to_owned(),
+                export_settings,
+                document.begin_export_preview(),
+            )
+        };
+
+        let export_settings = match export_settings {
+            Ok(settings) => settings,
+            Err(e) => return Ok(dto::ExportPreview::Error(e)),
+        };
+
+        let preview = tauri::async_runtime::spawn_blocking({
+            let texture_cache = self.texture_cache();
+            move || {
+                let progress = ExportProgress::new(|_| (), cancellation_token);
+                preview_sheet(
+                    &sheet,
+                    &sheet_path,
+                    &export_settings,
+                    texture_cache,
+                    &progress,
+                )
+            }
+        })
+        .await
+        .unwrap();
+
+        Ok(match preview {
+            Ok(preview) => dto::ExportPreview::Output((&preview).into()),
+            Err(e) => dto::ExportPreview::Error(e.to_string()),
+        })
+    }
+
+    async fn recover_document<P: Into<PathBuf> + Send + Sync>(&self, path: P) -> Result<Patch, ()> {
+        let path: PathBuf = path.into();
+        let Some(recovery_file) = self
+            .state()
+            .lock()
+            .recoverable_document(&path)
+            .map(|d| d.recovery_file.clone())
+        else {
+            return Ok(Patch(Vec::new()));
+        };
+
+        let result = tauri::async_runtime::spawn_blocking({
+            let path = path.clone();
+            move || Document::recover(path, recovery_file)
+        })
+        .await
+        .unwrap();
+
+        if let Err(e) = &result {
+            self.emit_all(
+                dto::EVENT_OPEN_DOCUMENT_ERROR,
+                dto::OpenDocumentError {
+                    document_name: path.to_file_name(),
+                    error: e.to_string(),
+                },
+            );
+        }
+
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Ok(document) = result {
+                state.open_recovered_document(document);
+            }
+        }))
+    }
+
     fn redo(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1012,6 +1325,14 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn reload_document<P: AsRef<Path>>(&self, path: P) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.document_mut(path.as_ref()) {
+                document.reload_external_modification().ok();
+            }
+        }))
+    }
+
     fn relocate_frame<P: Into<PathBuf>, Q: Into<PathBuf>>(
         &self,
         from: P,
@@ -1029,6 +1350,23 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn rename_export_template_variable<S: Into<String>, T: Into<String>>(
+        &self,
+        old_name: S,
+        new_name: T,
+    ) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::RenameExportTemplateVariable(
+                        old_name.into(),
+                        new_name.into(),
+                    ))
+                    .ok();
+            }
+        }))
+    }
+
     fn request_exit(&self) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             state.request_exit();
@@ -1060,6 +1398,56 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    async fn restore_backup<P: Into<PathBuf> + Send + Sync, Q: Into<PathBuf> + Send + Sync>(
+        &self,
+        document: P,
+        backup: Q,
+    ) -> Result<Patch, ()> {
+        let document_path: PathBuf = document.into();
+        let backup: PathBuf = backup.into();
+
+        let backups_directory = self.paths().lock().backups_directory.clone();
+        if !backups::is_backup_of(&backup, &document_path, backups_directory) {
+            self.emit_all(
+                dto::EVENT_OPEN_DOCUMENT_ERROR,
+                dto::OpenDocumentError {
+                    document_name: backup.to_file_name(),
+                    error: format!(
+                        "This file is not a backup of `{}`",
+                        document_path.to_file_name()
+                    ),
+                },
+            );
+            return Ok(Patch(Vec::new()));
+        }
+
+        let result = tauri::async_runtime::spawn_blocking({
+            let document_path = document_path.clone();
+            let backup = backup.clone();
+            move || Document::read_sheet(document_path, backup)
+        })
+        .await
+        .unwrap();
+
+        match result {
+            Ok(sheet) => Ok(self.patch(StateTrim::Full, |state| {
+                if let Some(document) = state.document_mut(&document_path) {
+                    document.process_command(Command::RestoreBackup(sheet)).ok();
+                }
+            })),
+            Err(e) => {
+                self.emit_all(
+                    dto::EVENT_OPEN_DOCUMENT_ERROR,
+                    dto::OpenDocumentError {
+                        document_name: backup.to_file_name(),
+                        error: e.to_string(),
+                    },
+                );
+                Ok(Patch(Vec::new()))
+            }
+        }
+    }
+
     async fn save(&self) -> Result<Patch, ()> {
         let documents_to_save: Vec<DocumentToSave> = {
             let state_handle = self.state();
@@ -1072,6 +1460,7 @@ impl<A: TigerApp + Sync> Api for A {
                 source: document.path().to_owned(),
                 destination: document.path().to_owned(),
                 version: document.version(),
+                export_on_save: document.should_export_on_save(),
             }]
         };
         save_documents(self, documents_to_save).await
@@ -1088,6 +1477,7 @@ impl<A: TigerApp + Sync> Api for A {
                     source: d.path().to_owned(),
                     destination: d.path().to_owned(),
                     version: d.version(),
+                    export_on_save: d.should_export_on_save(),
                 })
                 .collect()
         };
@@ -1106,6 +1496,7 @@ impl<A: TigerApp + Sync> Api for A {
                 source: document.path().to_owned(),
                 destination: new_path.into(),
                 version: document.version(),
+                export_on_save: document.should_export_on_save(),
             }]
         };
         save_documents(self, documents_to_save).await
@@ -1237,6 +1628,12 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn set_automation_server(&self, enabled: bool) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.set_automation_server(enabled);
+        }))
+    }
+
     fn set_export_atlas_image_file<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1247,6 +1644,26 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn set_export_exporter_file<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::SetExportExporterFile(file.into()))
+                    .ok();
+            }
+        }))
+    }
+
+    fn set_export_format(&self, format: dto::ExportFormat) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::SetExportFormat(format.into()))
+                    .ok();
+            }
+        }))
+    }
+
     fn set_export_metadata_file<P: Into<PathBuf>>(&self, file: P) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1267,6 +1684,26 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn set_export_on_save(&self, enabled: bool) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::SetExportOnSave(enabled))
+                    .ok();
+            }
+        }))
+    }
+
+    fn set_export_origin(&self, origin: dto::ExportOrigin) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::SetExportOrigin(origin.into()))
+                    .ok();
+            }
+        }))
+    }
+
     fn set_export_template_file<P: Into<PathBuf>>(&self, path: P) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1277,6 +1714,43 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn set_export_template_variable_value<S: Into<String>, T: Into<String>>(
+        &self,
+        name: S,
+        value: T,
+    ) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::SetExportTemplateVariableValue(
+                        name.into(),
+                        value.into(),
+                    ))
+                    .ok();
+            }
+        }))
+    }
+
+    fn set_export_uv_coordinates(&self, enabled: bool) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::SetExportUvCoordinates(enabled))
+                    .ok();
+            }
+        }))
+    }
+
+    fn set_export_y_axis(&self, y_axis: dto::ExportYAxis) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            if let Some(document) = state.current_document_mut() {
+                document
+                    .process_command(Command::SetExportYAxis(y_axis.into()))
+                    .ok();
+            }
+        }))
+    }
+
     fn set_frames_list_mode(&self, list_mode: dto::ListMode) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1297,6 +1771,12 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn set_history_limit(&self, history_limit: usize) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::Full, |state| {
+            state.set_history_limit(history_limit);
+        }))
+    }
+
     fn set_hitbox_height(&self, height: u32) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1391,6 +1871,30 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn set_max_backups(&self, max_backups: usize) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.set_max_backups(max_backups);
+        }))
+    }
+
+    fn set_persist_history(&self, persist_history: bool) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.set_persist_history(persist_history);
+        }))
+    }
+
+    fn set_post_export_commands(&self, commands: Vec<String>) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.set_post_export_commands(commands);
+        }))
+    }
+
+    fn set_restore_views(&self, restore_views: bool) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.set_restore_views(restore_views);
+        }))
+    }
+
     fn set_snap_keyframe_durations(&self, snap: bool) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::Full, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1421,6 +1925,12 @@ impl<A: TigerApp + Sync> Api for A {
         }))
     }
 
+    fn set_texture_cache_budget_mb(&self, texture_cache_budget_mb: usize) -> Result<Patch, ()> {
+        Ok(self.patch(StateTrim::NoDocuments, |state| {
+            state.set_texture_cache_budget_mb(texture_cache_budget_mb);
+        }))
+    }
+
     fn set_timeline_offset(&self, offset_millis: f32) -> Result<Patch, ()> {
         Ok(self.patch(StateTrim::OnlyWorkbench, |state| {
             if let Some(document) = state.current_document_mut() {
@@ -1664,11 +2174,27 @@ async fn save_documents<A: TigerApp>(
     app: &A,
     mut documents: Vec<DocumentToSave>,
 ) -> Result<Patch, ()> {
+    let backups_directory = app.paths().lock().backups_directory.clone();
+    let max_backups = app.state().lock().settings().max_backups;
+
     let mut work = Vec::new();
     for document in &mut documents {
-        let sheet = std::mem::take(&mut document.sheet);
+        let sheet = if document.export_on_save {
+            document.sheet.clone()
+        } else {
+            std::mem::take(&mut document.sheet)
+        };
         let write_destination = document.destination.clone();
+        let backups_directory = backups_directory.clone();
         work.push(tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) =
+                backups::create_backup(&write_destination, backups_directory, max_backups)
+            {
+                error!(
+                    "Error while backing up `{}`: {e}",
+                    write_destination.display()
+                );
+            }
             sheet.write(&write_destination)
         }));
     }
@@ -1680,22 +2206,21 @@ async fn save_documents<A: TigerApp>(
         .collect::<Vec<_>>();
 
     for (document, result) in documents.iter().zip(&results) {
-        match result {
-            Ok(()) => (),
-            Err(e) => {
-                app.emit_all(
-                    dto::EVENT_SAVE_DOCUMENT_ERROR,
-                    dto::SaveDocumentError {
-                        document_name: document.destination.to_file_name(),
-                        error: e.to_string(),
-                    },
-                );
-            }
+        if let Err(e) = result {
+            app.emit_all(
+                dto::EVENT_SAVE_DOCUMENT_ERROR,
+                dto::SaveDocumentError {
+                    document_name: document.destination.to_file_name(),
+                    error: e.to_string(),
+                },
+            );
         }
     }
 
-    Ok(app.patch(StateTrim::Full, |state| {
-        for (document, result) in documents.iter().zip(results) {
+    // Documents are marked as saved before exporting, so they do not appear
+    // modified while export-on-save is running.
+    app.patch_state(StateTrim::Full, |state| {
+        for (document, result) in documents.iter().zip(&results) {
             if result.is_ok() {
                 state.relocate_document(&document.source, &document.destination);
                 if let Some(d) = state.document_mut(&document.destination) {
@@ -1703,7 +2228,17 @@ async fn save_documents<A: TigerApp>(
                 }
             }
         }
+    });
 
+    for (document, result) in documents.iter().zip(&results) {
+        if result.is_ok() && document.export_on_save {
+            export_and_notify(app, document.sheet.clone(), &document.destination)
+                .await
+                .ok();
+        }
+    }
+
+    Ok(app.patch(StateTrim::Full, |state| {
         state.advance_exit();
         if state.should_exit() {
             app.close_window();
@@ -1711,44 +2246,137 @@ async fn save_documents<A: TigerApp>(
     }))
 }
 
-async fn export_document<A: TigerApp>(app: &A) {
+pub async fn export_document<A: TigerApp>(app: &A) -> Result<(), ExportError> {
     let (sheet, document_path) = {
         let state_handle = app.state();
         let state = state_handle.lock();
         match state.current_document() {
             Some(d) => (d.sheet().clone(), d.path().to_owned()),
-            _ => return,
+            _ => return Ok(()),
+        }
+    };
+    export_and_notify(app, sheet, &document_path).await
+}
+
+async fn export_and_notify<A: TigerApp>(
+    app: &A,
+    sheet: Sheet<Absolute>,
+    document_path: &Path,
+) -> Result<(), ExportError> {
+    let (previous_export, cancellation_token, post_export_commands) = {
+        let state_handle = app.state();
+        let mut state = state_handle.lock();
+        if let Some(ExportSettings::Exporter(settings)) = sheet.export_settings() {
+            if !state.is_exporter_allowed(settings.exporter_file()) {
+                drop(state);
+                let error = ExportError::ExporterNotAllowed(settings.exporter_file().to_owned());
+                notify_export_error(app, document_path, &error);
+                return Err(error);
+            }
+        }
+        let post_export_commands = state.settings().post_export_commands.clone();
+        match state.document_mut(document_path) {
+            Some(document) => (
+                document.last_export(),
+                document.begin_export(),
+                post_export_commands,
+            ),
+            None => (None, CancellationToken::new(), post_export_commands),
         }
     };
 
-    let result = tauri::async_runtime::spawn_blocking({
+    let (stage_sender, mut stage_receiver) = futures::channel::mpsc::unbounded();
+    let progress = ExportProgress::new(
+        move |stage| {
+            stage_sender.unbounded_send(stage).ok();
+        },
+        cancellation_token,
+    );
+
+    let export = tauri::async_runtime::spawn_blocking({
         let texture_cache = app.texture_cache();
-        move || export_sheet(&sheet, texture_cache)
-    })
-    .await
-    .unwrap();
-
-    match result {
-        Ok(output) => {
-            let ExportOutput::TemplateExportOutput {
+        let document_path = document_path.to_owned();
+        move || {
+            let (output, fingerprint) = export_sheet(
+                &sheet,
+                &document_path,
+                texture_cache,
+                previous_export,
+                &progress,
+            )?;
+            let post_export_outputs = match sheet.export_settings() {
+                Some(settings) => run_post_export_commands(
+                    &post_export_commands,
+                    settings,
+                    POST_EXPORT_COMMAND_TIMEOUT,
+                )?,
+                None => Vec::new(),
+            };
+            Ok::<_, ExportError>((output, fingerprint, post_export_outputs))
+        }
+    });
+
+    // Stages are received until the export task finishes and drops the sender
+    let report_progress = async {
+        while let Some(stage) = stage_receiver.next().await {
+            let payload = dto::ExportProgress {
+                document_name: document_path.to_file_name(),
+                document_path: document_path.to_owned(),
+                stage: stage.into(),
+            };
+            app.emit_all(dto::EVENT_EXPORT_PROGRESS, payload);
+        }
+    };
+
+    let (result, _) = futures::join!(export, report_progress);
+    let result = result.unwrap();
+
+    if let Some(document) = app.state().lock().document_mut(document_path) {
+        document.end_export();
+        if let Ok((_, fingerprint, _)) = &result {
+            document.set_last_export(*fingerprint);
+        }
+    }
+
+    match &result {
+        Ok((output, _, post_export_outputs)) => {
+            let (ExportOutput::TemplateExportOutput {
+                atlas_image_path,
+                metadata_path,
+            }
+            | ExportOutput::ExporterExportOutput {
                 atlas_image_path,
                 metadata_path,
-            } = output;
+            }) = output;
             let payload = dto::ExportSuccess {
                 document_name: document_path.to_file_name(),
+                document_path: document_path.to_owned(),
                 atlas_image_file_path: atlas_image_path.clone(),
                 atlas_image_file_name: atlas_image_path.to_file_name(),
                 metadata_file_path: metadata_path.clone(),
                 metadata_file_name: metadata_path.to_file_name(),
+                post_export_commands: post_export_outputs.iter().map(|o| o.into()).collect(),
             };
             app.emit_all(dto::EVENT_EXPORT_SUCCESS, payload);
         }
-        Err(e) => {
-            let payload = dto::ExportError {
+        Err(ExportError::Cancelled) => {
+            let payload = dto::ExportCancelled {
                 document_name: document_path.to_file_name(),
-                error: e.to_string(),
+                document_path: document_path.to_owned(),
             };
-            app.emit_all(dto::EVENT_EXPORT_ERROR, payload);
+            app.emit_all(dto::EVENT_EXPORT_CANCELLED, payload);
         }
+        Err(e) => notify_export_error(app, document_path, e),
     }
+
+    result.map(|_| ())
+}
+
+fn notify_export_error<A: TigerApp>(app: &A, document_path: &Path, error: &ExportError) {
+    let payload = dto::ExportError {
+        document_name: document_path.to_file_name(),
+        document_path: document_path.to_owned(),
+        error: error.to_string(),
+    };
+    app.emit_all(dto::EVENT_EXPORT_ERROR, payload);
 }
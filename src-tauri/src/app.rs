@@ -25,6 +25,7 @@ pub trait TigerApp {
     fn command_line_arguments(&self) -> Vec<String>;
     fn focus_window(&self);
     fn close_window(&self);
+    fn exit(&self, exit_code: i32);
     fn check_update(&self) -> bool;
     fn install_update(&self) -> Result<(), String>;
     fn release_startup_guard(&self);
@@ -12,6 +12,7 @@ use crate::{
     dto,
     features::{
         self,
+        external_modifications::ExternalModificationsInfo,
         single_instance::{acquire_startup_guard, StartupGuardHandle},
         template_hot_reload::TemplateHotReloadInfo,
         texture_cache::{self, TextureCacheInfo},
@@ -36,8 +37,10 @@ pub struct TigerAppMock {
     command_line_arguments: handle::Handle<Vec<String>>,
     focused: handle::Handle<bool>,
     closed: handle::Handle<bool>,
+    exit_code: handle::Handle<Option<i32>>,
     startup_guard: StartupGuardHandle,
     main_window_visible: handle::Handle<bool>,
+    external_modifications_info: Option<ExternalModificationsInfo>,
     template_hot_reload_info: Option<TemplateHotReloadInfo>,
     texture_cache_info: Option<TextureCacheInfo>,
     texture_hot_reload_info: Option<TextureHotReloadInfo>,
@@ -101,19 +104,27 @@ impl TigerAppMockBuilder {
             command_line_arguments: handle::Handle::default(),
             focused: handle::Handle::default(),
             closed: handle::Handle::default(),
+            exit_code: handle::Handle::default(),
             startup_guard: StartupGuardHandle::new(startup_guard),
             main_window_visible: handle::Handle::default(),
+            external_modifications_info: None,
             template_hot_reload_info: None,
             texture_cache_info: None,
             texture_hot_reload_info: None,
         };
 
         features::app_updates::init(app.clone());
+        features::autosave::init(app.clone());
         features::texture_cache::init(app.clone());
         features::clipboard_analysis::init(app.clone());
+        features::macros::init(app.clone());
         features::missing_textures::init(app.clone());
         features::onboarding::init(app.clone());
         features::recent_documents::init(app.clone());
+        features::saved_histories::init(app.clone());
+        features::saved_views::init(app.clone());
+        features::settings::init(app.clone());
+        app.external_modifications_info = Some(features::external_modifications::init(app.clone()));
         app.template_hot_reload_info = Some(features::template_hot_reload::init(app.clone()));
         app.texture_cache_info = Some(features::texture_cache::init(app.clone()));
         app.texture_hot_reload_info = Some(features::texture_hot_reload::init(app.clone()));
@@ -144,6 +155,10 @@ impl TigerAppMock {
         self.texture_hot_reload_info.as_ref().unwrap().clone()
     }
 
+    pub fn external_modifications_info(&self) -> ExternalModificationsInfo {
+        self.external_modifications_info.as_ref().unwrap().clone()
+    }
+
     pub fn template_hot_reload_info(&self) -> TemplateHotReloadInfo {
         self.template_hot_reload_info.as_ref().unwrap().clone()
     }
@@ -160,6 +175,10 @@ impl TigerAppMock {
         *self.closed.lock()
     }
 
+    pub fn exit_code(&self) -> Option<i32> {
+        *self.exit_code.lock()
+    }
+
     pub fn is_main_window_visible(&self) -> bool {
         *self.main_window_visible.lock()
     }
@@ -246,6 +265,11 @@ impl TigerApp for TigerAppMock {
         *self.closed.lock() = true;
     }
 
+    fn exit(&self, exit_code: i32) {
+        *self.closed.lock() = true;
+        *self.exit_code.lock() = Some(exit_code);
+    }
+
     fn check_update(&self) -> bool {
         true
     }
@@ -261,6 +285,10 @@ impl TigerAppMock {
         self.apply_patch(Api::acknowledge_error(self).unwrap());
     }
 
+    pub fn allow_exporter<P: Into<PathBuf>>(&self, exporter: P) {
+        self.apply_patch(Api::allow_exporter(self, exporter).unwrap());
+    }
+
     pub fn apply_direction_preset(&self, preset: dto::DirectionPreset) {
         self.apply_patch(Api::apply_direction_preset(self, preset).unwrap());
     }
@@ -281,6 +309,10 @@ impl TigerAppMock {
         self.apply_patch(Api::begin_export_as(self).unwrap());
     }
 
+    pub fn begin_macro_recording(&self) {
+        self.apply_patch(Api::begin_macro_recording(self).unwrap());
+    }
+
     pub fn begin_nudge_hitbox<S: Into<String>>(&self, name: S) {
         self.apply_patch(Api::begin_nudge_hitbox(self, name).unwrap());
     }
@@ -325,10 +357,18 @@ impl TigerAppMock {
         self.apply_patch(Api::cancel_close_document(self).unwrap());
     }
 
+    pub fn cancel_export<P: AsRef<Path>>(&self, document: P) {
+        self.apply_patch(Api::cancel_export(self, document).unwrap());
+    }
+
     pub fn cancel_export_as(&self) {
         self.apply_patch(Api::cancel_export_as(self).unwrap());
     }
 
+    pub fn cancel_macro_recording(&self) {
+        self.apply_patch(Api::cancel_macro_recording(self).unwrap());
+    }
+
     pub fn cancel_relocate_frames(&self) {
         self.apply_patch(Api::cancel_relocate_frames(self).unwrap());
     }
@@ -373,6 +413,10 @@ impl TigerAppMock {
         self.apply_patch(Api::create_animation(self).unwrap());
     }
 
+    pub fn create_export_template_variable(&self) {
+        self.apply_patch(Api::create_export_template_variable(self).unwrap());
+    }
+
     pub fn create_hitbox(&self) {
         self.apply_patch(Api::create_hitbox(self).unwrap());
     }
@@ -385,6 +429,10 @@ impl TigerAppMock {
         self.apply_patch(Api::delete_animation(self, name).unwrap());
     }
 
+    pub fn delete_export_template_variable<S: Into<String>>(&self, name: S) {
+        self.apply_patch(Api::delete_export_template_variable(self, name).unwrap());
+    }
+
     pub fn delete_frame<P: Into<PathBuf>>(&self, path: P) {
         self.apply_patch(Api::delete_frame(self, path).unwrap());
     }
@@ -393,6 +441,10 @@ impl TigerAppMock {
         self.apply_patch(Api::delete_hitbox(self, name).unwrap());
     }
 
+    pub fn delete_macro<S: AsRef<str>>(&self, name: S) {
+        self.apply_patch(Api::delete_macro(self, name).unwrap());
+    }
+
     pub fn delete_selected_animations(&self) {
         self.apply_patch(Api::delete_selected_animations(self).unwrap());
     }
@@ -417,6 +469,10 @@ impl TigerAppMock {
         self.apply_patch(Api::disable_sprite_darkening(self).unwrap());
     }
 
+    pub fn discard_recoverable_document<P: AsRef<Path>>(&self, path: P) {
+        self.apply_patch(Api::discard_recoverable_document(self, path).unwrap());
+    }
+
     pub fn drop_frame_on_timeline(&self, direction: dto::Direction, index: usize) {
         self.apply_patch(Api::drop_frame_on_timeline(self, direction, index).unwrap());
     }
@@ -449,6 +505,10 @@ impl TigerAppMock {
         self.apply_patch(Api::end_export_as(self).await.unwrap());
     }
 
+    pub fn end_macro_recording<S: Into<String>>(&self, name: S) {
+        self.apply_patch(Api::end_macro_recording(self, name).unwrap());
+    }
+
     pub fn end_nudge_hitbox(&self) {
         self.apply_patch(Api::end_nudge_hitbox(self).unwrap());
     }
@@ -501,6 +561,10 @@ impl TigerAppMock {
         self.apply_patch(Api::focus_previous_document(self).unwrap());
     }
 
+    pub fn get_texture_cache_stats(&self) -> dto::TextureCacheStats {
+        Api::get_texture_cache_stats(self).unwrap()
+    }
+
     pub fn hide_hitboxes(&self) {
         self.apply_patch(Api::hide_hitboxes(self).unwrap());
     }
@@ -525,6 +589,10 @@ impl TigerAppMock {
         self.apply_patch(Api::jump_to_animation_start(self).unwrap());
     }
 
+    pub fn jump_to_history_index(&self, index: usize) {
+        self.apply_patch(Api::jump_to_history_index(self, index).unwrap());
+    }
+
     pub fn jump_to_next_frame(&self) {
         self.apply_patch(Api::jump_to_next_frame(self).unwrap());
     }
@@ -533,6 +601,14 @@ impl TigerAppMock {
         self.apply_patch(Api::jump_to_previous_frame(self).unwrap());
     }
 
+    pub fn keep_local_changes<P: AsRef<Path>>(&self, path: P) {
+        self.apply_patch(Api::keep_local_changes(self, path).unwrap());
+    }
+
+    pub fn list_backups<P: AsRef<Path>>(&self, document: P) -> Vec<dto::Backup> {
+        Api::list_backups(self, document).unwrap()
+    }
+
     pub fn lock_hitboxes(&self) {
         self.apply_patch(Api::lock_hitboxes(self).unwrap());
     }
@@ -577,14 +653,46 @@ impl TigerAppMock {
         self.apply_patch(Api::play(self).unwrap());
     }
 
+    pub fn play_macro<S: Into<String>>(&self, name: S) {
+        self.apply_patch(Api::play_macro(self, name).unwrap());
+    }
+
+    pub fn play_macro_on_animations<S: Into<String>, T: Into<String>>(
+        &self,
+        name: S,
+        animations: Vec<T>,
+    ) {
+        self.apply_patch(Api::play_macro_on_animations(self, name, animations).unwrap());
+    }
+
+    pub async fn preview_export(&self) -> dto::ExportPreview {
+        Api::preview_export(self).await.unwrap()
+    }
+
+    pub async fn recover_document<P: Into<PathBuf> + Send + Sync>(&self, path: P) {
+        self.apply_patch(Api::recover_document(self, path).await.unwrap());
+    }
+
     pub fn redo(&self) {
         self.apply_patch(Api::redo(self).unwrap());
     }
 
+    pub fn reload_document<P: AsRef<Path>>(&self, path: P) {
+        self.apply_patch(Api::reload_document(self, path).unwrap());
+    }
+
     pub fn relocate_frame<P: Into<PathBuf>, Q: Into<PathBuf>>(&self, from: P, to: Q) {
         self.apply_patch(Api::relocate_frame(self, from, to).unwrap());
     }
 
+    pub fn rename_export_template_variable<S: Into<String>, T: Into<String>>(
+        &self,
+        old_name: S,
+        new_name: T,
+    ) {
+        self.apply_patch(Api::rename_export_template_variable(self, old_name, new_name).unwrap());
+    }
+
     pub fn request_exit(&self) {
         self.apply_patch(Api::request_exit(self).unwrap());
     }
@@ -601,6 +709,14 @@ impl TigerAppMock {
         self.apply_patch(Api::reset_workbench_zoom(self).unwrap());
     }
 
+    pub async fn restore_backup<P: Into<PathBuf> + Send + Sync, Q: Into<PathBuf> + Send + Sync>(
+        &self,
+        document: P,
+        backup: Q,
+    ) {
+        self.apply_patch(Api::restore_backup(self, document, backup).await.unwrap());
+    }
+
     pub async fn save(&self) {
         self.apply_patch(Api::save(self).await.unwrap());
     }
@@ -655,10 +771,22 @@ impl TigerAppMock {
         self.apply_patch(Api::set_animations_list_offset(self, offset).unwrap());
     }
 
+    pub fn set_automation_server(&self, enabled: bool) {
+        self.apply_patch(Api::set_automation_server(self, enabled).unwrap());
+    }
+
     pub fn set_export_atlas_image_file<P: Into<PathBuf>>(&self, path: P) {
         self.apply_patch(Api::set_export_atlas_image_file(self, path).unwrap());
     }
 
+    pub fn set_export_exporter_file<P: Into<PathBuf>>(&self, path: P) {
+        self.apply_patch(Api::set_export_exporter_file(self, path).unwrap());
+    }
+
+    pub fn set_export_format(&self, format: dto::ExportFormat) {
+        self.apply_patch(Api::set_export_format(self, format).unwrap());
+    }
+
     pub fn set_export_metadata_file<P: Into<PathBuf>>(&self, path: P) {
         self.apply_patch(Api::set_export_metadata_file(self, path).unwrap());
     }
@@ -667,10 +795,34 @@ impl TigerAppMock {
         self.apply_patch(Api::set_export_metadata_paths_root(self, path).unwrap());
     }
 
+    pub fn set_export_on_save(&self, enabled: bool) {
+        self.apply_patch(Api::set_export_on_save(self, enabled).unwrap());
+    }
+
+    pub fn set_export_origin(&self, origin: dto::ExportOrigin) {
+        self.apply_patch(Api::set_export_origin(self, origin).unwrap());
+    }
+
     pub fn set_export_template_file<P: Into<PathBuf>>(&self, path: P) {
         self.apply_patch(Api::set_export_template_file(self, path).unwrap());
     }
 
+    pub fn set_export_template_variable_value<S: Into<String>, T: Into<String>>(
+        &self,
+        name: S,
+        value: T,
+    ) {
+        self.apply_patch(Api::set_export_template_variable_value(self, name, value).unwrap());
+    }
+
+    pub fn set_export_uv_coordinates(&self, enabled: bool) {
+        self.apply_patch(Api::set_export_uv_coordinates(self, enabled).unwrap());
+    }
+
+    pub fn set_export_y_axis(&self, y_axis: dto::ExportYAxis) {
+        self.apply_patch(Api::set_export_y_axis(self, y_axis).unwrap());
+    }
+
     pub fn set_frames_list_mode(&self, list_mode: dto::ListMode) {
         self.apply_patch(Api::set_frames_list_mode(self, list_mode).unwrap());
     }
@@ -679,6 +831,10 @@ impl TigerAppMock {
         self.apply_patch(Api::set_frames_list_offset(self, offset).unwrap());
     }
 
+    pub fn set_history_limit(&self, history_limit: usize) {
+        self.apply_patch(Api::set_history_limit(self, history_limit).unwrap());
+    }
+
     pub fn set_hitbox_height(&self, height: u32) {
         self.apply_patch(Api::set_hitbox_height(self, height).unwrap());
     }
@@ -715,6 +871,22 @@ impl TigerAppMock {
         self.apply_patch(Api::set_keyframe_snapping_base_duration(self, duration_millis).unwrap());
     }
 
+    pub fn set_max_backups(&self, max_backups: usize) {
+        self.apply_patch(Api::set_max_backups(self, max_backups).unwrap());
+    }
+
+    pub fn set_persist_history(&self, persist_history: bool) {
+        self.apply_patch(Api::set_persist_history(self, persist_history).unwrap());
+    }
+
+    pub fn set_post_export_commands(&self, commands: Vec<String>) {
+        self.apply_patch(Api::set_post_export_commands(self, commands).unwrap());
+    }
+
+    pub fn set_restore_views(&self, restore_views: bool) {
+        self.apply_patch(Api::set_restore_views(self, restore_views).unwrap());
+    }
+
     pub fn set_snap_keyframe_durations(&self, snap: bool) {
         self.apply_patch(Api::set_snap_keyframe_durations(self, snap).unwrap());
     }
@@ -727,6 +899,10 @@ impl TigerAppMock {
         self.apply_patch(Api::set_snap_keyframes_to_other_keyframes(self, snap).unwrap());
     }
 
+    pub fn set_texture_cache_budget_mb(&self, texture_cache_budget_mb: usize) {
+        self.apply_patch(Api::set_texture_cache_budget_mb(self, texture_cache_budget_mb).unwrap());
+    }
+
     pub fn set_timeline_offset(&self, offset_millis: f32) {
         self.apply_patch(Api::set_timeline_offset(self, offset_millis).unwrap());
     }
@@ -2,10 +2,14 @@ use json_patch::Patch;
 use log::error;
 use serde::Serialize;
 use std::{
+    io::Cursor,
     path::{Path, PathBuf},
     time::Duration,
 };
-use tauri::{ClipboardManager, Manager};
+use tauri::{
+    http::{Request, Response, ResponseBuilder},
+    ClipboardManager, Manager,
+};
 
 use crate::{
     api::Api,
@@ -13,7 +17,7 @@ use crate::{
     dto::{self, StateTrim},
     features::{single_instance::StartupGuardHandle, texture_cache},
     state::{self, State},
-    utils::paths,
+    utils::{paths, texture_list::TextureList},
 };
 
 impl TigerApp for tauri::AppHandle {
@@ -117,6 +121,10 @@ impl TigerApp for tauri::AppHandle {
         }
     }
 
+    fn exit(&self, exit_code: i32) {
+        tauri::AppHandle::exit(self, exit_code);
+    }
+
     fn check_update(&self) -> bool {
         tauri::async_runtime::block_on(async {
             match self
@@ -164,11 +172,46 @@ impl TigerApp for tauri::AppHandle {
     }
 }
 
+// Serves downscaled frames for list views at `thumbnail://localhost/<path>`.
+// Only textures from open documents can be requested.
+pub fn thumbnail_protocol(
+    app: &tauri::AppHandle,
+    request: &Request,
+) -> Result<Response, Box<dyn std::error::Error>> {
+    let uri = request.uri();
+    let encoded_path = uri
+        .strip_prefix("thumbnail://localhost/")
+        .or_else(|| uri.strip_prefix("https://thumbnail.localhost/"))
+        .and_then(|p| p.split('?').next())
+        .unwrap_or_default();
+    let path = PathBuf::from(
+        percent_encoding::percent_decode_str(encoded_path)
+            .decode_utf8_lossy()
+            .into_owned(),
+    );
+
+    if !TigerApp::state(app).lock().list_textures().contains(&path) {
+        return ResponseBuilder::new().status(403).body(Vec::new());
+    }
+
+    let Ok(thumbnail) = texture_cache::get_or_create_thumbnail(&app.texture_cache(), &path) else {
+        return ResponseBuilder::new().status(404).body(Vec::new());
+    };
+    let mut png = Vec::new();
+    thumbnail.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+    ResponseBuilder::new().mimetype("image/png").body(png)
+}
+
 #[tauri::command]
 pub fn get_state(app: tauri::AppHandle) -> Result<dto::State, ()> {
     app.get_state()
 }
 
+#[tauri::command]
+pub fn get_texture_cache_stats(app: tauri::AppHandle) -> Result<dto::TextureCacheStats, ()> {
+    app.get_texture_cache_stats()
+}
+
 #[tauri::command]
 pub fn request_install_update(app: tauri::AppHandle) -> Result<Patch, ()> {
     Api::request_install_update(&app)
@@ -189,6 +232,11 @@ pub fn acknowledge_error(app: tauri::AppHandle) -> Result<Patch, ()> {
     app.acknowledge_error()
 }
 
+#[tauri::command]
+pub fn allow_exporter(app: tauri::AppHandle, exporter: PathBuf) -> Result<Patch, ()> {
+    app.allow_exporter(exporter)
+}
+
 #[tauri::command]
 pub fn new_document(app: tauri::AppHandle, path: PathBuf) -> Result<Patch, ()> {
     app.new_document(path)
@@ -289,6 +337,88 @@ pub async fn save_as(app: tauri::AppHandle, new_path: PathBuf) -> Result<Patch,
     app.save_as(new_path).await
 }
 
+#[tauri::command]
+pub async fn preview_export(app: tauri::AppHandle) -> Result<dto::ExportPreview, ()> {
+    app.preview_export().await
+}
+
+#[tauri::command]
+pub async fn recover_document(app: tauri::AppHandle, path: PathBuf) -> Result<Patch, ()> {
+    app.recover_document(path).await
+}
+
+#[tauri::command]
+pub fn list_backups(app: tauri::AppHandle, document: PathBuf) -> Result<Vec<dto::Backup>, ()> {
+    app.list_backups(document)
+}
+
+#[tauri::command]
+pub async fn restore_backup(
+    app: tauri::AppHandle,
+    document: PathBuf,
+    backup: PathBuf,
+) -> Result<Patch, ()> {
+    app.restore_backup(document, backup).await
+}
+
+#[tauri::command]
+pub fn set_automation_server(app: tauri::AppHandle, enabled: bool) -> Result<Patch, ()> {
+    app.set_automation_server(enabled)
+}
+
+#[tauri::command]
+pub fn set_history_limit(app: tauri::AppHandle, history_limit: usize) -> Result<Patch, ()> {
+    app.set_history_limit(history_limit)
+}
+
+#[tauri::command]
+pub fn set_max_backups(app: tauri::AppHandle, max_backups: usize) -> Result<Patch, ()> {
+    app.set_max_backups(max_backups)
+}
+
+#[tauri::command]
+pub fn delete_macro(app: tauri::AppHandle, name: String) -> Result<Patch, ()> {
+    app.delete_macro(name)
+}
+
+#[tauri::command]
+pub fn set_persist_history(app: tauri::AppHandle, persist_history: bool) -> Result<Patch, ()> {
+    app.set_persist_history(persist_history)
+}
+
+#[tauri::command]
+pub fn set_post_export_commands(app: tauri::AppHandle, commands: Vec<String>) -> Result<Patch, ()> {
+    app.set_post_export_commands(commands)
+}
+
+#[tauri::command]
+pub fn set_restore_views(app: tauri::AppHandle, restore_views: bool) -> Result<Patch, ()> {
+    app.set_restore_views(restore_views)
+}
+
+#[tauri::command]
+pub fn set_texture_cache_budget_mb(
+    app: tauri::AppHandle,
+    texture_cache_budget_mb: usize,
+) -> Result<Patch, ()> {
+    app.set_texture_cache_budget_mb(texture_cache_budget_mb)
+}
+
+#[tauri::command]
+pub fn reload_document(app: tauri::AppHandle, path: PathBuf) -> Result<Patch, ()> {
+    app.reload_document(path)
+}
+
+#[tauri::command]
+pub fn keep_local_changes(app: tauri::AppHandle, path: PathBuf) -> Result<Patch, ()> {
+    app.keep_local_changes(path)
+}
+
+#[tauri::command]
+pub fn discard_recoverable_document(app: tauri::AppHandle, path: PathBuf) -> Result<Patch, ()> {
+    app.discard_recoverable_document(path)
+}
+
 #[tauri::command]
 pub fn undo(app: tauri::AppHandle) -> Result<Patch, ()> {
     app.undo()
@@ -634,6 +764,11 @@ pub fn jump_to_previous_frame(app: tauri::AppHandle) -> Result<Patch, ()> {
     app.jump_to_previous_frame()
 }
 
+#[tauri::command]
+pub fn jump_to_history_index(app: tauri::AppHandle, index: usize) -> Result<Patch, ()> {
+    app.jump_to_history_index(index)
+}
+
 #[tauri::command]
 pub fn jump_to_next_frame(app: tauri::AppHandle) -> Result<Patch, ()> {
     app.jump_to_next_frame()
@@ -935,11 +1070,49 @@ pub fn begin_export_as(app: tauri::AppHandle) -> Result<Patch, ()> {
     app.begin_export_as()
 }
 
+#[tauri::command]
+pub fn set_export_format(app: tauri::AppHandle, format: dto::ExportFormat) -> Result<Patch, ()> {
+    app.set_export_format(format)
+}
+
 #[tauri::command]
 pub fn set_export_template_file(app: tauri::AppHandle, file: PathBuf) -> Result<Patch, ()> {
     app.set_export_template_file(file)
 }
 
+#[tauri::command]
+pub fn create_export_template_variable(app: tauri::AppHandle) -> Result<Patch, ()> {
+    app.create_export_template_variable()
+}
+
+#[tauri::command]
+pub fn rename_export_template_variable(
+    app: tauri::AppHandle,
+    old_name: String,
+    new_name: String,
+) -> Result<Patch, ()> {
+    app.rename_export_template_variable(old_name, new_name)
+}
+
+#[tauri::command]
+pub fn set_export_template_variable_value(
+    app: tauri::AppHandle,
+    name: String,
+    value: String,
+) -> Result<Patch, ()> {
+    app.set_export_template_variable_value(name, value)
+}
+
+#[tauri::command]
+pub fn delete_export_template_variable(app: tauri::AppHandle, name: String) -> Result<Patch, ()> {
+    app.delete_export_template_variable(name)
+}
+
+#[tauri::command]
+pub fn set_export_exporter_file(app: tauri::AppHandle, file: PathBuf) -> Result<Patch, ()> {
+    app.set_export_exporter_file(file)
+}
+
 #[tauri::command]
 pub fn set_export_atlas_image_file(app: tauri::AppHandle, file: PathBuf) -> Result<Patch, ()> {
     app.set_export_atlas_image_file(file)
@@ -958,6 +1131,31 @@ pub fn set_export_metadata_paths_root(
     app.set_export_metadata_paths_root(directory)
 }
 
+#[tauri::command]
+pub fn set_export_on_save(app: tauri::AppHandle, enabled: bool) -> Result<Patch, ()> {
+    app.set_export_on_save(enabled)
+}
+
+#[tauri::command]
+pub fn set_export_origin(app: tauri::AppHandle, origin: dto::ExportOrigin) -> Result<Patch, ()> {
+    app.set_export_origin(origin)
+}
+
+#[tauri::command]
+pub fn set_export_y_axis(app: tauri::AppHandle, y_axis: dto::ExportYAxis) -> Result<Patch, ()> {
+    app.set_export_y_axis(y_axis)
+}
+
+#[tauri::command]
+pub fn set_export_uv_coordinates(app: tauri::AppHandle, enabled: bool) -> Result<Patch, ()> {
+    app.set_export_uv_coordinates(enabled)
+}
+
+#[tauri::command]
+pub fn cancel_export(app: tauri::AppHandle, document: PathBuf) -> Result<Patch, ()> {
+    app.cancel_export(document)
+}
+
 #[tauri::command]
 pub fn cancel_export_as(app: tauri::AppHandle) -> Result<Patch, ()> {
     app.cancel_export_as()
@@ -967,3 +1165,32 @@ pub fn cancel_export_as(app: tauri::AppHandle) -> Result<Patch, ()> {
 pub async fn end_export_as(app: tauri::AppHandle) -> Result<Patch, ()> {
     app.end_export_as().await
 }
+
+#[tauri::command]
+pub fn begin_macro_recording(app: tauri::AppHandle) -> Result<Patch, ()> {
+    app.begin_macro_recording()
+}
+
+#[tauri::command]
+pub fn cancel_macro_recording(app: tauri::AppHandle) -> Result<Patch, ()> {
+    app.cancel_macro_recording()
+}
+
+#[tauri::command]
+pub fn end_macro_recording(app: tauri::AppHandle, name: String) -> Result<Patch, ()> {
+    app.end_macro_recording(name)
+}
+
+#[tauri::command]
+pub fn play_macro(app: tauri::AppHandle, name: String) -> Result<Patch, ()> {
+    app.play_macro(name)
+}
+
+#[tauri::command]
+pub fn play_macro_on_animations(
+    app: tauri::AppHandle,
+    name: String,
+    animations: Vec<String>,
+) -> Result<Patch, ()> {
+    app.play_macro_on_animations(name, animations)
+}
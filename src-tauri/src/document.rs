@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 use thiserror::Error;
 
+use crate::export::{CancellationToken, ExportFingerprint};
 use crate::sheet::*;
 
 mod clipboard;
@@ -10,6 +11,7 @@ mod command;
 mod content;
 mod export;
 mod keyframe;
+mod macros;
 mod relocate;
 mod selection;
 mod timeline;
@@ -21,6 +23,7 @@ pub use command::*;
 pub use content::*;
 pub use export::*;
 pub use keyframe::*;
+pub use macros::*;
 pub use relocate::*;
 pub use selection::*;
 pub use timeline::*;
@@ -38,6 +41,8 @@ pub struct Document {
     latest_version: i32,
     history: Vec<HistoryEntry>,
     history_index: usize,
+    history_sheet: Sheet<Absolute>, // Sheet as of the current history entry
+    history_limit: usize,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -49,6 +54,11 @@ pub struct Persistent {
     pub(super) relocate_frames_edit: Option<HashMap<PathBuf, PathBuf>>,
     pub(super) preserve_aspect_ratio: bool,
     pub(super) missing_textures: HashSet<PathBuf>,
+    pub(super) external_modification: Option<Sheet<Absolute>>,
+    pub(super) macro_recording: Option<MacroRecording>,
+    pub(super) last_export: Option<ExportFingerprint>,
+    pub(super) export_cancellation: Option<CancellationToken>,
+    pub(super) preview_cancellation: Option<CancellationToken>,
 }
 
 #[derive(Error, Debug)]
@@ -63,6 +73,10 @@ pub enum DocumentError {
     NoKeyframeAtTime(Duration),
     #[error("Sequence does not have a keyframe at index `{0}`")]
     NoKeyframeAtIndex(usize),
+    #[error("History does not have an entry at index `{0}`")]
+    NoHistoryEntryAtIndex(usize),
+    #[error("Command `{0}` cannot be part of a batch")]
+    CommandNotBatchable(String),
     #[error("Not currently renaming an animation")]
     NotRenamingAnyAnimation,
     #[error("Not currently renaming a hitbox")]
@@ -87,6 +101,8 @@ pub enum DocumentError {
     NotResizingHitbox,
     #[error("Not currently adjusting export settings")]
     NotEditingExportSettings,
+    #[error("Export settings being adjusted are not in the `{0:?}` format")]
+    UnexpectedExportFormat(ExportFormat),
     #[error("Not currently relocating frames")]
     NotRelocatingFrames,
     #[error("Sequence in animation has no keyframes")]
@@ -109,36 +125,86 @@ impl Document {
             persistent: Default::default(),
             latest_version,
             history_index: 0,
+            history_sheet: Default::default(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
         }
     }
 
     pub fn open<T: AsRef<Path>>(path: T) -> DocumentResult<Document> {
-        let mut directory = path.as_ref().to_owned();
-        directory.pop();
+        Document::open_with_saved_state(path, None, None)
+    }
 
-        let mut document = Document::new(&path);
-        document.sheet = Sheet::<Any>::read(path.as_ref())?
-            .with_relative_paths(directory)?
-            .with_absolute_paths();
+    pub fn open_with_saved_state<T: AsRef<Path>>(
+        path: T,
+        saved_view: Option<&SavedView>,
+        saved_history: Option<&SavedHistory>,
+    ) -> DocumentResult<Document> {
+        let mut document = Document::load(&path, &path, saved_view)?;
         document.mark_as_saved(document.version());
+        if let Some(saved_history) = saved_history {
+            document.restore_history(saved_history);
+        }
+        Ok(document)
+    }
 
-        if let Some(name) = document
-            .sheet
-            .sorted_animations()
-            .into_iter()
-            .map(|(name, _)| name)
-            .min()
-            .cloned()
-        {
+    pub fn recover<T: AsRef<Path>, U: AsRef<Path>>(
+        path: T,
+        recovery_file: U,
+    ) -> DocumentResult<Document> {
+        Document::load(path, recovery_file, None)
+    }
+
+    fn load<T: AsRef<Path>, U: AsRef<Path>>(
+        path: T,
+        source: U,
+        saved_view: Option<&SavedView>,
+    ) -> DocumentResult<Document> {
+        let mut document = Document::new(&path);
+        document.sheet = Document::read_sheet(&path, source)?;
+
+        let saved_animation = saved_view
+            .and_then(|v| v.current_animation.as_ref())
+            .filter(|name| document.sheet.has_animation(name));
+        if let Some(name) = saved_animation.cloned().or_else(|| {
+            document
+                .sheet
+                .sorted_animations()
+                .into_iter()
+                .map(|(name, _)| name)
+                .min()
+                .cloned()
+        }) {
             document.edit_animation(name)?;
         }
 
-        document.history[0].sheet = document.sheet.clone();
+        if let Some(saved_view) = saved_view {
+            document.view.restore(saved_view);
+            if saved_animation.is_some() && saved_view.current_sequence.is_some() {
+                document.view.current_sequence = saved_view.current_sequence;
+                document.sanitize_view();
+                document.select_current_keyframe().ok();
+            }
+        }
+
+        document.history_sheet = document.sheet.clone();
         document.history[0].view = document.view.clone();
 
         Ok(document)
     }
 
+    // Reads a sheet whose paths are relative to the directory of the document at `path`,
+    // even if the file itself lives elsewhere (eg. backups and recovery files).
+    pub fn read_sheet<T: AsRef<Path>, U: AsRef<Path>>(
+        path: T,
+        source: U,
+    ) -> DocumentResult<Sheet<Absolute>> {
+        let mut directory = path.as_ref().to_owned();
+        directory.pop();
+        Ok(Sheet::<Any>::read(source.as_ref())?
+            .with_relative_paths(directory)?
+            .with_absolute_paths())
+    }
+
     pub fn sheet(&self) -> &Sheet<Absolute> {
         &self.sheet
     }
@@ -167,6 +233,39 @@ impl Document {
         self.close_requested() && self.is_saved()
     }
 
+    pub fn was_modified_externally(&self) -> bool {
+        self.persistent.external_modification.is_some()
+    }
+
+    pub fn notify_external_modification(&mut self, sheet: Sheet<Absolute>) {
+        // Changes made by saving this document are not external modifications
+        let saved_sheet = self
+            .history
+            .iter()
+            .position(|entry| Some(entry.version) == self.persistent.disk_version)
+            .map(|index| self.history_sheet_at(index));
+        if sheet == self.sheet || Some(&sheet) == saved_sheet.as_ref() {
+            return;
+        }
+        self.persistent.external_modification = Some(sheet);
+    }
+
+    pub fn reload_external_modification(&mut self) -> DocumentResult<()> {
+        let Some(sheet) = self.persistent.external_modification.take() else {
+            return Ok(());
+        };
+        self.process_command(Command::Reload(sheet))?;
+        self.mark_as_saved(self.version());
+        Ok(())
+    }
+
+    pub fn keep_local_version(&mut self) {
+        if self.persistent.external_modification.take().is_some() {
+            // File on disk no longer matches any version of this document
+            self.persistent.disk_version = None;
+        }
+    }
+
     pub fn is_timeline_playing(&self) -> bool {
         self.persistent.timeline_is_playing
     }
@@ -1,17 +1,27 @@
 use euclid::default::Vector2D;
 use log::error;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::{path::PathBuf, time::Duration};
 
 use crate::document::*;
-use crate::sheet::{Direction, DirectionPreset, Sheet};
+use crate::sheet::{
+    Direction, DirectionPreset, ExportFormat, ExportOrigin, ExportYAxis, Sheet, SheetDiff,
+};
 
 #[derive(Clone, Debug)]
 pub enum Command {
     Undo,
     Redo,
+    JumpToHistoryIndex(usize),
+    Batch {
+        label: String,
+        commands: Vec<Command>,
+    },
     DetachedNavigation,
     Paste(Clipboard),
+    RestoreBackup(Sheet<Absolute>),
+    Reload(Sheet<Absolute>),
     SetFramesListMode(ListMode),
     SetFramesListOffset(f64),
     FilterFrames(String),
@@ -171,29 +181,59 @@ pub enum Command {
     },
     EndResizeHitbox,
     BeginExportAs,
+    SetExportFormat(ExportFormat),
     SetExportTemplateFile(PathBuf),
+    SetExportExporterFile(PathBuf),
+    CreateExportTemplateVariable,
+    RenameExportTemplateVariable(String, String),
+    SetExportTemplateVariableValue(String, String),
+    DeleteExportTemplateVariable(String),
     SetExportAtlasImageFile(PathBuf),
     SetExportMetadataFile(PathBuf),
     SetExportMetadataPathsRoot(PathBuf),
+    SetExportOnSave(bool),
+    SetExportOrigin(ExportOrigin),
+    SetExportYAxis(ExportYAxis),
+    SetExportUvCoordinates(bool),
     CancelExportAs,
     EndExportAs,
 }
 
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
 #[derive(Debug, Default)]
 pub(super) struct HistoryEntry {
-    pub(super) last_command: Option<Command>,
-    pub(super) sheet: Sheet<Absolute>,
+    pub(super) effect: Option<String>, // Description of the command that created this entry
+    pub(super) sheet_diff: SheetDiff<Absolute>, // Changes from the previous history entry
     pub(super) view: View,
     pub(super) version: i32,
 }
 
+// Undo history of a document, as stored between sessions
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SavedHistory {
+    head: Sheet<Absolute>, // Sheet as last saved to disk
+    head_index: usize,     // Index of the history entry matching `head`
+    entries: Vec<SavedHistoryEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct SavedHistoryEntry {
+    effect: Option<String>,
+    sheet_diff: SheetDiff<Absolute>,
+}
+
 impl Document {
     fn process_command_internal(&mut self, command: Command) -> DocumentResult<()> {
         match command {
             Command::Undo => self.undo()?,
             Command::Redo => self.redo()?,
+            Command::JumpToHistoryIndex(i) => self.jump_to_history_index(i)?,
+            Command::Batch { commands, .. } => self.process_batch(commands)?,
             Command::DetachedNavigation => (),
             Command::Paste(ref c) => self.paste(c.clone())?,
+            Command::RestoreBackup(sheet) => self.sheet = sheet,
+            Command::Reload(sheet) => self.sheet = sheet,
             Command::SetFramesListMode(m) => self.view.frames_list_mode = m,
             Command::FilterFrames(ref q) => self.view.frames_filter = q.clone(),
             Command::FilterAnimations(ref q) => self.view.animations_filter = q.clone(),
@@ -340,10 +380,26 @@ impl Document {
             } => self.update_resize_hitbox(displacement, preserve_aspect_ratio)?,
             Command::EndResizeHitbox => self.end_resize_hitbox(),
             Command::BeginExportAs => self.begin_export_as(),
+            Command::SetExportFormat(format) => self.set_export_format(format)?,
             Command::SetExportTemplateFile(ref p) => self.set_export_template_file(p)?,
+            Command::SetExportExporterFile(ref p) => self.set_export_exporter_file(p)?,
+            Command::CreateExportTemplateVariable => self.create_export_template_variable()?,
+            Command::RenameExportTemplateVariable(ref old_name, ref new_name) => {
+                self.rename_export_template_variable(old_name, new_name)?
+            }
+            Command::SetExportTemplateVariableValue(ref name, ref value) => {
+                self.set_export_template_variable_value(name, value)?
+            }
+            Command::DeleteExportTemplateVariable(ref name) => {
+                self.delete_export_template_variable(name)?
+            }
             Command::SetExportAtlasImageFile(ref p) => self.set_export_atlas_image_file(p)?,
             Command::SetExportMetadataFile(ref p) => self.set_export_metadata_file(p)?,
             Command::SetExportMetadataPathsRoot(ref p) => self.set_export_metadata_paths_root(p)?,
+            Command::SetExportOnSave(enabled) => self.set_export_on_save(enabled)?,
+            Command::SetExportOrigin(origin) => self.set_export_origin(origin)?,
+            Command::SetExportYAxis(y_axis) => self.set_export_y_axis(y_axis)?,
+            Command::SetExportUvCoordinates(enabled) => self.set_export_uv_coordinates(enabled)?,
             Command::CancelExportAs => self.cancel_export_as(),
             Command::EndExportAs => self.end_export_as()?,
         }
@@ -363,6 +419,10 @@ impl Document {
 
         self.sanitize_view();
 
+        if result.is_ok() {
+            self.record_macro_step(&command);
+        }
+
         if command.is_recordable() {
             self.record_command(command);
         }
@@ -370,6 +430,46 @@ impl Document {
         result
     }
 
+    // Applies all commands as a single history entry, or none of them if any fails
+    pub fn process_commands<S: Into<String>>(
+        &mut self,
+        label: S,
+        commands: Vec<Command>,
+    ) -> DocumentResult<()> {
+        self.process_command(Command::Batch {
+            label: label.into(),
+            commands,
+        })
+    }
+
+    fn process_batch(&mut self, commands: Vec<Command>) -> DocumentResult<()> {
+        if let Some(command) = commands.iter().find(|c| !c.is_batchable()) {
+            return Err(DocumentError::CommandNotBatchable(command.to_string()));
+        }
+
+        let sheet = self.sheet.clone();
+        let view = self.view.clone();
+        let transient = self.transient.clone();
+        let persistent = self.persistent.clone();
+
+        for command in commands {
+            if !command.preserves_transient_state() {
+                self.transient = Default::default();
+            }
+            let result = self.process_command_internal(command);
+            self.sanitize_view();
+            if let Err(e) = result {
+                self.sheet = sheet;
+                self.view = view;
+                self.transient = transient;
+                self.persistent = persistent;
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn is_saved(&self) -> bool {
         if self.persistent.disk_version == Some(self.version()) {
             true
@@ -380,13 +480,13 @@ impl Document {
             self.version() == 1
                 && self.persistent.disk_version == Some(0)
                 && self.history_index > 0
-                && self.history[self.history_index - 1].sheet
-                    == self.history[self.history_index].sheet
+                && self.history[self.history_index].sheet_diff.is_empty()
         }
     }
 
     pub fn mark_as_saved(&mut self, saved_version: i32) {
         self.persistent.disk_version = Some(saved_version);
+        self.persistent.external_modification = None;
     }
 
     pub fn version(&self) -> i32 {
@@ -398,15 +498,28 @@ impl Document {
         self.history.push(entry);
         self.history_index = self.history.len() - 1;
 
-        while self.history.len() > 100 {
-            self.history.remove(0);
-            self.history_index -= 1;
+        self.trim_history();
+    }
+
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit.max(1);
+        self.trim_history();
+    }
+
+    fn trim_history(&mut self) {
+        let excess = self.history.len().saturating_sub(self.history_limit);
+        if excess > 0 {
+            // Never discard the current entry or those after it
+            let excess = excess.min(self.history_index);
+            self.history.drain(0..excess);
+            self.history_index -= excess;
+            self.history[0].sheet_diff = Default::default();
         }
     }
 
     fn record_command(&mut self, command: Command) {
         let current_history_entry = &self.history[self.history_index];
-        let has_sheet_changes = current_history_entry.sheet != self.sheet;
+        let has_sheet_changes = self.history_sheet != self.sheet;
         let has_view_changes = current_history_entry.view != self.view;
         let is_at_head = self.history_index == self.history.len() - 1;
 
@@ -421,9 +534,9 @@ impl Document {
                         } else {
                             self.latest_version += 1;
                             self.push_undo_state(HistoryEntry {
-                                sheet: current_history_entry.sheet.clone(),
+                                sheet_diff: Default::default(),
                                 view: detached_view.clone(),
-                                last_command: Some(Command::DetachedNavigation),
+                                effect: Some(Command::DetachedNavigation.to_string()),
                                 version: self.latest_version,
                             });
                         }
@@ -433,10 +546,12 @@ impl Document {
             }
             // Record change that was just done
             self.latest_version += 1;
+            let sheet_diff = self.history_sheet.diff(&self.sheet);
+            self.history_sheet.apply(&sheet_diff);
             self.push_undo_state(HistoryEntry {
-                sheet: self.sheet.clone(),
+                sheet_diff,
                 view: self.view.clone(),
-                last_command: Some(command),
+                effect: Some(command.to_string()),
                 version: self.latest_version,
             });
         } else if has_view_changes && is_at_head {
@@ -445,9 +560,9 @@ impl Document {
             } else {
                 self.latest_version += 1;
                 self.push_undo_state(HistoryEntry {
-                    sheet: self.sheet.clone(),
+                    sheet_diff: Default::default(),
                     view: self.view.clone(),
-                    last_command: Some(command),
+                    effect: Some(command.to_string()),
                     version: self.latest_version,
                 });
             }
@@ -462,49 +577,128 @@ impl Document {
 
     fn can_merge_view(&self) -> bool {
         let is_at_tail = self.history[self.history_index].version == 0;
-        let on_top_of_view_change = self.history_index > 0
-            && self.history[self.history_index - 1].sheet == self.history[self.history_index].sheet;
+        let on_top_of_view_change =
+            self.history_index > 0 && self.history[self.history_index].sheet_diff.is_empty();
         !is_at_tail && on_top_of_view_change
     }
 
     pub fn undo(&mut self) -> DocumentResult<()> {
         if self.history_index > 0 {
-            self.history_index -= 1;
-            self.sheet = self.history[self.history_index].sheet.clone();
-            self.view = self.history[self.history_index].view.clone();
-            self.persistent.timeline_is_playing = false;
+            self.jump_to_history_index(self.history_index - 1)?;
         }
         Ok(())
     }
 
     pub fn redo(&mut self) -> DocumentResult<()> {
         if self.history_index < self.history.len() - 1 {
-            self.history_index += 1;
-            self.sheet = self.history[self.history_index].sheet.clone();
-            self.view = self.history[self.history_index].view.clone();
-            self.persistent.timeline_is_playing = false;
+            self.jump_to_history_index(self.history_index + 1)?;
         }
         Ok(())
     }
 
-    fn undo_command(&self) -> Option<&Command> {
-        self.history[self.history_index].last_command.as_ref()
+    fn jump_to_history_index(&mut self, index: usize) -> DocumentResult<()> {
+        if index >= self.history.len() {
+            return Err(DocumentError::NoHistoryEntryAtIndex(index));
+        }
+        while self.history_index > index {
+            self.history_sheet
+                .revert(&self.history[self.history_index].sheet_diff);
+            self.history_index -= 1;
+        }
+        while self.history_index < index {
+            self.history_index += 1;
+            self.history_sheet
+                .apply(&self.history[self.history_index].sheet_diff);
+        }
+        self.sheet = self.history_sheet.clone();
+        self.view = self.history[self.history_index].view.clone();
+        // View now matches the history entry, so there are no navigation changes to preserve
+        self.detached_view = None;
+        self.persistent.timeline_is_playing = false;
+        Ok(())
     }
 
-    fn redo_command(&self) -> Option<&Command> {
-        if self.history_index < self.history.len() - 1 {
-            self.history[self.history_index + 1].last_command.as_ref()
+    pub(super) fn history_sheet_at(&self, index: usize) -> Sheet<Absolute> {
+        let mut sheet = self.history_sheet.clone();
+        if index < self.history_index {
+            for entry in self.history[index + 1..=self.history_index].iter().rev() {
+                sheet.revert(&entry.sheet_diff);
+            }
         } else {
-            None
+            for entry in &self.history[self.history_index + 1..=index] {
+                sheet.apply(&entry.sheet_diff);
+            }
         }
+        sheet
+    }
+
+    pub fn saved_history(&self) -> Option<SavedHistory> {
+        let disk_version = self.persistent.disk_version?;
+        let head_index = self
+            .history
+            .iter()
+            .position(|entry| entry.version == disk_version)?;
+        Some(SavedHistory {
+            head: self.history_sheet_at(head_index),
+            head_index,
+            entries: self
+                .history
+                .iter()
+                .map(|entry| SavedHistoryEntry {
+                    effect: entry.effect.clone(),
+                    sheet_diff: entry.sheet_diff.clone(),
+                })
+                .collect(),
+        })
+    }
+
+    // Replaces history with a saved one, provided the sheet on disk matches the saved head.
+    // Views of restored entries are not persisted so they all use the current view.
+    pub(super) fn restore_history(&mut self, saved_history: &SavedHistory) -> bool {
+        // Sheets are compared in serialized form, where frames and animations are sorted
+        let head_matches_disk = serde_json::to_value(&saved_history.head).ok()
+            == serde_json::to_value(&self.sheet).ok();
+        if !head_matches_disk || saved_history.head_index >= saved_history.entries.len() {
+            return false;
+        }
+        self.history = saved_history
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| HistoryEntry {
+                effect: entry.effect.clone(),
+                sheet_diff: entry.sheet_diff.clone(),
+                view: self.view.clone(),
+                version: index as i32,
+            })
+            .collect();
+        self.history_index = saved_history.head_index;
+        self.history_sheet = self.sheet.clone();
+        self.latest_version = self.history.len() as i32 - 1;
+        self.mark_as_saved(self.version());
+        self.trim_history();
+        true
+    }
+
+    pub fn history_index(&self) -> usize {
+        self.history_index
+    }
+
+    pub fn history_effects(&self) -> Vec<Option<String>> {
+        self.history
+            .iter()
+            .map(|entry| entry.effect.clone())
+            .collect()
     }
 
     pub fn undo_effect(&self) -> Option<String> {
-        self.undo_command().map(|c| c.to_string())
+        self.history[self.history_index].effect.clone()
     }
 
     pub fn redo_effect(&self) -> Option<String> {
-        self.redo_command().map(|c| c.to_string())
+        self.history
+            .get(self.history_index + 1)
+            .and_then(|entry| entry.effect.clone())
     }
 }
 
@@ -522,6 +716,7 @@ impl Command {
                 | Command::BeginRenameSelection
                 | Command::BeginResizeHitbox { .. }
                 | Command::CancelRename
+                | Command::JumpToHistoryIndex(_)
                 | Command::Redo
                 | Command::Undo
                 | Command::UpdateDragKeyframeDuration(_)
@@ -531,6 +726,13 @@ impl Command {
         )
     }
 
+    fn is_batchable(&self) -> bool {
+        !matches!(
+            self,
+            Command::JumpToHistoryIndex(_) | Command::Redo | Command::Undo
+        )
+    }
+
     fn preserves_transient_state(&self) -> bool {
         matches!(
             self,
@@ -616,20 +818,34 @@ impl Display for Command {
             | Command::ResetTimelineZoom => f.write_str("Navigation"),
 
             Command::BeginExportAs
+            | Command::SetExportFormat(_)
             | Command::SetExportTemplateFile(_)
+            | Command::SetExportExporterFile(_)
+            | Command::CreateExportTemplateVariable
+            | Command::RenameExportTemplateVariable(_, _)
+            | Command::SetExportTemplateVariableValue(_, _)
+            | Command::DeleteExportTemplateVariable(_)
             | Command::SetExportAtlasImageFile(_)
             | Command::SetExportMetadataFile(_)
             | Command::SetExportMetadataPathsRoot(_)
+            | Command::SetExportOnSave(_)
+            | Command::SetExportOrigin(_)
+            | Command::SetExportYAxis(_)
+            | Command::SetExportUvCoordinates(_)
             | Command::CancelExportAs
             | Command::EndExportAs => f.write_str("Change Export Settings"),
 
             Command::Undo => f.write_str("Undo"),
             Command::Redo => f.write_str("Redo"),
+            Command::JumpToHistoryIndex(_) => f.write_str("Jump to History Entry"),
+            Command::Batch { label, .. } => f.write_str(label),
             Command::Paste(c) => match c {
                 Clipboard::Animations(_) => f.write_str("Paste Animations"),
                 Clipboard::Keyframes(_) => f.write_str("Paste Keyframes"),
                 Clipboard::Hitboxes(_) => f.write_str("Paste Hitboxes"),
             },
+            Command::RestoreBackup(_) => f.write_str("Restore Backup"),
+            Command::Reload(_) => f.write_str("Reload Document"),
             Command::ImportFrames(_) => f.write_str("Import Frames"),
             Command::DeleteFrame(_) => f.write_str("Delete Frame"),
             Command::DeleteSelectedFrames => f.write_str("Delete Frames"),
@@ -709,7 +925,10 @@ impl Display for Command {
 #[cfg(test)]
 mod tests {
 
+    use std::path::PathBuf;
+
     use crate::app::mock::TigerAppMock;
+    use crate::document::{Command, Document, DocumentError};
 
     fn list_frames(app: &TigerAppMock) -> Vec<String> {
         app.document()
@@ -747,6 +966,108 @@ mod tests {
         assert_eq!(list_frames(&app), all_three);
     }
 
+    #[test]
+    fn can_jump_to_history_index() {
+        let app = TigerAppMock::new();
+        app.new_document("tmp");
+        app.import_frames(vec!["frame_1"]);
+        app.import_frames(vec!["frame_2"]);
+        app.import_frames(vec!["frame_3"]);
+
+        let history = app.document().history;
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[0].effect, None);
+        assert_eq!(history[3].effect, Some(String::from("Import Frames")));
+        assert_eq!(app.document().history_index, 3);
+
+        app.jump_to_history_index(1);
+        assert_eq!(app.document().history_index, 1);
+        assert_eq!(list_frames(&app), vec![String::from("frame_1")]);
+
+        app.jump_to_history_index(3);
+        assert_eq!(list_frames(&app).len(), 3);
+
+        app.jump_to_history_index(0);
+        assert!(list_frames(&app).is_empty());
+        app.redo();
+        assert_eq!(list_frames(&app), vec![String::from("frame_1")]);
+    }
+
+    #[test]
+    fn can_process_commands_as_single_history_entry() {
+        let mut document = Document::new("tmp");
+        document
+            .process_commands(
+                "Import Frames Twice",
+                vec![
+                    Command::ImportFrames(vec![PathBuf::from("frame_1")]),
+                    Command::ImportFrames(vec![PathBuf::from("frame_2")]),
+                ],
+            )
+            .unwrap();
+        assert_eq!(document.sheet().frames_iter().count(), 2);
+        assert_eq!(document.history_index(), 1);
+        assert_eq!(
+            document.undo_effect(),
+            Some(String::from("Import Frames Twice"))
+        );
+
+        document.undo().unwrap();
+        assert_eq!(document.sheet().frames_iter().count(), 0);
+    }
+
+    #[test]
+    fn failed_batch_is_rolled_back() {
+        let mut document = Document::new("tmp");
+        let result = document.process_commands(
+            "Import Frames",
+            vec![
+                Command::ImportFrames(vec![PathBuf::from("frame_1")]),
+                Command::EditAnimation(String::from("missing")),
+            ],
+        );
+        assert!(matches!(
+            result,
+            Err(DocumentError::AnimationNotInDocument(_))
+        ));
+        assert_eq!(document.sheet().frames_iter().count(), 0);
+        assert_eq!(document.history_index(), 0);
+        assert_eq!(document.undo_effect(), None);
+    }
+
+    #[test]
+    fn batch_cannot_contain_history_navigation() {
+        let mut document = Document::new("tmp");
+        let result = document.process_commands(
+            "Import Frames",
+            vec![
+                Command::ImportFrames(vec![PathBuf::from("frame_1")]),
+                Command::Undo,
+            ],
+        );
+        assert!(matches!(result, Err(DocumentError::CommandNotBatchable(_))));
+        assert_eq!(document.sheet().frames_iter().count(), 0);
+    }
+
+    #[test]
+    fn history_is_limited() {
+        let app = TigerAppMock::new();
+        app.set_history_limit(3);
+        app.new_document("tmp");
+        app.import_frames(vec!["frame_1"]);
+        app.import_frames(vec!["frame_2"]);
+        app.import_frames(vec!["frame_3"]);
+        app.import_frames(vec!["frame_4"]);
+
+        app.undo();
+        app.undo();
+        app.undo();
+        assert_eq!(
+            list_frames(&app),
+            vec![String::from("frame_1"), String::from("frame_2")]
+        );
+    }
+
     #[tokio::test]
     async fn view_changes_do_not_dirty_document() {
         let app = TigerAppMock::new();
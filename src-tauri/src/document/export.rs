@@ -6,6 +6,7 @@ use crate::export::Template;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ExportSettingsValidation {
     Template(TemplateExportSettingsValidation),
+    Exporter(ExporterExportSettingsValidation),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -25,6 +26,14 @@ pub struct TemplateExportSettingsValidation {
     metadata_paths_root_error: Option<ExportSettingsError>,
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExporterExportSettingsValidation {
+    exporter_file_error: Option<ExportSettingsError>,
+    atlas_image_file_error: Option<ExportSettingsError>,
+    metadata_file_error: Option<ExportSettingsError>,
+    metadata_paths_root_error: Option<ExportSettingsError>,
+}
+
 impl Document {
     pub fn export_settings_edit(&self) -> DocumentResult<&ExportSettings<Any>> {
         self.persistent
@@ -45,6 +54,20 @@ impl Document {
     ) -> DocumentResult<&mut TemplateExportSettings<Any>> {
         match self.export_settings_edit_mut()? {
             ExportSettings::Template(settings) => Ok(settings),
+            ExportSettings::Exporter(_) => Err(DocumentError::UnexpectedExportFormat(
+                ExportFormat::Template,
+            )),
+        }
+    }
+
+    pub(super) fn exporter_export_settings_mut(
+        &mut self,
+    ) -> DocumentResult<&mut ExporterExportSettings<Any>> {
+        match self.export_settings_edit_mut()? {
+            ExportSettings::Exporter(settings) => Ok(settings),
+            ExportSettings::Template(_) => Err(DocumentError::UnexpectedExportFormat(
+                ExportFormat::Exporter,
+            )),
         }
     }
 
@@ -62,6 +85,12 @@ impl Document {
         self.persistent.export_settings_edit = None;
     }
 
+    pub(super) fn set_export_format(&mut self, format: ExportFormat) -> DocumentResult<()> {
+        let settings = self.export_settings_edit_mut()?;
+        *settings = settings.clone().with_format(format);
+        Ok(())
+    }
+
     pub(super) fn set_export_template_file<T: AsRef<Path>>(
         &mut self,
         file: T,
@@ -70,12 +99,53 @@ impl Document {
         Ok(())
     }
 
-    pub(super) fn set_export_atlas_image_file<T: AsRef<Path>>(
+    pub(super) fn set_export_exporter_file<T: AsRef<Path>>(
         &mut self,
         file: T,
+    ) -> DocumentResult<()> {
+        self.exporter_export_settings_mut()?.set_exporter_file(file);
+        Ok(())
+    }
+
+    pub(super) fn create_export_template_variable(&mut self) -> DocumentResult<()> {
+        self.template_export_settings_mut()?
+            .create_variable("variable_1");
+        Ok(())
+    }
+
+    pub(super) fn rename_export_template_variable<T: AsRef<str>, U: AsRef<str>>(
+        &mut self,
+        old_name: T,
+        new_name: U,
     ) -> DocumentResult<()> {
         self.template_export_settings_mut()?
-            .set_atlas_image_file(file);
+            .rename_variable(old_name, new_name)?;
+        Ok(())
+    }
+
+    pub(super) fn set_export_template_variable_value<T: AsRef<str>, U: AsRef<str>>(
+        &mut self,
+        name: T,
+        value: U,
+    ) -> DocumentResult<()> {
+        self.template_export_settings_mut()?
+            .set_variable(name, value)?;
+        Ok(())
+    }
+
+    pub(super) fn delete_export_template_variable<T: AsRef<str>>(
+        &mut self,
+        name: T,
+    ) -> DocumentResult<()> {
+        self.template_export_settings_mut()?.delete_variable(name);
+        Ok(())
+    }
+
+    pub(super) fn set_export_atlas_image_file<T: AsRef<Path>>(
+        &mut self,
+        file: T,
+    ) -> DocumentResult<()> {
+        self.export_settings_edit_mut()?.set_atlas_image_file(file);
         Ok(())
     }
 
@@ -83,7 +153,7 @@ impl Document {
         &mut self,
         file: T,
     ) -> DocumentResult<()> {
-        self.template_export_settings_mut()?.set_metadata_file(file);
+        self.export_settings_edit_mut()?.set_metadata_file(file);
         Ok(())
     }
 
@@ -91,18 +161,99 @@ impl Document {
         &mut self,
         directory: T,
     ) -> DocumentResult<()> {
-        self.template_export_settings_mut()?
+        self.export_settings_edit_mut()?
             .set_metadata_paths_root(directory);
         Ok(())
     }
 
+    pub(super) fn set_export_on_save(&mut self, enabled: bool) -> DocumentResult<()> {
+        self.export_settings_edit_mut()?.set_export_on_save(enabled);
+        Ok(())
+    }
+
+    pub(super) fn set_export_origin(&mut self, origin: ExportOrigin) -> DocumentResult<()> {
+        let settings = self.export_settings_edit_mut()?;
+        let mut coordinates = settings.coordinates();
+        coordinates.set_origin(origin);
+        settings.set_coordinates(coordinates);
+        Ok(())
+    }
+
+    pub(super) fn set_export_y_axis(&mut self, y_axis: ExportYAxis) -> DocumentResult<()> {
+        let settings = self.export_settings_edit_mut()?;
+        let mut coordinates = settings.coordinates();
+        coordinates.set_y_axis(y_axis);
+        settings.set_coordinates(coordinates);
+        Ok(())
+    }
+
+    pub(super) fn set_export_uv_coordinates(&mut self, enabled: bool) -> DocumentResult<()> {
+        let settings = self.export_settings_edit_mut()?;
+        let mut coordinates = settings.coordinates();
+        coordinates.set_uv_coordinates(enabled);
+        settings.set_coordinates(coordinates);
+        Ok(())
+    }
+
+    pub fn last_export(&self) -> Option<ExportFingerprint> {
+        self.persistent.last_export
+    }
+
+    pub fn set_last_export(&mut self, fingerprint: ExportFingerprint) {
+        self.persistent.last_export = Some(fingerprint);
+    }
+
+    pub fn begin_export(&mut self) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.persistent.export_cancellation = Some(token.clone());
+        token
+    }
+
+    pub fn end_export(&mut self) {
+        self.persistent.export_cancellation = None;
+    }
+
+    pub fn cancel_export(&mut self) {
+        if let Some(token) = self.persistent.export_cancellation.take() {
+            token.cancel();
+        }
+    }
+
+    // Cancels the previous export preview, which is outdated once a new one begins
+    pub fn begin_export_preview(&mut self) -> CancellationToken {
+        if let Some(token) = self.persistent.preview_cancellation.take() {
+            token.cancel();
+        }
+        let token = CancellationToken::new();
+        self.persistent.preview_cancellation = Some(token.clone());
+        token
+    }
+
     pub fn validate_export_settings(&self) -> DocumentResult<ExportSettingsValidation> {
-        let validation = match self.export_settings_edit()? {
+        Ok(self.validate_settings(self.export_settings_edit()?))
+    }
+
+    // Whether saving this document should also export it, using the export
+    // settings of the sheet (as opposed to the ones being edited).
+    pub fn should_export_on_save(&self) -> bool {
+        let Some(settings) = self.sheet.export_settings() else {
+            return false;
+        };
+        settings.export_on_save()
+            && self
+                .validate_settings(&settings.clone().with_any_paths())
+                .is_valid()
+    }
+
+    pub fn validate_settings(&self, settings: &ExportSettings<Any>) -> ExportSettingsValidation {
+        match settings {
             ExportSettings::Template(s) => {
                 ExportSettingsValidation::Template(self.validate_template_export_settings(s))
             }
-        };
-        Ok(validation)
+            ExportSettings::Exporter(s) => {
+                ExportSettingsValidation::Exporter(self.validate_exporter_export_settings(s))
+            }
+        }
     }
 
     fn validate_template_export_settings(
@@ -119,6 +270,20 @@ impl Document {
         }
     }
 
+    fn validate_exporter_export_settings(
+        &self,
+        settings: &ExporterExportSettings<Any>,
+    ) -> ExporterExportSettingsValidation {
+        ExporterExportSettingsValidation {
+            exporter_file_error: validate_exporter_path(settings.exporter_file()),
+            atlas_image_file_error: validate_output_file_path(settings.atlas_image_file()),
+            metadata_file_error: validate_output_file_path(settings.metadata_file()),
+            metadata_paths_root_error: validate_output_directory_path(
+                settings.metadata_paths_root(),
+            ),
+        }
+    }
+
     pub(super) fn end_export_as(&mut self) -> DocumentResult<()> {
         let export_settings = self
             .export_settings_edit_mut()?
@@ -130,6 +295,19 @@ impl Document {
     }
 }
 
+impl ExportSettingsValidation {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            ExportSettingsValidation::Template(s) => {
+                *s == TemplateExportSettingsValidation::default()
+            }
+            ExportSettingsValidation::Exporter(s) => {
+                *s == ExporterExportSettingsValidation::default()
+            }
+        }
+    }
+}
+
 impl TemplateExportSettingsValidation {
     pub fn template_file_error(&self) -> Option<&ExportSettingsError> {
         self.template_file_error.as_ref()
@@ -148,6 +326,24 @@ impl TemplateExportSettingsValidation {
     }
 }
 
+impl ExporterExportSettingsValidation {
+    pub fn exporter_file_error(&self) -> Option<&ExportSettingsError> {
+        self.exporter_file_error.as_ref()
+    }
+
+    pub fn atlas_image_file_error(&self) -> Option<&ExportSettingsError> {
+        self.atlas_image_file_error.as_ref()
+    }
+
+    pub fn metadata_file_error(&self) -> Option<&ExportSettingsError> {
+        self.metadata_file_error.as_ref()
+    }
+
+    pub fn metadata_paths_root_error(&self) -> Option<&ExportSettingsError> {
+        self.metadata_paths_root_error.as_ref()
+    }
+}
+
 fn validate_template_path(path: &Path) -> Option<ExportSettingsError> {
     if path.is_relative() {
         Some(ExportSettingsError::ExpectedAbsolutePath)
@@ -162,6 +358,18 @@ fn validate_template_path(path: &Path) -> Option<ExportSettingsError> {
     }
 }
 
+fn validate_exporter_path(path: &Path) -> Option<ExportSettingsError> {
+    if path.is_relative() {
+        Some(ExportSettingsError::ExpectedAbsolutePath)
+    } else if path.is_dir() {
+        Some(ExportSettingsError::ExpectedFile)
+    } else if !path.exists() {
+        Some(ExportSettingsError::FileNotFound)
+    } else {
+        None
+    }
+}
+
 fn validate_output_file_path(p: &Path) -> Option<ExportSettingsError> {
     if p.is_relative() {
         Some(ExportSettingsError::ExpectedAbsolutePath)
@@ -199,7 +407,7 @@ mod tests {
         let app = TigerAppMock::new();
         app.open_documents(vec!["test-data/samurai.tiger"]).await;
 
-        let ExportSettings::Template(export_settings) = {
+        let export_settings = {
             let state_handle = app.state();
             let state = state_handle.lock();
             state
@@ -267,6 +475,66 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn can_edit_template_variables() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.create_export_template_variable();
+        app.create_export_template_variable();
+        app.rename_export_template_variable("variable_1", "namespace");
+        app.set_export_template_variable_value("namespace", "game");
+        app.delete_export_template_variable("variable_2");
+
+        let settings = app.document().export_settings_being_edited.unwrap();
+        assert_eq!(settings.template_variables.len(), 1);
+        assert_eq!(settings.template_variables[0].name, "namespace");
+        assert_eq!(settings.template_variables[0].value, "game");
+    }
+
+    #[tokio::test]
+    async fn can_edit_coordinate_conventions() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_origin(dto::ExportOrigin::BottomCenter);
+        app.set_export_y_axis(dto::ExportYAxis::Up);
+        app.set_export_uv_coordinates(true);
+
+        let settings = app.document().export_settings_being_edited.unwrap();
+        assert_eq!(settings.origin, dto::ExportOrigin::BottomCenter);
+        assert_eq!(settings.y_axis, dto::ExportYAxis::Up);
+        assert!(settings.uv_coordinates);
+    }
+
+    #[tokio::test]
+    async fn template_variables_are_available_to_templates() {
+        let metadata_file =
+            PathBuf::from("test-output/template_variables_are_available_to_templates.txt")
+                .resolve();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_template_file(PathBuf::from("test-data/variables.template").resolve());
+        app.set_export_atlas_image_file(
+            PathBuf::from("test-output/template_variables_are_available_to_templates.png")
+                .resolve(),
+        );
+        app.set_export_metadata_file(&metadata_file);
+        app.create_export_template_variable();
+        app.rename_export_template_variable("variable_1", "namespace");
+        app.set_export_template_variable_value("namespace", "game");
+        app.create_export_template_variable();
+        app.rename_export_template_variable("variable_1", "pixels_per_unit");
+        app.set_export_template_variable_value("pixels_per_unit", "16");
+        app.end_export_as().await;
+
+        let exported = std::fs::read_to_string(metadata_file).unwrap();
+        assert!(exported.contains("pixels_per_unit = 16"));
+        assert!(exported.contains("namespace: game"));
+    }
+
     #[tokio::test]
     async fn template_examples_match_known_output() {
         let atlas_file =
@@ -438,8 +706,393 @@ mod tests {
             let absolute_path = PathBuf::from(path).canonicalize().unwrap();
             d.set_export_template_file(absolute_path).unwrap();
             let ExportSettingsValidation::Template(validation) =
-                d.validate_export_settings().unwrap();
+                d.validate_export_settings().unwrap()
+            else {
+                panic!("Unexpected export format");
+            };
             assert!(test(validation.template_file_error));
         }
     }
+
+    #[test]
+    fn validates_exporter_file() {
+        type Test = fn(e: Option<ExportSettingsError>) -> bool;
+        let test_table: Vec<(&str, Test)> = vec![
+            ("test-data/exporter.bat", |e| e.is_none()),
+            ("test-data/samurai", |e| {
+                matches!(e, Some(ExportSettingsError::ExpectedFile))
+            }),
+            ("test-data/missing.bat", |e| {
+                matches!(e, Some(ExportSettingsError::FileNotFound))
+            }),
+        ];
+
+        let mut d = Document::new("tmp.tiger");
+        d.begin_export_as();
+        d.set_export_format(ExportFormat::Exporter).unwrap();
+        assert!(d.set_export_template_file("format.template").is_err());
+        for (path, test) in test_table {
+            d.set_export_exporter_file(PathBuf::from(path).resolve())
+                .unwrap();
+            let ExportSettingsValidation::Exporter(validation) =
+                d.validate_export_settings().unwrap()
+            else {
+                panic!("Unexpected export format");
+            };
+            assert!(test(validation.exporter_file_error));
+        }
+    }
+
+    #[tokio::test]
+    async fn can_export_with_external_exporter() {
+        let atlas_image_file =
+            PathBuf::from("test-output/can_export_with_external_exporter.png").resolve();
+        let metadata_file =
+            PathBuf::from("test-output/can_export_with_external_exporter.json").resolve();
+        std::fs::remove_file(&metadata_file).ok();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_format(dto::ExportFormat::Exporter);
+        app.set_export_exporter_file(PathBuf::from("test-data/exporter.bat").resolve());
+        app.allow_exporter("test-data/exporter.bat");
+        app.set_export_atlas_image_file(&atlas_image_file);
+        app.set_export_metadata_file(&metadata_file);
+        app.end_export_as().await;
+
+        let exported: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&metadata_file).unwrap()).unwrap();
+        assert_eq!(
+            exported["atlas_image_file"].as_str(),
+            atlas_image_file.to_str()
+        );
+        assert_eq!(exported["animations"].as_array().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn notifies_of_external_exporter_error() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_format(dto::ExportFormat::Exporter);
+        app.set_export_exporter_file(PathBuf::from("test-data/failing-exporter.bat").resolve());
+        app.allow_exporter("test-data/failing-exporter.bat");
+        app.set_export_atlas_image_file(
+            PathBuf::from("test-output/notifies_of_external_exporter_error.png").resolve(),
+        );
+        app.set_export_metadata_file(
+            PathBuf::from("test-output/notifies_of_external_exporter_error.json").resolve(),
+        );
+        app.end_export_as().await;
+        assert!(app.events().into_iter().any(|(event, payload)| {
+            event.as_str() == dto::EVENT_EXPORT_ERROR
+                && serde_json::from_value::<dto::ExportError>(payload)
+                    .unwrap()
+                    .error
+                    .contains("Unsupported sheet")
+        }));
+    }
+
+    #[tokio::test]
+    async fn does_not_run_exporters_which_were_not_allowed() {
+        let metadata_file =
+            PathBuf::from("test-output/does_not_run_exporters_which_were_not_allowed.json")
+                .resolve();
+        std::fs::remove_file(&metadata_file).ok();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_format(dto::ExportFormat::Exporter);
+        app.set_export_exporter_file(PathBuf::from("test-data/exporter.bat").resolve());
+        app.set_export_atlas_image_file(
+            PathBuf::from("test-output/does_not_run_exporters_which_were_not_allowed.png")
+                .resolve(),
+        );
+        app.set_export_metadata_file(&metadata_file);
+        app.end_export_as().await;
+
+        assert!(!metadata_file.exists());
+        assert!(app.events().into_iter().any(|(event, payload)| {
+            event.as_str() == dto::EVENT_EXPORT_ERROR
+                && serde_json::from_value::<dto::ExportError>(payload)
+                    .unwrap()
+                    .error
+                    .contains("has not been allowed")
+        }));
+    }
+
+    #[tokio::test]
+    async fn reports_post_export_command_output() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_post_export_commands(vec!["echo done".to_owned(), " ".to_owned()]);
+        app.end_export_as().await;
+        assert!(app.events().into_iter().any(|(event, payload)| {
+            event.as_str() == dto::EVENT_EXPORT_SUCCESS
+                && serde_json::from_value::<dto::ExportSuccess>(payload)
+                    .unwrap()
+                    .post_export_commands
+                    .iter()
+                    .any(|c| c.output == "done" && c.exit_code == Some(0))
+        }));
+    }
+
+    #[tokio::test]
+    async fn notifies_of_post_export_command_error() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_post_export_commands(vec!["echo Upload failed && exit 1".to_owned()]);
+        app.end_export_as().await;
+        assert!(app.events().into_iter().any(|(event, payload)| {
+            event.as_str() == dto::EVENT_EXPORT_ERROR
+                && serde_json::from_value::<dto::ExportError>(payload)
+                    .unwrap()
+                    .error
+                    .contains("Upload failed")
+        }));
+    }
+
+    fn count_export_events(app: &TigerAppMock) -> usize {
+        app.events()
+            .into_iter()
+            .filter(|(event, _)| {
+                event.as_str() == dto::EVENT_EXPORT_SUCCESS
+                    || event.as_str() == dto::EVENT_EXPORT_ERROR
+            })
+            .count()
+    }
+
+    #[tokio::test]
+    async fn exports_on_save_when_enabled() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.save_as("test-output/exports_on_save_when_enabled.tiger")
+            .await;
+        assert_eq!(count_export_events(&app), 0);
+
+        app.begin_export_as();
+        app.set_export_on_save(true);
+        app.end_export_as().await;
+        assert_eq!(count_export_events(&app), 1);
+
+        app.save().await;
+        assert_eq!(count_export_events(&app), 2);
+        assert!(app
+            .events()
+            .into_iter()
+            .all(|(event, _)| event.as_str() != dto::EVENT_EXPORT_ERROR));
+    }
+
+    #[tokio::test]
+    async fn skips_export_on_save_with_invalid_settings() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.save_as("test-output/skips_export_on_save_with_invalid_settings.tiger")
+            .await;
+
+        app.begin_export_as();
+        app.set_export_on_save(true);
+        app.set_export_template_file(PathBuf::from("test-data/missing.template").resolve());
+        app.end_export_as().await;
+        assert_eq!(count_export_events(&app), 1);
+
+        app.save().await;
+        assert_eq!(count_export_events(&app), 1);
+    }
+
+    #[tokio::test]
+    async fn can_preview_export() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        let dto::ExportPreview::Output(preview) = app.preview_export().await else {
+            panic!("Expected export preview output");
+        };
+        assert_eq!(
+            preview.metadata,
+            std::fs::read_to_string("test-data/samurai.export").unwrap()
+        );
+        assert_eq!(
+            (preview.atlas_image_width, preview.atlas_image_height),
+            image::image_dimensions("test-data/samurai.png").unwrap()
+        );
+        assert!(preview.atlas_image.starts_with("data:image/png;base64,"));
+    }
+
+    #[tokio::test]
+    async fn export_preview_uses_settings_being_edited() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_template_file(PathBuf::from("test-data/only-frames.template").resolve());
+        let dto::ExportPreview::Output(preview) = app.preview_export().await else {
+            panic!("Expected export preview output");
+        };
+        assert_ne!(
+            preview.metadata,
+            std::fs::read_to_string("test-data/samurai.export").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn export_preview_reports_missing_settings() {
+        let app = TigerAppMock::new();
+        app.new_document("tmp.tiger");
+        assert!(matches!(
+            app.preview_export().await,
+            dto::ExportPreview::Error(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn export_preview_reports_invalid_settings() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_template_file(PathBuf::from("test-data/missing.template").resolve());
+        assert!(matches!(
+            app.preview_export().await,
+            dto::ExportPreview::Error(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn export_preview_does_not_run_exporters() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_format(dto::ExportFormat::Exporter);
+        app.set_export_exporter_file(PathBuf::from("test-data/failing-exporter.bat").resolve());
+        app.allow_exporter("test-data/failing-exporter.bat");
+        app.set_export_atlas_image_file(
+            PathBuf::from("test-output/export_preview_does_not_run_exporters.png").resolve(),
+        );
+        app.set_export_metadata_file(
+            PathBuf::from("test-output/export_preview_does_not_run_exporters.json").resolve(),
+        );
+        let dto::ExportPreview::Output(preview) = app.preview_export().await else {
+            panic!("Expected export preview output");
+        };
+        let exporter_input: serde_json::Value = serde_json::from_str(&preview.metadata).unwrap();
+        assert_eq!(exporter_input["animations"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn export_preview_cancels_previous_preview() {
+        let mut d = Document::new("tmp.tiger");
+        let first_preview = d.begin_export_preview();
+        let second_preview = d.begin_export_preview();
+        assert!(first_preview.is_cancelled());
+        assert!(!second_preview.is_cancelled());
+    }
+
+    fn modified_time<P: AsRef<Path>>(path: P) -> std::time::SystemTime {
+        std::fs::metadata(path).unwrap().modified().unwrap()
+    }
+
+    #[tokio::test]
+    async fn skips_export_when_nothing_changed() {
+        let atlas_image_file =
+            PathBuf::from("test-output/skips_export_when_nothing_changed.png").resolve();
+        let metadata_file =
+            PathBuf::from("test-output/skips_export_when_nothing_changed.export").resolve();
+        std::fs::remove_file(&atlas_image_file).ok();
+        std::fs::remove_file(&metadata_file).ok();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_atlas_image_file(&atlas_image_file);
+        app.set_export_metadata_file(&metadata_file);
+        app.end_export_as().await;
+        let atlas_image_time = modified_time(&atlas_image_file);
+        let metadata_time = modified_time(&metadata_file);
+        let metadata = std::fs::read_to_string(&metadata_file).unwrap();
+
+        app.export().await;
+        assert_eq!(modified_time(&atlas_image_file), atlas_image_time);
+        assert_eq!(modified_time(&metadata_file), metadata_time);
+
+        std::fs::write(&metadata_file, "tampered").unwrap();
+        app.export().await;
+        assert_eq!(std::fs::read_to_string(&metadata_file).unwrap(), metadata);
+        assert_eq!(modified_time(&atlas_image_file), atlas_image_time);
+    }
+
+    #[tokio::test]
+    async fn only_rewrites_changed_export_outputs() {
+        let atlas_image_file =
+            PathBuf::from("test-output/only_rewrites_changed_export_outputs.png").resolve();
+        let metadata_file =
+            PathBuf::from("test-output/only_rewrites_changed_export_outputs.export").resolve();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_atlas_image_file(&atlas_image_file);
+        app.set_export_metadata_file(&metadata_file);
+        app.end_export_as().await;
+        let atlas_image_time = modified_time(&atlas_image_file);
+        let metadata = std::fs::read_to_string(&metadata_file).unwrap();
+
+        app.begin_export_as();
+        app.set_export_template_file(PathBuf::from("test-data/only-frames.template").resolve());
+        app.end_export_as().await;
+        assert_eq!(modified_time(&atlas_image_file), atlas_image_time);
+        assert_ne!(std::fs::read_to_string(&metadata_file).unwrap(), metadata);
+    }
+
+    #[tokio::test]
+    async fn reports_export_progress() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_atlas_image_file(
+            PathBuf::from("test-output/reports_export_progress.png").resolve(),
+        );
+        app.set_export_metadata_file(
+            PathBuf::from("test-output/reports_export_progress.export").resolve(),
+        );
+        app.end_export_as().await;
+
+        let stages = app
+            .events()
+            .into_iter()
+            .filter(|(event, _)| event.as_str() == dto::EVENT_EXPORT_PROGRESS)
+            .map(|(_, payload)| serde_json::from_value::<dto::ExportProgress>(payload).unwrap())
+            .map(|progress| progress.stage)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stages,
+            vec![
+                dto::ExportStage::LoadingTextures,
+                dto::ExportStage::Packing,
+                dto::ExportStage::Compositing,
+                dto::ExportStage::RenderingTemplate,
+                dto::ExportStage::Writing,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn can_cancel_export() {
+        use crate::export::{export_sheet, CancellationToken, ExportError, ExportProgress};
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        let (sheet, sheet_path) = {
+            let state_handle = app.state();
+            let state = state_handle.lock();
+            let document = state.current_document().unwrap();
+            (document.sheet().clone(), document.path().to_owned())
+        };
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+        let progress = ExportProgress::new(|_| (), cancellation_token);
+        let result = export_sheet(&sheet, &sheet_path, app.texture_cache(), None, &progress);
+        assert!(matches!(result, Err(ExportError::Cancelled)));
+    }
 }
@@ -0,0 +1,754 @@
+use euclid::vec2;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::document::*;
+
+// Stands for the animation a macro is played on
+pub const MACRO_ANIMATION_PARAMETER: &str = "{animation}";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Macro {
+    steps: Vec<MacroStep>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MacroRecording {
+    animation: Option<String>, // Animation being edited when recording started
+    steps: Vec<MacroStep>,
+}
+
+// A command as stored in a macro. This is persisted to disk, so changes to
+// existing variants need a new version of the macros file format.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MacroStep {
+    Batch {
+        label: String,
+        steps: Vec<MacroStep>,
+    },
+    ImportFrames(Vec<PathBuf>),
+    DeleteFrame(PathBuf),
+    DeleteSelectedFrames,
+    DeleteSelection,
+    NudgeSelection {
+        direction: MacroNudgeDirection,
+        large_nudge: bool,
+    },
+    BrowseSelection {
+        direction: MacroBrowseDirection,
+        shift: bool,
+    },
+    BrowseToEnd(bool),
+    BrowseToStart(bool),
+    ClearSelection,
+    SelectAll,
+    SelectFrame {
+        path: PathBuf,
+        shift: bool,
+        ctrl: bool,
+    },
+    SelectAnimation {
+        name: String,
+        shift: bool,
+        ctrl: bool,
+    },
+    SelectKeyframe {
+        direction: MacroDirection,
+        index: usize,
+        shift: bool,
+        ctrl: bool,
+    },
+    SelectHitbox {
+        name: String,
+        shift: bool,
+        ctrl: bool,
+    },
+    CreateAnimation,
+    EditAnimation(String),
+    BeginRenameSelection,
+    BeginRenameAnimation(String),
+    BeginRenameHitbox(String),
+    CancelRename,
+    EndRenameAnimation(String),
+    EndRenameHitbox(String),
+    DeleteAnimation(String),
+    DeleteSelectedAnimations,
+    ScrubTimeline {
+        time_millis: u64,
+    },
+    JumpToAnimationStart,
+    JumpToAnimationEnd,
+    JumpToPreviousFrame,
+    JumpToNextFrame,
+    SetAnimationLooping(bool),
+    ApplyDirectionPreset(MacroDirectionPreset),
+    SelectDirection(MacroDirection),
+    BeginDragAndDropFrame(PathBuf),
+    DropFrameOnTimeline {
+        direction: MacroDirection,
+        index: usize,
+    },
+    EndDragAndDropFrame,
+    DeleteSelectedKeyframes,
+    SetKeyframeDuration {
+        duration_millis: u64,
+    },
+    SetKeyframeOffsetX(i32),
+    SetKeyframeOffsetY(i32),
+    BeginDragAndDropKeyframe {
+        direction: MacroDirection,
+        index: usize,
+    },
+    DropKeyframeOnTimeline {
+        direction: MacroDirection,
+        index: usize,
+    },
+    EndDragAndDropKeyframe,
+    BeginDragKeyframeDuration {
+        direction: MacroDirection,
+        index: usize,
+    },
+    UpdateDragKeyframeDuration(i64),
+    EndDragKeyframeDuration,
+    BeginNudgeKeyframe {
+        direction: MacroDirection,
+        index: usize,
+    },
+    UpdateNudgeKeyframe {
+        displacement: (i32, i32),
+        both_axis: bool,
+    },
+    EndNudgeKeyframe,
+    CreateHitbox,
+    DeleteHitbox(String),
+    DeleteSelectedHitboxes,
+    LockHitboxes,
+    UnlockHitboxes,
+    SetHitboxPositionX(i32),
+    SetHitboxPositionY(i32),
+    SetHitboxWidth(u32),
+    SetHitboxHeight(u32),
+    TogglePreserveAspectRatio,
+    BeginNudgeHitbox(String),
+    UpdateNudgeHitbox {
+        displacement: (i32, i32),
+        both_axis: bool,
+    },
+    EndNudgeHitbox,
+    BeginResizeHitbox {
+        name: String,
+        axis: MacroResizeAxis,
+    },
+    UpdateResizeHitbox {
+        displacement: (i32, i32),
+        preserve_aspect_ratio: bool,
+    },
+    EndResizeHitbox,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MacroDirection {
+    East,
+    NorthEast,
+    North,
+    NorthWest,
+    West,
+    SouthWest,
+    South,
+    SouthEast,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MacroDirectionPreset {
+    FourDirections,
+    EightDirections,
+    LeftRight,
+    UpDown,
+    Isometric,
+    FixedAngle,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MacroNudgeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MacroBrowseDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MacroResizeAxis {
+    N,
+    S,
+    W,
+    E,
+    NW,
+    NE,
+    SE,
+    SW,
+}
+
+impl Document {
+    pub fn begin_macro_recording(&mut self) {
+        self.persistent.macro_recording = Some(MacroRecording {
+            animation: self.view.current_animation.clone(),
+            steps: Vec::new(),
+        });
+    }
+
+    pub fn cancel_macro_recording(&mut self) {
+        self.persistent.macro_recording = None;
+    }
+
+    pub fn end_macro_recording(&mut self) -> Option<Macro> {
+        let recording = self.persistent.macro_recording.take()?;
+        let steps = recording
+            .steps
+            .into_iter()
+            .map(|step| match &recording.animation {
+                Some(animation) => step.with_animation_names(&|name| {
+                    if name == animation {
+                        MACRO_ANIMATION_PARAMETER.to_owned()
+                    } else {
+                        name.to_owned()
+                    }
+                }),
+                None => step,
+            })
+            .collect();
+        Some(Macro { steps })
+    }
+
+    pub fn is_recording_macro(&self) -> bool {
+        self.persistent.macro_recording.is_some()
+    }
+
+    // Commands without a macro step equivalent (undo, navigation, view
+    // settings, etc.) are left out of the recording
+    pub(super) fn record_macro_step(&mut self, command: &Command) {
+        if let Some(recording) = &mut self.persistent.macro_recording {
+            if let Ok(step) = MacroStep::try_from(command) {
+                recording.steps.push(step);
+            }
+        }
+    }
+
+    // Animations a macro applies to when none are specified
+    pub fn macro_targets(&self) -> Vec<String> {
+        let mut animations = self
+            .view
+            .selection
+            .animations()
+            .cloned()
+            .collect::<Vec<_>>();
+        if animations.is_empty() {
+            animations.extend(self.view.current_animation.clone());
+        }
+        animations.sort();
+        animations
+    }
+
+    pub fn play_macro<S: Into<String>>(
+        &mut self,
+        name: S,
+        recorded_macro: &Macro,
+        animations: Vec<String>,
+    ) -> DocumentResult<()> {
+        let commands = animations
+            .into_iter()
+            .flat_map(|animation| {
+                std::iter::once(Command::EditAnimation(animation.clone())).chain(
+                    recorded_macro.steps.iter().map(move |step| {
+                        Command::from(&step.with_animation_names(&|name| {
+                            name.replace(MACRO_ANIMATION_PARAMETER, &animation)
+                        }))
+                    }),
+                )
+            })
+            .collect();
+        self.process_commands(name, commands)
+    }
+}
+
+impl MacroStep {
+    fn with_animation_names(&self, substitute: &dyn Fn(&str) -> String) -> MacroStep {
+        match self {
+            MacroStep::Batch { label, steps } => MacroStep::Batch {
+                label: label.clone(),
+                steps: steps
+                    .iter()
+                    .map(|s| s.with_animation_names(substitute))
+                    .collect(),
+            },
+            MacroStep::EditAnimation(name) => MacroStep::EditAnimation(substitute(name)),
+            MacroStep::SelectAnimation { name, shift, ctrl } => MacroStep::SelectAnimation {
+                name: substitute(name),
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            MacroStep::BeginRenameAnimation(name) => {
+                MacroStep::BeginRenameAnimation(substitute(name))
+            }
+            MacroStep::EndRenameAnimation(name) => MacroStep::EndRenameAnimation(substitute(name)),
+            MacroStep::DeleteAnimation(name) => MacroStep::DeleteAnimation(substitute(name)),
+            step => step.clone(),
+        }
+    }
+}
+
+impl TryFrom<&Command> for MacroStep {
+    type Error = ();
+
+    fn try_from(command: &Command) -> Result<Self, Self::Error> {
+        Ok(match command {
+            Command::Batch { label, commands } => MacroStep::Batch {
+                label: label.clone(),
+                steps: commands
+                    .iter()
+                    .filter_map(|c| MacroStep::try_from(c).ok())
+                    .collect(),
+            },
+            Command::ImportFrames(paths) => MacroStep::ImportFrames(paths.clone()),
+            Command::DeleteFrame(path) => MacroStep::DeleteFrame(path.clone()),
+            Command::DeleteSelectedFrames => MacroStep::DeleteSelectedFrames,
+            Command::DeleteSelection => MacroStep::DeleteSelection,
+            Command::NudgeSelection {
+                direction,
+                large_nudge,
+            } => MacroStep::NudgeSelection {
+                direction: (*direction).into(),
+                large_nudge: *large_nudge,
+            },
+            Command::BrowseSelection { direction, shift } => MacroStep::BrowseSelection {
+                direction: (*direction).into(),
+                shift: *shift,
+            },
+            Command::BrowseToEnd(shift) => MacroStep::BrowseToEnd(*shift),
+            Command::BrowseToStart(shift) => MacroStep::BrowseToStart(*shift),
+            Command::ClearSelection => MacroStep::ClearSelection,
+            Command::SelectAll => MacroStep::SelectAll,
+            Command::SelectFrame { path, shift, ctrl } => MacroStep::SelectFrame {
+                path: path.clone(),
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            Command::SelectAnimation { name, shift, ctrl } => MacroStep::SelectAnimation {
+                name: name.clone(),
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            Command::SelectKeyframe {
+                direction,
+                index,
+                shift,
+                ctrl,
+            } => MacroStep::SelectKeyframe {
+                direction: (*direction).into(),
+                index: *index,
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            Command::SelectHitbox { name, shift, ctrl } => MacroStep::SelectHitbox {
+                name: name.clone(),
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            Command::CreateAnimation => MacroStep::CreateAnimation,
+            Command::EditAnimation(name) => MacroStep::EditAnimation(name.clone()),
+            Command::BeginRenameSelection => MacroStep::BeginRenameSelection,
+            Command::BeginRenameAnimation(name) => MacroStep::BeginRenameAnimation(name.clone()),
+            Command::BeginRenameHitbox(name) => MacroStep::BeginRenameHitbox(name.clone()),
+            Command::CancelRename => MacroStep::CancelRename,
+            Command::EndRenameAnimation(name) => MacroStep::EndRenameAnimation(name.clone()),
+            Command::EndRenameHitbox(name) => MacroStep::EndRenameHitbox(name.clone()),
+            Command::DeleteAnimation(name) => MacroStep::DeleteAnimation(name.clone()),
+            Command::DeleteSelectedAnimations => MacroStep::DeleteSelectedAnimations,
+            Command::ScrubTimeline(time) => MacroStep::ScrubTimeline {
+                time_millis: time.as_millis() as u64,
+            },
+            Command::JumpToAnimationStart => MacroStep::JumpToAnimationStart,
+            Command::JumpToAnimationEnd => MacroStep::JumpToAnimationEnd,
+            Command::JumpToPreviousFrame => MacroStep::JumpToPreviousFrame,
+            Command::JumpToNextFrame => MacroStep::JumpToNextFrame,
+            Command::SetAnimationLooping(is_looping) => MacroStep::SetAnimationLooping(*is_looping),
+            Command::ApplyDirectionPreset(preset) => {
+                MacroStep::ApplyDirectionPreset((*preset).into())
+            }
+            Command::SelectDirection(direction) => MacroStep::SelectDirection((*direction).into()),
+            Command::BeginDragAndDropFrame(path) => MacroStep::BeginDragAndDropFrame(path.clone()),
+            Command::DropFrameOnTimeline { direction, index } => MacroStep::DropFrameOnTimeline {
+                direction: (*direction).into(),
+                index: *index,
+            },
+            Command::EndDragAndDropFrame => MacroStep::EndDragAndDropFrame,
+            Command::DeleteSelectedKeyframes => MacroStep::DeleteSelectedKeyframes,
+            Command::SetKeyframeDuration(duration) => MacroStep::SetKeyframeDuration {
+                duration_millis: duration.as_millis() as u64,
+            },
+            Command::SetKeyframeOffsetX(x) => MacroStep::SetKeyframeOffsetX(*x),
+            Command::SetKeyframeOffsetY(y) => MacroStep::SetKeyframeOffsetY(*y),
+            Command::BeginDragAndDropKeyframe { direction, index } => {
+                MacroStep::BeginDragAndDropKeyframe {
+                    direction: (*direction).into(),
+                    index: *index,
+                }
+            }
+            Command::DropKeyframeOnTimeline { direction, index } => {
+                MacroStep::DropKeyframeOnTimeline {
+                    direction: (*direction).into(),
+                    index: *index,
+                }
+            }
+            Command::EndDragAndDropKeyframe => MacroStep::EndDragAndDropKeyframe,
+            Command::BeginDragKeyframeDuration { direction, index } => {
+                MacroStep::BeginDragKeyframeDuration {
+                    direction: (*direction).into(),
+                    index: *index,
+                }
+            }
+            Command::UpdateDragKeyframeDuration(delta_millis) => {
+                MacroStep::UpdateDragKeyframeDuration(*delta_millis)
+            }
+            Command::EndDragKeyframeDuration => MacroStep::EndDragKeyframeDuration,
+            Command::BeginNudgeKeyframe { direction, index } => MacroStep::BeginNudgeKeyframe {
+                direction: (*direction).into(),
+                index: *index,
+            },
+            Command::UpdateNudgeKeyframe {
+                displacement,
+                both_axis,
+            } => MacroStep::UpdateNudgeKeyframe {
+                displacement: (displacement.x, displacement.y),
+                both_axis: *both_axis,
+            },
+            Command::EndNudgeKeyframe => MacroStep::EndNudgeKeyframe,
+            Command::CreateHitbox => MacroStep::CreateHitbox,
+            Command::DeleteHitbox(name) => MacroStep::DeleteHitbox(name.clone()),
+            Command::DeleteSelectedHitboxes => MacroStep::DeleteSelectedHitboxes,
+            Command::LockHitboxes => MacroStep::LockHitboxes,
+            Command::UnlockHitboxes => MacroStep::UnlockHitboxes,
+            Command::SetHitboxPositionX(x) => MacroStep::SetHitboxPositionX(*x),
+            Command::SetHitboxPositionY(y) => MacroStep::SetHitboxPositionY(*y),
+            Command::SetHitboxWidth(width) => MacroStep::SetHitboxWidth(*width),
+            Command::SetHitboxHeight(height) => MacroStep::SetHitboxHeight(*height),
+            Command::TogglePreserveAspectRatio => MacroStep::TogglePreserveAspectRatio,
+            Command::BeginNudgeHitbox(name) => MacroStep::BeginNudgeHitbox(name.clone()),
+            Command::UpdateNudgeHitbox {
+                displacement,
+                both_axis,
+            } => MacroStep::UpdateNudgeHitbox {
+                displacement: (displacement.x, displacement.y),
+                both_axis: *both_axis,
+            },
+            Command::EndNudgeHitbox => MacroStep::EndNudgeHitbox,
+            Command::BeginResizeHitbox { name, axis } => MacroStep::BeginResizeHitbox {
+                name: name.clone(),
+                axis: (*axis).into(),
+            },
+            Command::UpdateResizeHitbox {
+                displacement,
+                preserve_aspect_ratio,
+            } => MacroStep::UpdateResizeHitbox {
+                displacement: (displacement.x, displacement.y),
+                preserve_aspect_ratio: *preserve_aspect_ratio,
+            },
+            Command::EndResizeHitbox => MacroStep::EndResizeHitbox,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl From<&MacroStep> for Command {
+    fn from(step: &MacroStep) -> Self {
+        match step {
+            MacroStep::Batch { label, steps } => Command::Batch {
+                label: label.clone(),
+                commands: steps.iter().map(|s| s.into()).collect(),
+            },
+            MacroStep::ImportFrames(paths) => Command::ImportFrames(paths.clone()),
+            MacroStep::DeleteFrame(path) => Command::DeleteFrame(path.clone()),
+            MacroStep::DeleteSelectedFrames => Command::DeleteSelectedFrames,
+            MacroStep::DeleteSelection => Command::DeleteSelection,
+            MacroStep::NudgeSelection {
+                direction,
+                large_nudge,
+            } => Command::NudgeSelection {
+                direction: (*direction).into(),
+                large_nudge: *large_nudge,
+            },
+            MacroStep::BrowseSelection { direction, shift } => Command::BrowseSelection {
+                direction: (*direction).into(),
+                shift: *shift,
+            },
+            MacroStep::BrowseToEnd(shift) => Command::BrowseToEnd(*shift),
+            MacroStep::BrowseToStart(shift) => Command::BrowseToStart(*shift),
+            MacroStep::ClearSelection => Command::ClearSelection,
+            MacroStep::SelectAll => Command::SelectAll,
+            MacroStep::SelectFrame { path, shift, ctrl } => Command::SelectFrame {
+                path: path.clone(),
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            MacroStep::SelectAnimation { name, shift, ctrl } => Command::SelectAnimation {
+                name: name.clone(),
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            MacroStep::SelectKeyframe {
+                direction,
+                index,
+                shift,
+                ctrl,
+            } => Command::SelectKeyframe {
+                direction: (*direction).into(),
+                index: *index,
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            MacroStep::SelectHitbox { name, shift, ctrl } => Command::SelectHitbox {
+                name: name.clone(),
+                shift: *shift,
+                ctrl: *ctrl,
+            },
+            MacroStep::CreateAnimation => Command::CreateAnimation,
+            MacroStep::EditAnimation(name) => Command::EditAnimation(name.clone()),
+            MacroStep::BeginRenameSelection => Command::BeginRenameSelection,
+            MacroStep::BeginRenameAnimation(name) => Command::BeginRenameAnimation(name.clone()),
+            MacroStep::BeginRenameHitbox(name) => Command::BeginRenameHitbox(name.clone()),
+            MacroStep::CancelRename => Command::CancelRename,
+            MacroStep::EndRenameAnimation(name) => Command::EndRenameAnimation(name.clone()),
+            MacroStep::EndRenameHitbox(name) => Command::EndRenameHitbox(name.clone()),
+            MacroStep::DeleteAnimation(name) => Command::DeleteAnimation(name.clone()),
+            MacroStep::DeleteSelectedAnimations => Command::DeleteSelectedAnimations,
+            MacroStep::ScrubTimeline { time_millis } => {
+                Command::ScrubTimeline(Duration::from_millis(*time_millis))
+            }
+            MacroStep::JumpToAnimationStart => Command::JumpToAnimationStart,
+            MacroStep::JumpToAnimationEnd => Command::JumpToAnimationEnd,
+            MacroStep::JumpToPreviousFrame => Command::JumpToPreviousFrame,
+            MacroStep::JumpToNextFrame => Command::JumpToNextFrame,
+            MacroStep::SetAnimationLooping(is_looping) => Command::SetAnimationLooping(*is_looping),
+            MacroStep::ApplyDirectionPreset(preset) => {
+                Command::ApplyDirectionPreset((*preset).into())
+            }
+            MacroStep::SelectDirection(direction) => Command::SelectDirection((*direction).into()),
+            MacroStep::BeginDragAndDropFrame(path) => Command::BeginDragAndDropFrame(path.clone()),
+            MacroStep::DropFrameOnTimeline { direction, index } => Command::DropFrameOnTimeline {
+                direction: (*direction).into(),
+                index: *index,
+            },
+            MacroStep::EndDragAndDropFrame => Command::EndDragAndDropFrame,
+            MacroStep::DeleteSelectedKeyframes => Command::DeleteSelectedKeyframes,
+            MacroStep::SetKeyframeDuration { duration_millis } => {
+                Command::SetKeyframeDuration(Duration::from_millis(*duration_millis))
+            }
+            MacroStep::SetKeyframeOffsetX(x) => Command::SetKeyframeOffsetX(*x),
+            MacroStep::SetKeyframeOffsetY(y) => Command::SetKeyframeOffsetY(*y),
+            MacroStep::BeginDragAndDropKeyframe { direction, index } => {
+                Command::BeginDragAndDropKeyframe {
+                    direction: (*direction).into(),
+                    index: *index,
+                }
+            }
+            MacroStep::DropKeyframeOnTimeline { direction, index } => {
+                Command::DropKeyframeOnTimeline {
+                    direction: (*direction).into(),
+                    index: *index,
+                }
+            }
+            MacroStep::EndDragAndDropKeyframe => Command::EndDragAndDropKeyframe,
+            MacroStep::BeginDragKeyframeDuration { direction, index } => {
+                Command::BeginDragKeyframeDuration {
+                    direction: (*direction).into(),
+                    index: *index,
+                }
+            }
+            MacroStep::UpdateDragKeyframeDuration(delta_millis) => {
+                Command::UpdateDragKeyframeDuration(*delta_millis)
+            }
+            MacroStep::EndDragKeyframeDuration => Command::EndDragKeyframeDuration,
+            MacroStep::BeginNudgeKeyframe { direction, index } => Command::BeginNudgeKeyframe {
+                direction: (*direction).into(),
+                index: *index,
+            },
+            MacroStep::UpdateNudgeKeyframe {
+                displacement,
+                both_axis,
+            } => Command::UpdateNudgeKeyframe {
+                displacement: vec2(displacement.0, displacement.1),
+                both_axis: *both_axis,
+            },
+            MacroStep::EndNudgeKeyframe => Command::EndNudgeKeyframe,
+            MacroStep::CreateHitbox => Command::CreateHitbox,
+            MacroStep::DeleteHitbox(name) => Command::DeleteHitbox(name.clone()),
+            MacroStep::DeleteSelectedHitboxes => Command::DeleteSelectedHitboxes,
+            MacroStep::LockHitboxes => Command::LockHitboxes,
+            MacroStep::UnlockHitboxes => Command::UnlockHitboxes,
+            MacroStep::SetHitboxPositionX(x) => Command::SetHitboxPositionX(*x),
+            MacroStep::SetHitboxPositionY(y) => Command::SetHitboxPositionY(*y),
+            MacroStep::SetHitboxWidth(width) => Command::SetHitboxWidth(*width),
+            MacroStep::SetHitboxHeight(height) => Command::SetHitboxHeight(*height),
+            MacroStep::TogglePreserveAspectRatio => Command::TogglePreserveAspectRatio,
+            MacroStep::BeginNudgeHitbox(name) => Command::BeginNudgeHitbox(name.clone()),
+            MacroStep::UpdateNudgeHitbox {
+                displacement,
+                both_axis,
+            } => Command::UpdateNudgeHitbox {
+                displacement: vec2(displacement.0, displacement.1),
+                both_axis: *both_axis,
+            },
+            MacroStep::EndNudgeHitbox => Command::EndNudgeHitbox,
+            MacroStep::BeginResizeHitbox { name, axis } => Command::BeginResizeHitbox {
+                name: name.clone(),
+                axis: (*axis).into(),
+            },
+            MacroStep::UpdateResizeHitbox {
+                displacement,
+                preserve_aspect_ratio,
+            } => Command::UpdateResizeHitbox {
+                displacement: vec2(displacement.0, displacement.1),
+                preserve_aspect_ratio: *preserve_aspect_ratio,
+            },
+            MacroStep::EndResizeHitbox => Command::EndResizeHitbox,
+        }
+    }
+}
+
+impl From<Direction> for MacroDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::East => MacroDirection::East,
+            Direction::NorthEast => MacroDirection::NorthEast,
+            Direction::North => MacroDirection::North,
+            Direction::NorthWest => MacroDirection::NorthWest,
+            Direction::West => MacroDirection::West,
+            Direction::SouthWest => MacroDirection::SouthWest,
+            Direction::South => MacroDirection::South,
+            Direction::SouthEast => MacroDirection::SouthEast,
+        }
+    }
+}
+
+impl From<MacroDirection> for Direction {
+    fn from(direction: MacroDirection) -> Self {
+        match direction {
+            MacroDirection::East => Direction::East,
+            MacroDirection::NorthEast => Direction::NorthEast,
+            MacroDirection::North => Direction::North,
+            MacroDirection::NorthWest => Direction::NorthWest,
+            MacroDirection::West => Direction::West,
+            MacroDirection::SouthWest => Direction::SouthWest,
+            MacroDirection::South => Direction::South,
+            MacroDirection::SouthEast => Direction::SouthEast,
+        }
+    }
+}
+
+impl From<DirectionPreset> for MacroDirectionPreset {
+    fn from(preset: DirectionPreset) -> Self {
+        match preset {
+            DirectionPreset::FourDirections => MacroDirectionPreset::FourDirections,
+            DirectionPreset::EightDirections => MacroDirectionPreset::EightDirections,
+            DirectionPreset::LeftRight => MacroDirectionPreset::LeftRight,
+            DirectionPreset::UpDown => MacroDirectionPreset::UpDown,
+            DirectionPreset::Isometric => MacroDirectionPreset::Isometric,
+            DirectionPreset::FixedAngle => MacroDirectionPreset::FixedAngle,
+        }
+    }
+}
+
+impl From<MacroDirectionPreset> for DirectionPreset {
+    fn from(preset: MacroDirectionPreset) -> Self {
+        match preset {
+            MacroDirectionPreset::FourDirections => DirectionPreset::FourDirections,
+            MacroDirectionPreset::EightDirections => DirectionPreset::EightDirections,
+            MacroDirectionPreset::LeftRight => DirectionPreset::LeftRight,
+            MacroDirectionPreset::UpDown => DirectionPreset::UpDown,
+            MacroDirectionPreset::Isometric => DirectionPreset::Isometric,
+            MacroDirectionPreset::FixedAngle => DirectionPreset::FixedAngle,
+        }
+    }
+}
+
+impl From<NudgeDirection> for MacroNudgeDirection {
+    fn from(direction: NudgeDirection) -> Self {
+        match direction {
+            NudgeDirection::Up => MacroNudgeDirection::Up,
+            NudgeDirection::Down => MacroNudgeDirection::Down,
+            NudgeDirection::Left => MacroNudgeDirection::Left,
+            NudgeDirection::Right => MacroNudgeDirection::Right,
+        }
+    }
+}
+
+impl From<MacroNudgeDirection> for NudgeDirection {
+    fn from(direction: MacroNudgeDirection) -> Self {
+        match direction {
+            MacroNudgeDirection::Up => NudgeDirection::Up,
+            MacroNudgeDirection::Down => NudgeDirection::Down,
+            MacroNudgeDirection::Left => NudgeDirection::Left,
+            MacroNudgeDirection::Right => NudgeDirection::Right,
+        }
+    }
+}
+
+impl From<BrowseDirection> for MacroBrowseDirection {
+    fn from(direction: BrowseDirection) -> Self {
+        match direction {
+            BrowseDirection::Up => MacroBrowseDirection::Up,
+            BrowseDirection::Down => MacroBrowseDirection::Down,
+            BrowseDirection::Left => MacroBrowseDirection::Left,
+            BrowseDirection::Right => MacroBrowseDirection::Right,
+        }
+    }
+}
+
+impl From<MacroBrowseDirection> for BrowseDirection {
+    fn from(direction: MacroBrowseDirection) -> Self {
+        match direction {
+            MacroBrowseDirection::Up => BrowseDirection::Up,
+            MacroBrowseDirection::Down => BrowseDirection::Down,
+            MacroBrowseDirection::Left => BrowseDirection::Left,
+            MacroBrowseDirection::Right => BrowseDirection::Right,
+        }
+    }
+}
+
+impl From<ResizeAxis> for MacroResizeAxis {
+    fn from(axis: ResizeAxis) -> Self {
+        match axis {
+            ResizeAxis::N => MacroResizeAxis::N,
+            ResizeAxis::S => MacroResizeAxis::S,
+            ResizeAxis::W => MacroResizeAxis::W,
+            ResizeAxis::E => MacroResizeAxis::E,
+            ResizeAxis::NW => MacroResizeAxis::NW,
+            ResizeAxis::NE => MacroResizeAxis::NE,
+            ResizeAxis::SE => MacroResizeAxis::SE,
+            ResizeAxis::SW => MacroResizeAxis::SW,
+        }
+    }
+}
+
+impl From<MacroResizeAxis> for ResizeAxis {
+    fn from(axis: MacroResizeAxis) -> Self {
+        match axis {
+            MacroResizeAxis::N => ResizeAxis::N,
+            MacroResizeAxis::S => ResizeAxis::S,
+            MacroResizeAxis::W => ResizeAxis::W,
+            MacroResizeAxis::E => ResizeAxis::E,
+            MacroResizeAxis::NW => ResizeAxis::NW,
+            MacroResizeAxis::NE => ResizeAxis::NE,
+            MacroResizeAxis::SE => ResizeAxis::SE,
+            MacroResizeAxis::SW => ResizeAxis::SW,
+        }
+    }
+}
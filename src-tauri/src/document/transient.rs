@@ -51,7 +51,7 @@ pub(super) enum Rename {
     Hitbox(String),
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Transient {
     pub(super) rename: Option<Rename>,
     pub(super) frame_drag_and_drop: Option<PathBuf>,
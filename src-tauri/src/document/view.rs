@@ -1,11 +1,12 @@
 use euclid::default::*;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::time::Duration;
 
 use crate::document::*;
 use crate::sheet::Direction;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ListMode {
     Linear,
     Grid4xN,
@@ -39,6 +40,29 @@ pub struct View {
     pub(super) workbench_zoom_factor: u32,
 }
 
+// Subset of view state which is remembered between sessions
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct SavedView {
+    pub(super) animations_filter: String,
+    pub(super) current_animation: Option<String>,
+    pub(super) current_sequence: Option<Direction>,
+    pub(super) frames_filter: String,
+    pub(super) frames_list_mode: ListMode,
+    pub(super) keyframe_snapping_base_duration_millis: u64,
+    pub(super) snap_keyframe_durations: bool,
+    pub(super) snap_keyframes_to_multiples_of_duration: bool,
+    pub(super) snap_keyframes_to_other_keyframes: bool,
+    pub(super) timeline_zoom_amount: f32,
+    pub(super) workbench_zoom_factor: u32,
+}
+
+impl Default for SavedView {
+    fn default() -> Self {
+        View::default().saved()
+    }
+}
+
 impl Default for View {
     fn default() -> View {
         View {
@@ -71,6 +95,39 @@ impl Default for View {
 }
 
 impl View {
+    pub(super) fn saved(&self) -> SavedView {
+        SavedView {
+            animations_filter: self.animations_filter.clone(),
+            current_animation: self.current_animation.clone(),
+            current_sequence: self.current_sequence,
+            frames_filter: self.frames_filter.clone(),
+            frames_list_mode: self.frames_list_mode,
+            keyframe_snapping_base_duration_millis: self.keyframe_snapping_base_duration.as_millis()
+                as u64,
+            snap_keyframe_durations: self.snap_keyframe_durations,
+            snap_keyframes_to_multiples_of_duration: self.snap_keyframes_to_multiples_of_duration,
+            snap_keyframes_to_other_keyframes: self.snap_keyframes_to_other_keyframes,
+            timeline_zoom_amount: self.timeline_zoom_amount,
+            workbench_zoom_factor: self.workbench_zoom_factor,
+        }
+    }
+
+    // Current animation and sequence are not restored here because they
+    // need to be validated against the sheet being edited
+    pub(super) fn restore(&mut self, saved_view: &SavedView) {
+        self.animations_filter = saved_view.animations_filter.clone();
+        self.frames_filter = saved_view.frames_filter.clone();
+        self.frames_list_mode = saved_view.frames_list_mode;
+        self.keyframe_snapping_base_duration =
+            Duration::from_millis(saved_view.keyframe_snapping_base_duration_millis.max(1));
+        self.snap_keyframe_durations = saved_view.snap_keyframe_durations;
+        self.snap_keyframes_to_multiples_of_duration =
+            saved_view.snap_keyframes_to_multiples_of_duration;
+        self.snap_keyframes_to_other_keyframes = saved_view.snap_keyframes_to_other_keyframes;
+        self.set_timeline_zoom_amount(saved_view.timeline_zoom_amount);
+        self.set_workbench_zoom_factor(saved_view.workbench_zoom_factor);
+    }
+
     pub(super) fn zoom_in_workbench(&mut self) {
         self.set_workbench_zoom_factor(self.workbench_zoom_factor * 2);
     }
@@ -196,6 +253,10 @@ impl View {
 }
 
 impl Document {
+    pub fn saved_view(&self) -> SavedView {
+        self.view.saved()
+    }
+
     pub fn frames_list_mode(&self) -> ListMode {
         self.view.frames_list_mode
     }
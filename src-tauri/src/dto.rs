@@ -5,7 +5,8 @@ use std::path::PathBuf;
 use uuid::Uuid;
 
 use crate::document::{self};
-use crate::features::{app_updates, onboarding};
+use crate::export;
+use crate::features::{app_updates, backups, onboarding, settings, texture_cache};
 use crate::sheet::{self, Paths};
 use crate::state;
 
@@ -13,8 +14,11 @@ use crate::state;
 
 pub static EVENT_APP_UPDATE_ERROR: &str = "app-update-error";
 pub static EVENT_APP_UPDATE_SUCCESS: &str = "app-update-success";
+pub static EVENT_EXPORT_CANCELLED: &str = "export-cancelled";
 pub static EVENT_EXPORT_ERROR: &str = "export-error";
+pub static EVENT_EXPORT_PROGRESS: &str = "export-progress";
 pub static EVENT_EXPORT_SUCCESS: &str = "export-success";
+pub static EVENT_EXPORT_TEMPLATE_CHANGED: &str = "export-template-changed";
 pub static EVENT_INVALIDATE_TEXTURE: &str = "invalidate-texture";
 pub static EVENT_OPEN_DOCUMENT_ERROR: &str = "open-document-error";
 pub static EVENT_PATCH_STATE: &str = "patch-state";
@@ -27,6 +31,7 @@ pub struct State {
     pub documents: Vec<Document>,
     pub current_document_path: Option<PathBuf>,
     pub recent_document_paths: Vec<RecentDocument>,
+    pub recoverable_documents: Vec<RecoverableDocument>,
     pub clipboard_manifest: Option<ClipboardManifest>,
     pub is_release_build: bool,
     pub error: Option<UserFacingError>,
@@ -35,6 +40,46 @@ pub struct State {
     pub update_step: UpdateStep,
     pub about_dialog_open: bool,
     pub commit_hash: Option<String>,
+    pub settings: Settings,
+    pub macros: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    pub allowed_exporters: Vec<PathBuf>,
+    pub automation_server: bool,
+    pub history_limit: usize,
+    pub max_backups: usize,
+    pub persist_history: bool,
+    pub post_export_commands: Vec<String>,
+    pub restore_views: bool,
+    pub texture_cache_budget_mb: usize,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes_used: usize,
+    pub budget_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureCacheStats {
+    pub textures: CacheStats,
+    pub thumbnails: CacheStats,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    pub path: PathBuf,
+    pub name: String,
+    pub timestamp_millis: u64,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
@@ -44,6 +89,13 @@ pub struct RecentDocument {
     pub name: String,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableDocument {
+    pub path: PathBuf,
+    pub name: String,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub enum ClipboardManifest {
     Animations,
@@ -98,11 +150,14 @@ pub struct Document {
     pub hide_hitboxes: bool,
     pub hide_origin: bool,
     pub hide_sprite: bool,
+    pub history: Vec<HistoryEntry>,
+    pub history_index: usize,
     pub hitbox_being_renamed: Option<String>,
     pub hitboxes_being_nudged: HashSet<String>,
     pub hitboxes_being_resized: HashSet<String>,
     pub hitboxes_list_offset: f64,
     pub is_dragging_keyframe_duration: bool,
+    pub is_recording_macro: bool,
     pub keyframe_snapping_base_duration_millis: u64,
     pub keyframes_being_dragged: HashSet<(Direction, usize)>,
     pub last_interacted_animation: Option<String>,
@@ -124,10 +179,17 @@ pub struct Document {
     pub timeline_zoom_factor: f32,
     pub undo_effect: Option<String>,
     pub was_close_requested: bool,
+    pub was_modified_externally: bool,
     pub workbench_offset: (f32, f32),
     pub workbench_zoom: f32,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub effect: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Sheet {
@@ -243,13 +305,47 @@ pub enum BrowseDirection {
     Right,
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ExportFormat {
+    Template,
+    Exporter,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportSettings {
+    pub format: ExportFormat,
     pub template_file: PathBuf,
+    pub exporter_file: PathBuf,
     pub atlas_image_file: PathBuf,
     pub metadata_file: PathBuf,
     pub metadata_paths_root: PathBuf,
+    pub export_on_save: bool,
+    pub template_variables: Vec<TemplateVariable>,
+    pub origin: ExportOrigin,
+    pub y_axis: ExportYAxis,
+    pub uv_coordinates: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ExportOrigin {
+    TopLeft,
+    BottomLeft,
+    Center,
+    BottomCenter,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ExportYAxis {
+    Down,
+    Up,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVariable {
+    pub name: String,
+    pub value: String,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
@@ -267,6 +363,7 @@ pub enum ExportSettingsError {
 pub struct ExportSettingsValidation {
     pub valid_settings: bool,
     pub template_file_error: Option<ExportSettingsError>,
+    pub exporter_file_error: Option<ExportSettingsError>,
     pub atlas_image_file_error: Option<ExportSettingsError>,
     pub metadata_file_error: Option<ExportSettingsError>,
     pub metadata_paths_root_error: Option<ExportSettingsError>,
@@ -292,21 +389,73 @@ pub struct SaveDocumentError {
     pub error: String,
 }
 
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportCancelled {
+    pub document_name: String,
+    pub document_path: PathBuf,
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportError {
     pub document_name: String,
+    pub document_path: PathBuf,
     pub error: String,
 }
 
+#[derive(Clone, Debug, Copy, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ExportStage {
+    LoadingTextures,
+    Packing,
+    Compositing,
+    RenderingTemplate,
+    Writing,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    pub document_name: String,
+    pub document_path: PathBuf,
+    pub stage: ExportStage,
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportSuccess {
     pub document_name: String,
+    pub document_path: PathBuf,
     pub atlas_image_file_path: PathBuf,
     pub atlas_image_file_name: String,
     pub metadata_file_path: PathBuf,
     pub metadata_file_name: String,
+    pub post_export_commands: Vec<PostExportCommandOutput>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ExportPreview {
+    #[serde(rename = "output")]
+    Output(ExportPreviewOutput),
+    #[serde(rename = "error")]
+    Error(String),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreviewOutput {
+    pub metadata: String,
+    pub atlas_image_width: u32,
+    pub atlas_image_height: u32,
+    pub atlas_image: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostExportCommandOutput {
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub output: String,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -395,6 +544,13 @@ impl state::State {
                     name: d.to_file_name(),
                 })
                 .collect(),
+            recoverable_documents: self
+                .recoverable_documents()
+                .map(|d| RecoverableDocument {
+                    path: d.document.clone(),
+                    name: d.document.to_file_name(),
+                })
+                .collect(),
             clipboard_manifest: self.clipboard_manifest().as_ref().map(|m| m.into()),
             is_release_build: !cfg!(debug_assertions),
             error: self.error().map(|e| e.into()),
@@ -403,6 +559,8 @@ impl state::State {
             update_step: (&self.update_step()).into(),
             about_dialog_open: self.is_about_dialog_open(),
             commit_hash: std::option_env!("TIGER_COMMIT_SHA").map(String::from),
+            settings: self.settings().into(),
+            macros: self.macro_names(),
         }
     }
 }
@@ -418,6 +576,52 @@ impl From<&state::UserFacingError> for UserFacingError {
     }
 }
 
+impl From<&settings::Settings> for Settings {
+    fn from(settings: &settings::Settings) -> Self {
+        Self {
+            allowed_exporters: settings.allowed_exporters.clone(),
+            automation_server: settings.automation_server,
+            history_limit: settings.history_limit,
+            max_backups: settings.max_backups,
+            persist_history: settings.persist_history,
+            post_export_commands: settings.post_export_commands.clone(),
+            restore_views: settings.restore_views,
+            texture_cache_budget_mb: settings.texture_cache_budget_mb,
+        }
+    }
+}
+
+impl From<texture_cache::CacheStats> for CacheStats {
+    fn from(stats: texture_cache::CacheStats) -> Self {
+        Self {
+            entries: stats.entries,
+            bytes_used: stats.bytes_used,
+            budget_bytes: stats.budget_bytes,
+            hits: stats.hits,
+            misses: stats.misses,
+        }
+    }
+}
+
+impl From<texture_cache::TextureCacheStats> for TextureCacheStats {
+    fn from(stats: texture_cache::TextureCacheStats) -> Self {
+        Self {
+            textures: stats.textures.into(),
+            thumbnails: stats.thumbnails.into(),
+        }
+    }
+}
+
+impl From<&backups::Backup> for Backup {
+    fn from(backup: &backups::Backup) -> Self {
+        Self {
+            path: backup.path.clone(),
+            name: backup.path.to_file_name(),
+            timestamp_millis: backup.timestamp_millis,
+        }
+    }
+}
+
 impl From<&onboarding::OnboardingStep> for OnboardingStep {
     fn from(step: &onboarding::OnboardingStep) -> Self {
         match step {
@@ -518,6 +722,12 @@ impl document::Document {
             hide_hitboxes: self.is_hiding_hitboxes(),
             hide_origin: self.is_hiding_origin(),
             hide_sprite: self.is_hiding_sprite(),
+            history: self
+                .history_effects()
+                .into_iter()
+                .map(|effect| HistoryEntry { effect })
+                .collect(),
+            history_index: self.history_index(),
             hitbox_being_renamed: self.hitbox_being_renamed().cloned(),
             hitboxes_being_nudged: self
                 .hitboxes_being_nudged()
@@ -531,6 +741,7 @@ impl document::Document {
                 .collect(),
             hitboxes_list_offset: self.hitboxes_list_offset(),
             is_dragging_keyframe_duration: self.is_dragging_keyframe_duration(),
+            is_recording_macro: self.is_recording_macro(),
             keyframe_snapping_base_duration_millis: self
                 .keyframe_snapping_base_duration()
                 .as_millis() as u64,
@@ -563,6 +774,7 @@ impl document::Document {
             timeline_zoom_factor: self.timeline_zoom_factor(),
             undo_effect: self.undo_effect(),
             was_close_requested: self.close_requested(),
+            was_modified_externally: self.was_modified_externally(),
             workbench_offset: self.workbench_offset().to_tuple(),
             workbench_zoom: self.workbench_zoom(),
         }
@@ -927,21 +1139,139 @@ impl<P: Paths> From<&sheet::ExportSettings<P>> for ExportSettings {
     fn from(settings: &sheet::ExportSettings<P>) -> Self {
         match settings {
             sheet::ExportSettings::Template(template_settings) => Self {
+                format: ExportFormat::Template,
                 template_file: template_settings.template_file().to_owned(),
+                exporter_file: PathBuf::new(),
                 atlas_image_file: template_settings.atlas_image_file().to_owned(),
                 metadata_file: template_settings.metadata_file().to_owned(),
                 metadata_paths_root: template_settings.metadata_paths_root().to_owned(),
+                export_on_save: template_settings.export_on_save(),
+                template_variables: template_settings
+                    .variables()
+                    .iter()
+                    .map(|(name, value)| TemplateVariable {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+                origin: template_settings.coordinates().origin().into(),
+                y_axis: template_settings.coordinates().y_axis().into(),
+                uv_coordinates: template_settings.coordinates().uv_coordinates(),
+            },
+            sheet::ExportSettings::Exporter(exporter_settings) => Self {
+                format: ExportFormat::Exporter,
+                template_file: PathBuf::new(),
+                exporter_file: exporter_settings.exporter_file().to_owned(),
+                atlas_image_file: exporter_settings.atlas_image_file().to_owned(),
+                metadata_file: exporter_settings.metadata_file().to_owned(),
+                metadata_paths_root: exporter_settings.metadata_paths_root().to_owned(),
+                export_on_save: exporter_settings.export_on_save(),
+                template_variables: Vec::new(),
+                origin: exporter_settings.coordinates().origin().into(),
+                y_axis: exporter_settings.coordinates().y_axis().into(),
+                uv_coordinates: exporter_settings.coordinates().uv_coordinates(),
             },
         }
     }
 }
 
+impl From<&export::ExportPreview> for ExportPreviewOutput {
+    fn from(preview: &export::ExportPreview) -> Self {
+        Self {
+            metadata: preview.metadata.clone(),
+            atlas_image_width: preview.atlas_image_width,
+            atlas_image_height: preview.atlas_image_height,
+            atlas_image: preview.atlas_image_data_url(),
+        }
+    }
+}
+
+impl From<export::ExportStage> for ExportStage {
+    fn from(stage: export::ExportStage) -> Self {
+        match stage {
+            export::ExportStage::LoadingTextures => ExportStage::LoadingTextures,
+            export::ExportStage::Packing => ExportStage::Packing,
+            export::ExportStage::Compositing => ExportStage::Compositing,
+            export::ExportStage::RenderingTemplate => ExportStage::RenderingTemplate,
+            export::ExportStage::Writing => ExportStage::Writing,
+        }
+    }
+}
+
+impl From<&export::PostExportCommandOutput> for PostExportCommandOutput {
+    fn from(output: &export::PostExportCommandOutput) -> Self {
+        Self {
+            command: output.command.clone(),
+            exit_code: output.exit_code,
+            output: output.output.clone(),
+        }
+    }
+}
+
+impl From<ExportFormat> for sheet::ExportFormat {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Template => sheet::ExportFormat::Template,
+            ExportFormat::Exporter => sheet::ExportFormat::Exporter,
+        }
+    }
+}
+
+impl From<sheet::ExportOrigin> for ExportOrigin {
+    fn from(origin: sheet::ExportOrigin) -> Self {
+        match origin {
+            sheet::ExportOrigin::TopLeft => ExportOrigin::TopLeft,
+            sheet::ExportOrigin::BottomLeft => ExportOrigin::BottomLeft,
+            sheet::ExportOrigin::Center => ExportOrigin::Center,
+            sheet::ExportOrigin::BottomCenter => ExportOrigin::BottomCenter,
+        }
+    }
+}
+
+impl From<ExportOrigin> for sheet::ExportOrigin {
+    fn from(origin: ExportOrigin) -> Self {
+        match origin {
+            ExportOrigin::TopLeft => sheet::ExportOrigin::TopLeft,
+            ExportOrigin::BottomLeft => sheet::ExportOrigin::BottomLeft,
+            ExportOrigin::Center => sheet::ExportOrigin::Center,
+            ExportOrigin::BottomCenter => sheet::ExportOrigin::BottomCenter,
+        }
+    }
+}
+
+impl From<sheet::ExportYAxis> for ExportYAxis {
+    fn from(y_axis: sheet::ExportYAxis) -> Self {
+        match y_axis {
+            sheet::ExportYAxis::Down => ExportYAxis::Down,
+            sheet::ExportYAxis::Up => ExportYAxis::Up,
+        }
+    }
+}
+
+impl From<ExportYAxis> for sheet::ExportYAxis {
+    fn from(y_axis: ExportYAxis) -> Self {
+        match y_axis {
+            ExportYAxis::Down => sheet::ExportYAxis::Down,
+            ExportYAxis::Up => sheet::ExportYAxis::Up,
+        }
+    }
+}
+
 impl From<&document::ExportSettingsValidation> for ExportSettingsValidation {
     fn from(validation: &document::ExportSettingsValidation) -> Self {
         match validation {
             document::ExportSettingsValidation::Template(s) => Self {
-                valid_settings: *s == document::TemplateExportSettingsValidation::default(),
+                valid_settings: validation.is_valid(),
                 template_file_error: s.template_file_error().map(|e| e.into()),
+                exporter_file_error: None,
+                atlas_image_file_error: s.atlas_image_file_error().map(|e| e.into()),
+                metadata_file_error: s.metadata_file_error().map(|e| e.into()),
+                metadata_paths_root_error: s.metadata_paths_root_error().map(|e| e.into()),
+            },
+            document::ExportSettingsValidation::Exporter(s) => Self {
+                valid_settings: validation.is_valid(),
+                template_file_error: None,
+                exporter_file_error: s.exporter_file_error().map(|e| e.into()),
                 atlas_image_file_error: s.atlas_image_file_error().map(|e| e.into()),
                 metadata_file_error: s.metadata_file_error().map(|e| e.into()),
                 metadata_paths_root_error: s.metadata_paths_root_error().map(|e| e.into()),
@@ -1,7 +1,9 @@
 use image::ImageError;
 use std::{
+    collections::hash_map::DefaultHasher,
     fs::{create_dir_all, File},
-    io::Write,
+    hash::{Hash, Hasher},
+    io::{Cursor, Write},
     path::{Path, PathBuf},
 };
 use thiserror::Error;
@@ -10,71 +12,209 @@ use crate::features::texture_cache;
 use crate::sheet::*;
 
 mod atlas;
+mod exporter;
+mod hooks;
 mod metadata;
+mod preview;
+mod process;
+mod progress;
 
 pub use atlas::*;
+pub use exporter::*;
+pub use hooks::*;
 pub use metadata::*;
+pub use preview::*;
+pub use progress::*;
 
 pub enum ExportOutput {
     TemplateExportOutput {
         atlas_image_path: PathBuf,
         metadata_path: PathBuf,
     },
+    ExporterExportOutput {
+        atlas_image_path: PathBuf,
+        metadata_path: PathBuf,
+    },
+}
+
+// Identifies the inputs and outputs of an export, so that exporting again can skip
+// work and avoid touching files when nothing changed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExportFingerprint {
+    inputs: u64,
+    atlas_image: u64,
+    metadata: u64,
 }
 
 #[derive(Error, Debug)]
 pub enum ExportError {
     #[error("Missing export settings")]
     NoExportSettings,
+    #[error("Invalid export settings")]
+    InvalidExportSettings,
+    #[error("Export was cancelled")]
+    Cancelled,
+    #[error("Exporter `{0}` has not been allowed to run. Allow it from the Export dialog.")]
+    ExporterNotAllowed(PathBuf),
     #[error("Filesystem error for `{0}`: `{1}`")]
     IoError(PathBuf, std::io::Error),
     #[error("{0}")]
     MetadataError(#[from] MetadataError),
     #[error("{0}")]
+    ExporterError(#[from] ExporterError),
+    #[error("{0}")]
+    PostExportCommandError(#[from] PostExportCommandError),
+    #[error("Could not hash export inputs\n\n{0}")]
+    InputHashingError(serde_json::Error),
+    #[error("{0}")]
     TexturePackingError(#[from] PackError),
     #[error("{0}")]
     TextureStorageError(#[from] ImageError),
 }
 
+// Exports the sheet, skipping all work if it uses a template and its inputs and output files
+// are the same as in the `previous_export`. External exporters can depend on more than their
+// input, so they always run. Output files whose content did not change are not rewritten.
 pub fn export_sheet(
     sheet: &Sheet<Absolute>,
+    sheet_path: &Path,
     texture_cache: texture_cache::Handle,
-) -> Result<ExportOutput, ExportError> {
+    previous_export: Option<ExportFingerprint>,
+    progress: &ExportProgress,
+) -> Result<(ExportOutput, ExportFingerprint), ExportError> {
     let export_settings = sheet
         .export_settings()
         .as_ref()
         .ok_or(ExportError::NoExportSettings)?;
 
-    match export_settings {
-        ExportSettings::Template(template_settings) => {
-            let atlas = pack_sheet(sheet, texture_cache)?;
-            let metadata = generate_sheet_metadata(sheet, export_settings, &atlas)?;
-
-            {
-                let path = template_settings.metadata_file();
-                if let Some(directory) = path.parent() {
-                    create_dir(directory)?;
-                }
-                let mut file = create_file(path)?;
-                file.write_all(&metadata.into_bytes())
-                    .map_err(|e| ExportError::IoError(path.to_owned(), e))?;
+    let bitmaps = load_frames(sheet, texture_cache, progress)?;
+    let inputs = hash_inputs(sheet, sheet_path, export_settings, &bitmaps)?;
+    let fingerprint = match previous_export {
+        Some(previous)
+            if matches!(export_settings, ExportSettings::Template(_))
+                && previous.inputs == inputs
+                && hash_file(export_settings.atlas_image_file()) == Some(previous.atlas_image)
+                && hash_file(export_settings.metadata_file()) == Some(previous.metadata) =>
+        {
+            previous
+        }
+        _ => {
+            let (atlas, metadata) =
+                render_sheet(sheet, sheet_path, export_settings, &bitmaps, progress)?;
+            progress.begin(ExportStage::Writing)?;
+            let mut atlas_image = Vec::new();
+            atlas
+                .image()
+                .write_to(&mut Cursor::new(&mut atlas_image), image::ImageFormat::Png)?;
+            write_if_changed(export_settings.metadata_file(), &metadata)?;
+            write_if_changed(export_settings.atlas_image_file(), &atlas_image)?;
+            ExportFingerprint {
+                inputs,
+                atlas_image: hash_bytes(&atlas_image),
+                metadata: hash_bytes(&metadata),
             }
+        }
+    };
 
-            {
-                let path = template_settings.atlas_image_file();
-                if let Some(directory) = path.parent() {
-                    create_dir(directory)?;
-                }
-                let mut file = create_file(path)?;
-                atlas.image().write_to(&mut file, image::ImageFormat::Png)?;
-            }
+    let atlas_image_path = export_settings.atlas_image_file().to_owned();
+    let metadata_path = export_settings.metadata_file().to_owned();
+    let output = match export_settings {
+        ExportSettings::Template(_) => ExportOutput::TemplateExportOutput {
+            atlas_image_path,
+            metadata_path,
+        },
+        ExportSettings::Exporter(_) => ExportOutput::ExporterExportOutput {
+            atlas_image_path,
+            metadata_path,
+        },
+    };
+    Ok((output, fingerprint))
+}
+
+// Hashes everything a template export depends on: sheet data (including export settings),
+// frame pixels and the template files.
+fn hash_inputs(
+    sheet: &Sheet<Absolute>,
+    sheet_path: &Path,
+    export_settings: &ExportSettings<Absolute>,
+    bitmaps: &Bitmaps,
+) -> Result<u64, ExportError> {
+    let mut hasher = DefaultHasher::new();
+
+    // Going through a `Value` sorts map keys, which makes the hash independent of hash map ordering
+    let sheet_data = serde_json::to_value(sheet)
+        .and_then(|v| serde_json::to_vec(&v))
+        .map_err(ExportError::InputHashingError)?;
+    sheet_data.hash(&mut hasher);
+    sheet_path.hash(&mut hasher);
 
-            Ok(ExportOutput::TemplateExportOutput {
-                atlas_image_path: template_settings.atlas_image_file().to_owned(),
-                metadata_path: template_settings.metadata_file().to_owned(),
-            })
+    for frame in sheet.sorted_frames() {
+        let bitmap = bitmaps.get(frame.source()).ok_or(PackError::FrameRead)?;
+        frame.source().hash(&mut hasher);
+        (bitmap.width(), bitmap.height()).hash(&mut hasher);
+        bitmap.as_bytes().hash(&mut hasher);
+    }
+
+    let template_files = match export_settings {
+        ExportSettings::Template(settings) => {
+            let mut files = vec![settings.template_file().to_owned()];
+            files.extend(template_partials(settings.template_file()));
+            files
         }
+        ExportSettings::Exporter(_) => Vec::new(),
+    };
+    for file in template_files {
+        file.hash(&mut hasher);
+        std::fs::read(&file)
+            .map_err(|e| ExportError::IoError(file.clone(), e))?
+            .hash(&mut hasher);
+    }
+
+    Ok(hasher.finish())
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn hash_file(path: &Path) -> Option<u64> {
+    std::fs::read(path).ok().map(|content| hash_bytes(&content))
+}
+
+fn write_if_changed(path: &Path, content: &[u8]) -> Result<(), ExportError> {
+    if std::fs::read(path).ok().as_deref() == Some(content) {
+        return Ok(());
+    }
+    if let Some(directory) = path.parent() {
+        create_dir(directory)?;
     }
+    let mut file = create_file(path)?;
+    file.write_all(content)
+        .map_err(|e| ExportError::IoError(path.to_owned(), e))
+}
+
+// Packs the atlas and produces the content of the metadata file, without writing anything
+fn render_sheet(
+    sheet: &Sheet<Absolute>,
+    sheet_path: &Path,
+    export_settings: &ExportSettings<Absolute>,
+    bitmaps: &Bitmaps,
+    progress: &ExportProgress,
+) -> Result<(Atlas, Vec<u8>), ExportError> {
+    let atlas = pack_sheet(bitmaps, progress)?;
+    progress.begin(ExportStage::RenderingTemplate)?;
+    let metadata = generate_sheet_metadata(sheet, sheet_path, export_settings, &atlas)?;
+    let metadata = match export_settings {
+        ExportSettings::Template(_) => metadata.into_bytes(),
+        ExportSettings::Exporter(exporter_settings) => run_exporter(
+            exporter_settings.exporter_file(),
+            metadata.as_bytes(),
+            EXPORTER_TIMEOUT,
+        )?,
+    };
+    Ok((atlas, metadata))
 }
 
 fn create_file(path: &Path) -> Result<File, ExportError> {
@@ -1,8 +1,10 @@
 use image::{DynamicImage, GenericImage};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
 
+use super::{ExportError, ExportProgress, ExportStage};
 use crate::features::texture_cache;
 use crate::sheet::{Absolute, Sheet};
 
@@ -14,6 +16,8 @@ pub enum PackError {
     Packing,
 }
 
+pub(super) type Bitmaps = HashMap<PathBuf, Arc<DynamicImage>>;
+
 pub(super) struct AtlasFrame {
     pub position_in_sheet: (u32, u32),
     pub size_in_sheet: (u32, u32),
@@ -25,6 +29,10 @@ pub(super) struct Atlas {
 }
 
 impl Atlas {
+    pub fn new(image: DynamicImage, layout: HashMap<PathBuf, AtlasFrame>) -> Self {
+        Self { image, layout }
+    }
+
     pub fn image(&self) -> &DynamicImage {
         &self.image
     }
@@ -34,25 +42,27 @@ impl Atlas {
     }
 }
 
-pub(super) fn pack_sheet(
+// Gathers the bitmaps of all frames in the sheet, decoding the ones missing from the texture cache
+pub(super) fn load_frames(
     sheet: &Sheet<Absolute>,
     texture_cache: texture_cache::Handle,
-) -> Result<Atlas, PackError> {
-    let mut bitmaps = HashMap::new();
-    {
-        let cache = texture_cache.lock();
-        for frame in sheet.frames_iter() {
-            bitmaps.insert(
-                frame.source(),
-                cache
-                    .get(frame.source())
-                    .cloned()
-                    .or_else(|| image::open(frame.source()).ok())
-                    .ok_or(PackError::FrameRead)?,
-            );
-        }
-    }
+    progress: &ExportProgress,
+) -> Result<Bitmaps, ExportError> {
+    progress.begin(ExportStage::LoadingTextures)?;
+    let frames = sheet.frames_iter().map(|f| f.source()).collect::<Vec<_>>();
+    let bitmaps = texture_cache::get_or_decode(&texture_cache, &frames, || progress.is_cancelled())
+        .ok_or(ExportError::Cancelled)?
+        .into_iter()
+        .map(|(path, bitmap)| bitmap.map(|b| (path, b)))
+        .collect::<Result<Bitmaps, _>>()
+        .map_err(|_| PackError::FrameRead)?;
+    Ok(bitmaps)
+}
 
+pub(super) fn pack_sheet(
+    bitmaps: &Bitmaps,
+    progress: &ExportProgress,
+) -> Result<Atlas, ExportError> {
     let mut items = bitmaps
         .iter()
         .map(|(path, bitmap)| crunch::Item {
@@ -64,6 +74,7 @@ pub(super) fn pack_sheet(
         .collect::<Vec<_>>();
     items.sort_by_key(|i| i.data);
 
+    progress.begin(ExportStage::Packing)?;
     let (width, height, layout) =
         crunch::pack_into_po2(8_192, items).map_err(|_| PackError::Packing)?;
     let layout = layout
@@ -79,12 +90,14 @@ pub(super) fn pack_sheet(
         })
         .collect::<HashMap<_, _>>();
 
+    progress.begin(ExportStage::Compositing)?;
     let mut image = DynamicImage::new_rgba8(width as u32, height as u32);
-    layout.iter().for_each(|(path, frame)| {
+    for (path, frame) in &layout {
+        progress.check_cancelled()?;
         let bitmap = bitmaps.get(path.as_path()).unwrap();
         let (x, y) = (frame.position_in_sheet.0, frame.position_in_sheet.1);
-        image.copy_from(bitmap, x, y).unwrap();
-    });
+        image.copy_from(bitmap.as_ref(), x, y).unwrap();
+    }
 
-    Ok(Atlas { image, layout })
+    Ok(Atlas::new(image, layout))
 }
@@ -0,0 +1,51 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    time::Duration,
+};
+use thiserror::Error;
+
+use super::process::{run_process, ProcessError};
+
+pub const EXPORTER_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum ExporterError {
+    #[error("Could not start exporter `{0}`: `{1}`")]
+    LaunchError(PathBuf, std::io::Error),
+    #[error("Error while communicating with exporter `{0}`: `{1}`")]
+    IoError(PathBuf, std::io::Error),
+    #[error("Exporter `{0}` did not complete within {1} seconds")]
+    Timeout(PathBuf, u64),
+    #[error("Exporter `{0}` failed ({1})\n\n{2}")]
+    ExporterFailed(PathBuf, ExitStatus, String),
+}
+
+// Runs an external exporter which reads metadata (as JSON) from stdin and writes
+// the exported metadata file to stdout. Errors are reported on stderr.
+pub fn run_exporter(
+    exporter: &Path,
+    input: &[u8],
+    timeout: Duration,
+) -> Result<Vec<u8>, ExporterError> {
+    let mut command = Command::new(exporter);
+    if let Some(directory) = exporter.parent() {
+        command.current_dir(directory);
+    }
+
+    let output = run_process(&mut command, input, timeout).map_err(|e| match e {
+        ProcessError::LaunchError(e) => ExporterError::LaunchError(exporter.to_owned(), e),
+        ProcessError::IoError(e) => ExporterError::IoError(exporter.to_owned(), e),
+        ProcessError::Timeout => ExporterError::Timeout(exporter.to_owned(), timeout.as_secs()),
+    })?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(ExporterError::ExporterFailed(
+            exporter.to_owned(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ))
+    }
+}
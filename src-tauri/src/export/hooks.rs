@@ -0,0 +1,154 @@
+use std::{
+    process::{Command, ExitStatus},
+    time::Duration,
+};
+use thiserror::Error;
+
+use super::process::{run_process, ProcessError};
+use crate::sheet::{Absolute, ExportSettings};
+
+pub const POST_EXPORT_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PostExportCommandOutput {
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub output: String,
+}
+
+#[derive(Error, Debug)]
+pub enum PostExportCommandError {
+    #[error("Could not start post-export command `{0}`: `{1}`")]
+    LaunchError(String, std::io::Error),
+    #[error("Error while running post-export command `{0}`: `{1}`")]
+    IoError(String, std::io::Error),
+    #[error("Post-export command `{0}` did not complete within {1} seconds")]
+    Timeout(String, u64),
+    #[error("Post-export command `{0}` failed ({1})\n\n{2}")]
+    CommandFailed(String, ExitStatus, String),
+}
+
+// Runs post-export commands in order, through the system shell. Commands come from
+// the user settings rather than from sheet files, so that exporting a sheet never
+// runs commands its author chose.
+// Commands run from the directory containing the metadata file and can locate the
+// exported files via the TIGER_ATLAS_IMAGE_FILE and TIGER_METADATA_FILE variables.
+// Blank commands are skipped and execution stops at the first command which fails.
+pub fn run_post_export_commands(
+    commands: &[String],
+    export_settings: &ExportSettings<Absolute>,
+    timeout: Duration,
+) -> Result<Vec<PostExportCommandOutput>, PostExportCommandError> {
+    let mut outputs = Vec::new();
+    for command_line in commands {
+        if command_line.trim().is_empty() {
+            continue;
+        }
+        let mut command = shell_command(command_line);
+        command
+            .env("TIGER_ATLAS_IMAGE_FILE", export_settings.atlas_image_file())
+            .env("TIGER_METADATA_FILE", export_settings.metadata_file());
+        if let Some(directory) = export_settings.metadata_file().parent() {
+            command.current_dir(directory);
+        }
+
+        let output = run_process(&mut command, &[], timeout).map_err(|e| match e {
+            ProcessError::LaunchError(e) => {
+                PostExportCommandError::LaunchError(command_line.clone(), e)
+            }
+            ProcessError::IoError(e) => PostExportCommandError::IoError(command_line.clone(), e),
+            ProcessError::Timeout => {
+                PostExportCommandError::Timeout(command_line.clone(), timeout.as_secs())
+            }
+        })?;
+
+        let text = [output.stdout.as_slice(), output.stderr.as_slice()]
+            .iter()
+            .map(|stream| String::from_utf8_lossy(stream).trim().to_owned())
+            .filter(|stream| !stream.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if !output.status.success() {
+            return Err(PostExportCommandError::CommandFailed(
+                command_line.clone(),
+                output.status,
+                text,
+            ));
+        }
+
+        outputs.push(PostExportCommandOutput {
+            command: command_line.clone(),
+            exit_code: output.status.code(),
+            output: text,
+        });
+    }
+    Ok(outputs)
+}
+
+#[cfg(windows)]
+fn shell_command(command_line: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut command = Command::new("cmd");
+    command.arg("/C").raw_arg(command_line);
+    command
+}
+
+#[cfg(not(windows))]
+fn shell_command(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::path::Path;
+
+    use super::*;
+    use crate::sheet::Any;
+
+    fn export_settings() -> ExportSettings<Absolute> {
+        let directory = std::env::current_dir().unwrap().join("test-output");
+        let mut settings = ExportSettings::<Any>::default();
+        settings.set_atlas_image_file(directory.join("atlas.png"));
+        settings.set_metadata_file(directory.join("atlas.json"));
+        settings.set_metadata_paths_root(&directory);
+        settings.with_absolute_paths().unwrap()
+    }
+
+    fn commands(commands: &[&str]) -> Vec<String> {
+        commands.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn runs_commands_with_export_paths() {
+        let command = if cfg!(windows) {
+            "echo %TIGER_METADATA_FILE%"
+        } else {
+            "echo $TIGER_METADATA_FILE"
+        };
+        let settings = export_settings();
+        let commands = commands(&["echo first", command]);
+        let outputs =
+            run_post_export_commands(&commands, &settings, POST_EXPORT_COMMAND_TIMEOUT).unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].output, "first");
+        assert_eq!(outputs[0].exit_code, Some(0));
+        assert_eq!(Path::new(&outputs[1].output), settings.metadata_file());
+    }
+
+    #[test]
+    fn stops_at_failing_command() {
+        let settings = export_settings();
+        let commands = commands(&["echo oops && exit 3", "echo unreachable"]);
+        let result = run_post_export_commands(&commands, &settings, POST_EXPORT_COMMAND_TIMEOUT);
+        let Err(PostExportCommandError::CommandFailed(command, status, output)) = result else {
+            panic!("Unexpected result: {result:?}");
+        };
+        assert_eq!(command, "echo oops && exit 3");
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(output, "oops");
+    }
+}
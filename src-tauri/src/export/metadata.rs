@@ -1,7 +1,8 @@
 use euclid::default::*;
 use handlebars::{handlebars_helper, Handlebars};
 use pathdiff::diff_paths;
-use std::collections::HashMap;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -24,12 +25,34 @@ pub enum MetadataError {
     FrameWasNotPacked,
     #[error("Error converting an absolute path to a relative path\nAbsolute path: `{0}`\nRelative path root: `{1}`")]
     AbsoluteToRelativePath(PathBuf, PathBuf),
+    #[error("Error generating exporter input\n\n{0}")]
+    ExporterInputError(serde_json::Error),
 }
 
 handlebars_helper!(add: |a:f64, b:f64| a + b);
 handlebars_helper!(divide: |a:f64, b:f64| if b == 0.0 { 0.0 } else { a / b });
 handlebars_helper!(multiply: |a:f64, b:f64| a * b);
 handlebars_helper!(subtract: |a:f64, b:f64| a - b);
+handlebars_helper!(pad: |value:i64, width:u64| format!("{value:0width$}", width = width as usize));
+handlebars_helper!(format_number: |value:f64, decimals:u64| format!("{value:.decimals$}", decimals = decimals as usize));
+handlebars_helper!(json_escape: |value:str| {
+    let quoted = serde_json::to_string(value).unwrap();
+    quoted[1..quoted.len() - 1].to_owned()
+});
+handlebars_helper!(xml_escape: |value:str| value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;"));
+handlebars_helper!(direction_angle: |direction:Json| {
+    serde_json::from_value::<sheet::Direction>(direction.clone())
+        .map(|d| d.angle().into())
+        .unwrap_or(serde_json::Value::Null)
+});
+
+// Name of the directory (next to the template file) containing partials
+pub const PARTIALS_DIRECTORY: &str = "partials";
 
 pub struct Template<'a> {
     handlebars: Handlebars<'a>,
@@ -45,19 +68,105 @@ impl<'a> Template<'a> {
         handlebars.register_helper("divide", Box::new(divide));
         handlebars.register_helper("multiply", Box::new(multiply));
         handlebars.register_helper("subtract", Box::new(subtract));
+        handlebars.register_helper("pad", Box::new(pad));
+        handlebars.register_helper("format_number", Box::new(format_number));
+        handlebars.register_helper("json_escape", Box::new(json_escape));
+        handlebars.register_helper("xml_escape", Box::new(xml_escape));
+        handlebars.register_helper("direction_angle", Box::new(direction_angle));
+        for partial in template_partials(path.as_ref()) {
+            let name = partial
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            handlebars
+                .register_template_file(&name, &partial)
+                .map_err(|e| MetadataError::TemplateParsingError(Box::new(e)))?;
+        }
         handlebars
             .register_template_file(Self::TEMPLATE_NAME, path.as_ref())
             .map_err(|e| MetadataError::TemplateParsingError(Box::new(e)))?;
         Ok(Self { handlebars })
     }
 
-    fn render(&self, sheet: &Sheet) -> Result<String, MetadataError> {
+    fn render(&self, input: &TemplateInput) -> Result<String, MetadataError> {
         self.handlebars
-            .render(Self::TEMPLATE_NAME, sheet)
+            .render(Self::TEMPLATE_NAME, input)
             .map_err(|e| MetadataError::TemplateRenderingError(Box::new(e)))
     }
 }
 
+// Applies the origin and Y axis conventions from the export settings. Positions
+// within an animation are relative to the keyframe pivot, positions within the
+// atlas are relative to its top-left corner.
+#[derive(Clone, Copy, Debug)]
+struct Coordinates {
+    conventions: sheet::ExportCoordinates,
+    atlas_size: Vector2D<i32>,
+}
+
+impl Coordinates {
+    fn new(conventions: sheet::ExportCoordinates, atlas: &Atlas) -> Self {
+        Self {
+            conventions,
+            atlas_size: Vector2D::new(atlas.image().width(), atlas.image().height()).to_i32(),
+        }
+    }
+
+    fn anchor(&self, top_left: Vector2D<i32>, size: Vector2D<i32>) -> Vector2D<i32> {
+        top_left
+            + match self.conventions.origin() {
+                sheet::ExportOrigin::TopLeft => Vector2D::zero(),
+                sheet::ExportOrigin::BottomLeft => Vector2D::new(0, size.y),
+                sheet::ExportOrigin::Center => size / 2,
+                sheet::ExportOrigin::BottomCenter => Vector2D::new(size.x / 2, size.y),
+            }
+    }
+
+    fn pivot_position(&self, top_left: Vector2D<i32>, size: Vector2D<i32>) -> Vector2D<i32> {
+        let anchor = self.anchor(top_left, size);
+        match self.conventions.y_axis() {
+            sheet::ExportYAxis::Down => anchor,
+            sheet::ExportYAxis::Up => Vector2D::new(anchor.x, -anchor.y),
+        }
+    }
+
+    fn atlas_position(&self, top_left: Vector2D<i32>, size: Vector2D<i32>) -> Vector2D<i32> {
+        let anchor = self.anchor(top_left, size);
+        match self.conventions.y_axis() {
+            sheet::ExportYAxis::Down => anchor,
+            sheet::ExportYAxis::Up => Vector2D::new(anchor.x, self.atlas_size.y - anchor.y),
+        }
+    }
+
+    fn uv(&self, top_left: Vector2D<i32>, size: Vector2D<i32>) -> Option<UvRect> {
+        if !self.conventions.uv_coordinates() {
+            return None;
+        }
+        let atlas_size = self.atlas_size.to_f64().max(Vector2D::new(1.0, 1.0));
+        let (top, bottom) = match self.conventions.y_axis() {
+            sheet::ExportYAxis::Down => (top_left.y, top_left.y + size.y),
+            sheet::ExportYAxis::Up => (
+                self.atlas_size.y - top_left.y - size.y,
+                self.atlas_size.y - top_left.y,
+            ),
+        };
+        Some(UvRect {
+            u0: top_left.x as f64 / atlas_size.x,
+            v0: top as f64 / atlas_size.y,
+            u1: (top_left.x + size.x) as f64 / atlas_size.x,
+            v1: bottom as f64 / atlas_size.y,
+        })
+    }
+}
+
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+struct UvRect {
+    u0: f64,
+    v0: f64,
+    u1: f64,
+    v1: f64,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct Hitbox {
     name: String,
@@ -68,54 +177,111 @@ struct Hitbox {
 }
 
 impl Hitbox {
-    fn new(hitbox_name: String, hitbox: &sheet::Hitbox) -> Result<Hitbox, MetadataError> {
+    fn new(
+        hitbox_name: String,
+        hitbox: &sheet::Hitbox,
+        coordinates: &Coordinates,
+    ) -> Result<Hitbox, MetadataError> {
+        let position = coordinates.pivot_position(hitbox.position(), hitbox.size().to_i32());
         Ok(Self {
             name: hitbox_name,
-            x: hitbox.position().x,
-            y: hitbox.position().y,
+            x: position.x,
+            y: position.y,
             width: hitbox.size().x as i32,
             height: hitbox.size().y as i32,
         })
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
 struct Frame {
     index: i32,
     x: i32,
     y: i32,
     width: i32,
     height: i32,
+    file_name: String,
+    file_stem: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uv: Option<UvRect>,
 }
 
 impl Frame {
     fn new(
-        sheet: &sheet::Sheet<Absolute>,
+        index: usize,
         frame: &sheet::Frame<Absolute>,
         atlas_layout: &AtlasLayout,
+        coordinates: &Coordinates,
     ) -> Result<Self, MetadataError> {
-        let index = sheet
-            .sorted_frames()
-            .into_iter()
-            .position(|f| std::ptr::eq(f, frame))
-            .ok_or(MetadataError::InvalidFrameReference)?;
-
         let frame_layout = atlas_layout
             .get(frame.source())
             .ok_or(MetadataError::FrameWasNotPacked)?;
 
+        let top_left = Vector2D::new(
+            frame_layout.position_in_sheet.0,
+            frame_layout.position_in_sheet.1,
+        )
+        .to_i32();
+        let size =
+            Vector2D::new(frame_layout.size_in_sheet.0, frame_layout.size_in_sheet.1).to_i32();
+        let position = coordinates.atlas_position(top_left, size);
+
         Ok(Self {
             index: index as i32,
-            x: frame_layout.position_in_sheet.0 as i32,
-            y: frame_layout.position_in_sheet.1 as i32,
-            width: frame_layout.size_in_sheet.0 as i32,
-            height: frame_layout.size_in_sheet.1 as i32,
+            x: position.x,
+            y: position.y,
+            width: size.x,
+            height: size.y,
+            file_name: file_name_string(frame.source().file_name()),
+            file_stem: file_name_string(frame.source().file_stem()),
+            uv: coordinates.uv(top_left, size),
+        })
+    }
+}
+
+// Metadata for every frame of the sheet, computed once and looked up by path
+// when generating keyframes.
+struct FrameTable<'a> {
+    frames: Vec<Frame>,
+    indices: HashMap<&'a Path, usize>,
+    coordinates: Coordinates,
+}
+
+impl<'a> FrameTable<'a> {
+    fn new(
+        sheet: &'a sheet::Sheet<Absolute>,
+        atlas_layout: &AtlasLayout,
+        coordinates: Coordinates,
+    ) -> Result<Self, MetadataError> {
+        let sorted_frames = sheet.sorted_frames();
+        let frames = sorted_frames
+            .par_iter()
+            .enumerate()
+            .map(|(index, frame)| Frame::new(index, frame, atlas_layout, &coordinates))
+            .collect::<Result<Vec<_>, _>>()?;
+        let indices = sorted_frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| (frame.source(), index))
+            .collect();
+        Ok(Self {
+            frames,
+            indices,
+            coordinates,
         })
     }
+
+    fn frame(&self, path: &Path) -> Result<&Frame, MetadataError> {
+        self.indices
+            .get(path)
+            .map(|index| &self.frames[*index])
+            .ok_or(MetadataError::InvalidFrameReference)
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct Keyframe {
+    start_time: i32,
     duration: i32,
     x: i32,
     y: i32,
@@ -125,28 +291,24 @@ struct Keyframe {
 
 impl Keyframe {
     fn new(
-        sheet: &sheet::Sheet<Absolute>,
         keyframe: &sheet::Keyframe<Absolute>,
-        atlas_layout: &AtlasLayout,
+        start_time: u64,
+        frame_table: &FrameTable,
     ) -> Result<Self, MetadataError> {
-        let atlas_frame = atlas_layout
-            .get(keyframe.frame())
-            .ok_or(MetadataError::FrameWasNotPacked)?;
-
-        let frame_size: Vector2D<u32> = atlas_frame.size_in_sheet.into();
-        let position = keyframe.offset() - (frame_size.to_f32() / 2.0).floor().to_i32();
+        let frame_data = frame_table.frame(keyframe.frame())?.clone();
+        let coordinates = &frame_table.coordinates;
 
-        let frame = sheet
-            .frame(keyframe.frame())
-            .ok_or(MetadataError::InvalidFrameReference)?;
-        let frame_data = Frame::new(sheet, frame, atlas_layout)?;
+        let frame_size = Vector2D::new(frame_data.width, frame_data.height);
+        let top_left = keyframe.offset() - (frame_size.to_f32() / 2.0).floor().to_i32();
+        let position = coordinates.pivot_position(top_left, frame_size);
 
         let mut hitboxes = Vec::new();
         for (hitbox_name, hitbox) in keyframe.sorted_hitboxes() {
-            hitboxes.push(Hitbox::new(hitbox_name.clone(), hitbox)?);
+            hitboxes.push(Hitbox::new(hitbox_name.clone(), hitbox, coordinates)?);
         }
 
         Ok(Keyframe {
+            start_time: start_time as i32,
             duration: keyframe.duration_millis() as i32,
             x: position.x,
             y: position.y,
@@ -159,24 +321,31 @@ impl Keyframe {
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct Sequence {
     direction: sheet::Direction,
+    direction_angle: u32,
+    duration: i32,
+    frame_count: i32,
     keyframes: Vec<Keyframe>,
 }
 
 impl Sequence {
     fn new(
-        sheet: &sheet::Sheet<Absolute>,
         direction: sheet::Direction,
         sequence: &sheet::Sequence<Absolute>,
-        atlas_layout: &AtlasLayout,
+        frame_table: &FrameTable,
     ) -> Result<Self, MetadataError> {
         let mut keyframes = Vec::new();
+        let mut start_time = 0;
         for keyframe in sequence.keyframes_iter() {
-            let frame = Keyframe::new(sheet, keyframe, atlas_layout)?;
+            let frame = Keyframe::new(keyframe, start_time, frame_table)?;
             keyframes.push(frame);
+            start_time += keyframe.duration_millis();
         }
 
         Ok(Self {
             direction,
+            direction_angle: direction.angle(),
+            duration: start_time as i32,
+            frame_count: keyframes.len() as i32,
             keyframes,
         })
     }
@@ -191,14 +360,13 @@ struct Animation {
 
 impl Animation {
     fn new(
-        sheet: &sheet::Sheet<Absolute>,
         animation_name: String,
         animation: &sheet::Animation<Absolute>,
-        atlas_layout: &AtlasLayout,
+        frame_table: &FrameTable,
     ) -> Result<Self, MetadataError> {
         let mut sequences = Vec::new();
         for (direction, sequence) in animation.sequences_iter() {
-            let sequence = Sequence::new(sheet, *direction, sequence, atlas_layout)?;
+            let sequence = Sequence::new(*direction, sequence, frame_table)?;
             sequences.push(sequence);
         }
 
@@ -219,7 +387,7 @@ struct Image {
 
 impl Image {
     fn new<P: AsRef<Path>>(
-        settings: &sheet::TemplateExportSettings<Absolute>,
+        settings: &sheet::ExportSettings<Absolute>,
         path: P,
         width: u32,
         height: u32,
@@ -245,6 +413,7 @@ impl Image {
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct Sheet {
+    sheet_name: String,
     frames: Vec<Frame>,
     animations: Vec<Animation>,
     atlas_image: Image,
@@ -253,26 +422,20 @@ struct Sheet {
 impl Sheet {
     fn new(
         sheet: &sheet::Sheet<Absolute>,
-        settings: &sheet::TemplateExportSettings<Absolute>,
+        sheet_path: &Path,
+        settings: &sheet::ExportSettings<Absolute>,
         atlas: &Atlas,
     ) -> Result<Self, MetadataError> {
-        let frames = {
-            let mut frames = Vec::new();
-            for frame in sheet.sorted_frames() {
-                frames.push(Frame::new(sheet, frame, atlas.layout())?);
-            }
-            frames
-        };
+        let coordinates = Coordinates::new(settings.coordinates(), atlas);
+        let frame_table = FrameTable::new(sheet, atlas.layout(), coordinates)?;
 
-        let animations = {
-            let mut animations = Vec::new();
-            for (animation_name, animation) in sheet.sorted_animations() {
-                let animation_data =
-                    Animation::new(sheet, animation_name.clone(), animation, atlas.layout())?;
-                animations.push(animation_data);
-            }
-            animations
-        };
+        let animations = sheet
+            .sorted_animations()
+            .par_iter()
+            .map(|(animation_name, animation)| {
+                Animation::new((*animation_name).clone(), animation, &frame_table)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         let atlas_image = Image::new(
             settings,
@@ -282,27 +445,83 @@ impl Sheet {
         )?;
 
         Ok(Self {
-            frames,
+            sheet_name: file_name_string(sheet_path.file_stem()),
+            frames: frame_table.frames,
             animations,
             atlas_image,
         })
     }
 }
 
+// Data available to templates: the sheet data model, plus the user-defined
+// variables from the export settings.
+#[derive(serde::Serialize, Debug)]
+struct TemplateInput<'a> {
+    #[serde(flatten)]
+    sheet: Sheet,
+    variables: &'a BTreeMap<String, String>,
+}
+
+// Data sent to external exporters: the same data model as templates,
+// plus the absolute path of the atlas image.
+#[derive(serde::Serialize, Debug)]
+struct ExporterInput<'a> {
+    #[serde(flatten)]
+    sheet: Sheet,
+    atlas_image_file: &'a Path,
+}
+
+// Partials are all the files in the partials directory next to a template. They
+// can be referenced from the template by their file name, without extension.
+pub fn template_partials<P: AsRef<Path>>(template_file: P) -> Vec<PathBuf> {
+    let directory = match template_file.as_ref().parent() {
+        Some(parent) => parent.join(PARTIALS_DIRECTORY),
+        None => return Vec::new(),
+    };
+    let mut partials = match std::fs::read_dir(directory) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    partials.sort();
+    partials
+}
+
+// For external exporters, the metadata is the JSON document they receive as input
 pub(super) fn generate_sheet_metadata(
     sheet: &sheet::Sheet<Absolute>,
+    sheet_path: &Path,
     export_settings: &sheet::ExportSettings<Absolute>,
     atlas: &Atlas,
 ) -> Result<String, MetadataError> {
+    let globals = Sheet::new(sheet, sheet_path, export_settings, atlas)?;
     match export_settings {
         sheet::ExportSettings::Template(template_settings) => {
             let template = Template::new(template_settings.template_file())?;
-            let globals = Sheet::new(sheet, template_settings, atlas)?;
-            template.render(&globals)
+            let input = TemplateInput {
+                sheet: globals,
+                variables: template_settings.variables(),
+            };
+            template.render(&input)
+        }
+        sheet::ExportSettings::Exporter(exporter_settings) => {
+            let input = ExporterInput {
+                sheet: globals,
+                atlas_image_file: exporter_settings.atlas_image_file(),
+            };
+            serde_json::to_string(&input).map_err(MetadataError::ExporterInputError)
         }
     }
 }
 
+fn file_name_string(name: Option<&std::ffi::OsStr>) -> String {
+    name.map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
 trait WithForwardSlashes {
     fn with_forward_slashes(&self) -> PathBuf;
 }
@@ -312,3 +531,274 @@ impl<T: AsRef<Path>> WithForwardSlashes for T {
         self.as_ref().to_string_lossy().replace('\\', "/").into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use image::DynamicImage;
+    use std::time::{Duration, Instant};
+    use sugar_path::SugarPath;
+
+    use super::*;
+    use crate::sheet::Any;
+
+    const FRAME_SIZE: u32 = 16;
+    const FRAMES_PER_ROW: u32 = 64;
+    const SYNTHETIC_SHEET: &str = "test-data/synthetic.tiger";
+
+    // Builds a sheet whose frames are not backed by actual files, along with
+    // an atlas laying them out on a grid.
+    fn synthetic_sheet(num_frames: u32) -> (sheet::Sheet<Absolute>, Atlas) {
+        let samurai = sheet::Sheet::<Any>::read("test-data/samurai.tiger")
+            .and_then(|s| s.with_relative_paths("test-data"))
+            .unwrap()
+            .with_absolute_paths();
+
+        let mut sheet = sheet::Sheet::<Absolute>::default();
+        sheet.set_export_settings(samurai.export_settings().clone().unwrap());
+
+        let frames = (0..num_frames)
+            .map(|i| PathBuf::from(format!("test-data/synthetic/frame-{i:05}.png")).resolve())
+            .collect::<Vec<_>>();
+        sheet.add_frames(&frames);
+
+        for chunk in frames.chunks(25) {
+            let (_, animation) = sheet.create_animation("animation");
+            animation.apply_direction_preset(sheet::DirectionPreset::FourDirections);
+            let sequence = animation.sequence_mut(sheet::Direction::North).unwrap();
+            for (index, frame) in chunk.iter().enumerate() {
+                sequence
+                    .insert_keyframe(sheet::Keyframe::new(frame), index)
+                    .unwrap();
+            }
+        }
+
+        let layout = frames
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                let (column, row) = (i as u32 % FRAMES_PER_ROW, i as u32 / FRAMES_PER_ROW);
+                (
+                    frame.clone(),
+                    AtlasFrame {
+                        position_in_sheet: (column * FRAME_SIZE, row * FRAME_SIZE),
+                        size_in_sheet: (FRAME_SIZE, FRAME_SIZE),
+                    },
+                )
+            })
+            .collect();
+        let num_rows = (num_frames + FRAMES_PER_ROW - 1) / FRAMES_PER_ROW;
+        let image = DynamicImage::new_rgba8(FRAMES_PER_ROW * FRAME_SIZE, num_rows * FRAME_SIZE);
+
+        (sheet, Atlas::new(image, layout))
+    }
+
+    #[test]
+    fn metadata_generation_scales_to_large_sheets() {
+        let (sheet, atlas) = synthetic_sheet(5_000);
+        let export_settings = sheet.export_settings().clone().unwrap();
+
+        let metadata =
+            generate_sheet_metadata(&sheet, Path::new(SYNTHETIC_SHEET), &export_settings, &atlas)
+                .unwrap();
+        assert!(!metadata.is_empty());
+
+        let globals =
+            Sheet::new(&sheet, Path::new(SYNTHETIC_SHEET), &export_settings, &atlas).unwrap();
+        assert_eq!(globals.frames.len(), 5_000);
+        for (index, frame) in globals.frames.iter().enumerate() {
+            assert_eq!(frame.index, index as i32);
+        }
+
+        let frame_indices = sheet
+            .sorted_frames()
+            .into_iter()
+            .enumerate()
+            .map(|(index, frame)| (frame.source().to_owned(), index as i32))
+            .collect::<HashMap<_, _>>();
+        for ((_, animation), animation_data) in sheet
+            .sorted_animations()
+            .into_iter()
+            .zip(&globals.animations)
+        {
+            let sequence = animation.sequence(sheet::Direction::North).unwrap();
+            let sequence_data = animation_data
+                .sequences
+                .iter()
+                .find(|s| s.direction == sheet::Direction::North)
+                .unwrap();
+            for (keyframe, keyframe_data) in sequence.keyframes_iter().zip(&sequence_data.keyframes)
+            {
+                assert_eq!(keyframe_data.frame.index, frame_indices[keyframe.frame()]);
+            }
+        }
+    }
+
+    // Timing depends on the machine running the tests, run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn metadata_generation_is_fast_for_large_sheets() {
+        let (sheet, atlas) = synthetic_sheet(5_000);
+        let export_settings = sheet.export_settings().clone().unwrap();
+
+        let start = Instant::now();
+        generate_sheet_metadata(&sheet, Path::new(SYNTHETIC_SHEET), &export_settings, &atlas)
+            .unwrap();
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed < Duration::from_secs(5),
+            "Generating metadata for 5,000 frames took {elapsed:?}"
+        );
+    }
+
+    #[test]
+    fn templates_can_use_partials() {
+        let (sheet, atlas) = synthetic_sheet(3);
+        let export_settings = sheet.export_settings().clone().unwrap();
+        let template = Template::new("test-data/templates/partials.template").unwrap();
+        let input = TemplateInput {
+            sheet: Sheet::new(&sheet, Path::new(SYNTHETIC_SHEET), &export_settings, &atlas)
+                .unwrap(),
+            variables: &BTreeMap::new(),
+        };
+        let metadata = template.render(&input).unwrap();
+        assert!(metadata.contains("frame 000: 16x16"));
+        assert!(metadata.contains("frame 002: 16x16"));
+    }
+
+    #[test]
+    fn lists_template_partials() {
+        let partials = template_partials(PathBuf::from("test-data/templates/partials.template"));
+        assert_eq!(
+            partials,
+            vec![PathBuf::from("test-data/templates/partials/frame.template")]
+        );
+        assert!(template_partials("test-data/export.template").is_empty());
+    }
+
+    #[test]
+    fn templates_have_formatting_helpers() {
+        let template = Template::new("test-data/templates/partials.template").unwrap();
+        let render = |source: &str, data: serde_json::Value| {
+            template.handlebars.render_template(source, &data).unwrap()
+        };
+        let no_data = serde_json::Value::Null;
+
+        assert_eq!(render("{{ pad 7 3 }}", no_data.clone()), "007");
+        assert_eq!(render("{{ pad -7 3 }}", no_data.clone()), "-07");
+        assert_eq!(render("{{ format_number 1.5 2 }}", no_data.clone()), "1.50");
+        assert_eq!(render("{{ format_number 2 0 }}", no_data.clone()), "2");
+        assert_eq!(
+            render(
+                "{{{ json_escape text }}}",
+                serde_json::json!({ "text": "say \"hi\"\n" })
+            ),
+            "say \\\"hi\\\"\\n"
+        );
+        assert_eq!(
+            render(
+                "{{{ xml_escape text }}}",
+                serde_json::json!({ "text": "<a & 'b'>" })
+            ),
+            "&lt;a &amp; &apos;b&apos;&gt;"
+        );
+        assert_eq!(
+            render("{{ #if (eq 1 1) }}yes{{ /if }}", no_data.clone()),
+            "yes"
+        );
+        assert_eq!(
+            render(
+                "{{ #if (lt 2 1) }}yes{{ else }}no{{ /if }}",
+                no_data.clone()
+            ),
+            "no"
+        );
+        assert_eq!(
+            render("{{ direction_angle \"NorthWest\" }}", no_data.clone()),
+            "135"
+        );
+        assert_eq!(render("{{ direction_angle \"Up\" }}", no_data), "");
+    }
+
+    #[test]
+    fn exposes_timings_names_and_totals() {
+        let (sheet, atlas) = synthetic_sheet(50);
+        let export_settings = sheet.export_settings().clone().unwrap();
+        let globals =
+            Sheet::new(&sheet, Path::new(SYNTHETIC_SHEET), &export_settings, &atlas).unwrap();
+
+        assert_eq!(globals.sheet_name, "synthetic");
+        assert_eq!(globals.frames[7].file_name, "frame-00007.png");
+        assert_eq!(globals.frames[7].file_stem, "frame-00007");
+
+        let sequence = globals.animations[0]
+            .sequences
+            .iter()
+            .find(|s| s.direction == sheet::Direction::North)
+            .unwrap();
+        assert_eq!(sequence.direction_angle, 90);
+        assert_eq!(sequence.frame_count, 25);
+        assert_eq!(sequence.keyframes.len(), 25);
+
+        let mut start_time = 0;
+        for keyframe in &sequence.keyframes {
+            assert_eq!(keyframe.start_time, start_time);
+            start_time += keyframe.duration;
+        }
+        assert_eq!(sequence.duration, start_time);
+    }
+
+    #[test]
+    fn applies_coordinate_conventions() {
+        let (sheet, atlas) = synthetic_sheet(3);
+        let default_settings = sheet.export_settings().clone().unwrap();
+        let mut settings = default_settings.clone().with_any_paths();
+        let mut conventions = sheet::ExportCoordinates::default();
+        conventions.set_origin(sheet::ExportOrigin::BottomCenter);
+        conventions.set_y_axis(sheet::ExportYAxis::Up);
+        conventions.set_uv_coordinates(true);
+        settings.set_coordinates(conventions);
+        let settings = settings.with_absolute_paths().unwrap();
+
+        let default_globals = Sheet::new(
+            &sheet,
+            Path::new(SYNTHETIC_SHEET),
+            &default_settings,
+            &atlas,
+        )
+        .unwrap();
+        let globals = Sheet::new(&sheet, Path::new(SYNTHETIC_SHEET), &settings, &atlas).unwrap();
+
+        let default_frame = &default_globals.frames[1];
+        assert_eq!((default_frame.x, default_frame.y), (16, 0));
+        assert_eq!(default_frame.uv, None);
+        let frame = &globals.frames[1];
+        assert_eq!((frame.x, frame.y), (24, 0));
+        assert_eq!(
+            frame.uv,
+            Some(UvRect {
+                u0: 16.0 / 1024.0,
+                v0: 0.0,
+                u1: 32.0 / 1024.0,
+                v1: 1.0,
+            })
+        );
+
+        let keyframe = |globals: &Sheet| {
+            let sequence = globals.animations[0]
+                .sequences
+                .iter()
+                .find(|s| s.direction == sheet::Direction::North)
+                .unwrap();
+            let keyframe = &sequence.keyframes[0];
+            (keyframe.x, keyframe.y)
+        };
+        assert_eq!(keyframe(&default_globals), (-8, -8));
+        assert_eq!(keyframe(&globals), (0, -8));
+
+        let coordinates = Coordinates::new(conventions, &atlas);
+        let hitbox = Hitbox::new("hitbox".to_owned(), &sheet::Hitbox::new(), &coordinates).unwrap();
+        assert_eq!((hitbox.x, hitbox.y), (0, -10));
+        assert_eq!((hitbox.width, hitbox.height), (20, 20));
+    }
+}
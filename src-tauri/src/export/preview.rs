@@ -0,0 +1,75 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use super::{
+    generate_sheet_metadata, load_frames, pack_sheet, ExportError, ExportProgress, ExportStage,
+};
+use crate::features::texture_cache;
+use crate::sheet::{Absolute, ExportSettings, Sheet};
+
+pub struct ExportPreview {
+    pub metadata: String,
+    pub atlas_image_width: u32,
+    pub atlas_image_height: u32,
+    pub atlas_image_png: Vec<u8>,
+}
+
+impl ExportPreview {
+    pub fn atlas_image_data_url(&self) -> String {
+        format!(
+            "data:image/png;base64,{}",
+            STANDARD.encode(&self.atlas_image_png)
+        )
+    }
+}
+
+// Renders the atlas image and metadata that exporting the sheet with the
+// given settings would produce, without writing any file. External exporters
+// are not run, the preview shows the input they would receive instead.
+pub fn preview_sheet(
+    sheet: &Sheet<Absolute>,
+    sheet_path: &Path,
+    export_settings: &ExportSettings<Absolute>,
+    texture_cache: texture_cache::Handle,
+    progress: &ExportProgress,
+) -> Result<ExportPreview, ExportError> {
+    let bitmaps = load_frames(sheet, texture_cache, progress)?;
+    let atlas = pack_sheet(&bitmaps, progress)?;
+    progress.begin(ExportStage::RenderingTemplate)?;
+    let metadata = generate_sheet_metadata(sheet, sheet_path, export_settings, &atlas)?;
+    progress.check_cancelled()?;
+    let mut atlas_image_png = Vec::new();
+    atlas.image().write_to(
+        &mut Cursor::new(&mut atlas_image_png),
+        image::ImageFormat::Png,
+    )?;
+    Ok(ExportPreview {
+        metadata,
+        atlas_image_width: atlas.image().width(),
+        atlas_image_height: atlas.image().height(),
+        atlas_image_png,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn atlas_image_data_url_embeds_png() {
+        let preview = ExportPreview {
+            metadata: String::new(),
+            atlas_image_width: 1,
+            atlas_image_height: 1,
+            atlas_image_png: b"foobar".to_vec(),
+        };
+        assert_eq!(
+            preview.atlas_image_data_url(),
+            "data:image/png;base64,Zm9vYmFy"
+        );
+    }
+}
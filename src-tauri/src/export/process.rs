@@ -0,0 +1,74 @@
+use std::{
+    io::{Read, Write},
+    process::{Command, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+pub(super) enum ProcessError {
+    LaunchError(std::io::Error),
+    IoError(std::io::Error),
+    Timeout,
+}
+
+// Runs a process to completion, feeding it `input` on stdin and collecting its output.
+// The process is killed if it does not exit before the timeout.
+pub(super) fn run_process(
+    command: &mut Command,
+    input: &[u8],
+    timeout: Duration,
+) -> Result<Output, ProcessError> {
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = command.spawn().map_err(ProcessError::LaunchError)?;
+
+    // Processes are free to exit without reading all their input, so write errors are ignored
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_owned();
+    let writer = thread::spawn(move || stdin.write_all(&input).ok());
+    let stdout = read_to_end(child.stdout.take().unwrap());
+    let stderr = read_to_end(child.stderr.take().unwrap());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                child.kill().ok();
+                child.wait().ok();
+                return Err(ProcessError::Timeout);
+            }
+            Err(e) => return Err(ProcessError::IoError(e)),
+        }
+    };
+
+    writer.join().ok();
+    let stdout = stdout.join().unwrap().map_err(ProcessError::IoError)?;
+    let stderr = stderr.join().unwrap().map_err(ProcessError::IoError)?;
+
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+fn read_to_end<R: Read + Send + 'static>(
+    mut source: R,
+) -> thread::JoinHandle<Result<Vec<u8>, std::io::Error>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        source.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
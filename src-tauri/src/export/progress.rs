@@ -0,0 +1,69 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use super::ExportError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExportStage {
+    LoadingTextures,
+    Packing,
+    Compositing,
+    RenderingTemplate,
+    Writing,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Reports which stage an export is in, and lets the export stop early when
+// its cancellation token is cancelled.
+pub struct ExportProgress {
+    on_stage: Box<dyn Fn(ExportStage) + Send + Sync>,
+    cancellation_token: CancellationToken,
+}
+
+impl ExportProgress {
+    pub fn new<F: Fn(ExportStage) + Send + Sync + 'static>(
+        on_stage: F,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            on_stage: Box::new(on_stage),
+            cancellation_token,
+        }
+    }
+
+    pub(super) fn begin(&self, stage: ExportStage) -> Result<(), ExportError> {
+        self.check_cancelled()?;
+        (self.on_stage)(stage);
+        Ok(())
+    }
+
+    pub(super) fn is_cancelled(&self) -> bool {
+        self.cancellation_token.is_cancelled()
+    }
+
+    pub(super) fn check_cancelled(&self) -> Result<(), ExportError> {
+        if self.is_cancelled() {
+            Err(ExportError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
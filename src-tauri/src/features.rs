@@ -1,8 +1,18 @@
 pub mod app_updates;
+#[cfg(windows)]
+pub mod automation;
+pub mod autosave;
+pub mod backups;
 pub mod clipboard_analysis;
+pub mod command_line;
+pub mod external_modifications;
+pub mod macros;
 pub mod missing_textures;
 pub mod onboarding;
 pub mod recent_documents;
+pub mod saved_histories;
+pub mod saved_views;
+pub mod settings;
 pub mod single_instance;
 pub mod template_hot_reload;
 pub mod texture_cache;
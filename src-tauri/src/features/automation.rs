@@ -0,0 +1,792 @@
+use interprocess::{os::windows::named_pipe::DuplexMsgPipeStream, ReliableReadMsg};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use squeak::Response;
+use std::{
+    ffi::OsStr,
+    io::{self, Write},
+    iter::once,
+    mem::size_of,
+    os::windows::{
+        ffi::OsStrExt,
+        io::{FromRawHandle, RawHandle},
+    },
+    path::PathBuf,
+    ptr::null_mut,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::channel,
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use thiserror::Error;
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, GetLastError, ERROR_PIPE_CONNECTED, HANDLE, INVALID_HANDLE_VALUE},
+    Security::{
+        Authorization::{ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1},
+        PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES,
+    },
+    Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+    System::{
+        Memory::LocalFree,
+        Pipes::{
+            ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_MESSAGE, PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_TYPE_MESSAGE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+        },
+    },
+};
+
+use crate::{api::Api, app::TigerApp, dto};
+
+// Scripts connect to a named pipe and exchange JSON-RPC 2.0 messages, one request per message.
+// Methods and their (camelCase) parameters are the same as the Tauri commands.
+static PIPE_NAME_PREFIX: &str = "tiger-automation";
+// Only grants access to the owner of the pipe, which is the user running Tiger
+static PIPE_SECURITY_DESCRIPTOR: &str = "D:P(A;;GA;;;OW)";
+const PIPE_BUFFER_SIZE: u32 = 64 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct Reply {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ReplyError>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReplyError {
+    code: i32,
+    message: String,
+}
+
+#[derive(Error, Debug)]
+enum AutomationError {
+    #[error("Invalid JSON-RPC request")]
+    InvalidRequest,
+    #[error("Unknown method `{0}`")]
+    MethodNotFound(String),
+    #[error("Invalid parameters: {0}")]
+    InvalidParams(#[from] serde_json::Error),
+    #[error("Operation `{0}` failed")]
+    OperationFailed(String),
+    #[error("Automation server is disabled in settings")]
+    Disabled,
+}
+
+impl AutomationError {
+    fn code(&self) -> i32 {
+        match self {
+            AutomationError::InvalidRequest => -32600,
+            AutomationError::MethodNotFound(_) => -32601,
+            AutomationError::InvalidParams(_) => -32602,
+            AutomationError::OperationFailed(_) => -32000,
+            AutomationError::Disabled => -32001,
+        }
+    }
+}
+
+pub fn init<A: TigerApp + Api + Clone + Send + Sync + 'static>(app: A) {
+    let (tx, rx) = channel();
+    {
+        let state_handle = app.state();
+        let state = state_handle.lock();
+        tx.send(state.settings().automation_server).ok();
+        state.settings_delegate().subscribe(move |settings| {
+            tx.send(settings.automation_server).ok();
+            Response::StaySubscribed
+        });
+    }
+
+    thread::Builder::new()
+        .name("automation-settings-thread".to_owned())
+        .spawn(move || {
+            let mut server: Option<Server> = None;
+            loop {
+                let Ok(enabled) = rx.recv() else { break };
+                if enabled && server.is_none() {
+                    server = Server::start(app.clone())
+                        .map_err(|e| error!("Error while starting automation server: {e}"))
+                        .ok();
+                } else if !enabled {
+                    if let Some(server) = server.take() {
+                        server.stop();
+                    }
+                }
+            }
+        })
+        .unwrap();
+}
+
+struct Server {
+    name: String,
+    stopping: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Server {
+    fn start<A: TigerApp + Api + Clone + Send + Sync + 'static>(app: A) -> io::Result<Self> {
+        let name = pipe_name();
+        let security_descriptor = SecurityDescriptor::new(PIPE_SECURITY_DESCRIPTOR)?;
+        // The first instance is created before returning, so that `stop` always
+        // has a pipe to connect to
+        let first_instance = create_pipe_instance(&name, &security_descriptor, true)?;
+        let stopping = Arc::new(AtomicBool::new(false));
+        let thread = thread::Builder::new()
+            .name("automation-server-thread".to_owned())
+            .spawn({
+                let name = name.clone();
+                let stopping = stopping.clone();
+                move || {
+                    let mut instance = first_instance;
+                    loop {
+                        let connected = connect_pipe_instance(instance);
+                        if stopping.load(Ordering::Relaxed) {
+                            unsafe { CloseHandle(instance) };
+                            break;
+                        }
+                        let next_instance =
+                            match create_pipe_instance(&name, &security_descriptor, false) {
+                                Ok(next_instance) => next_instance,
+                                Err(e) => {
+                                    error!("Error while creating automation pipe: {e}");
+                                    unsafe { CloseHandle(instance) };
+                                    break;
+                                }
+                            };
+                        match connected {
+                            Ok(()) => {
+                                let stream = unsafe {
+                                    DuplexMsgPipeStream::from_raw_handle(instance as RawHandle)
+                                };
+                                thread::Builder::new()
+                                    .name("automation-client-thread".to_owned())
+                                    .spawn({
+                                        let app = app.clone();
+                                        move || handle_client(app, stream)
+                                    })
+                                    .unwrap();
+                            }
+                            Err(e) => {
+                                error!("Error accepting automation client: {e}");
+                                unsafe { CloseHandle(instance) };
+                            }
+                        }
+                        instance = next_instance;
+                    }
+                }
+            })?;
+        Ok(Self {
+            name,
+            stopping,
+            thread,
+        })
+    }
+
+    // Clients which are already connected are not disconnected, but their
+    // requests are rejected while the server is disabled in settings
+    fn stop(self) {
+        self.stopping.store(true, Ordering::Relaxed);
+        // Wakes up the server thread, which is waiting for a client to connect
+        while !self.thread.is_finished() {
+            DuplexMsgPipeStream::connect(OsStr::new(&self.name)).ok();
+            thread::sleep(Duration::from_millis(10));
+        }
+        self.thread.join().ok();
+    }
+}
+
+// Scripts connect to `tiger-automation-<process id of Tiger>`, so that multiple
+// Tiger processes do not conflict. Starting the server fails if another program
+// already created a pipe with this name.
+fn pipe_name() -> String {
+    format!("{PIPE_NAME_PREFIX}-{}", std::process::id())
+}
+
+fn create_pipe_instance(
+    name: &str,
+    security_descriptor: &SecurityDescriptor,
+    first: bool,
+) -> io::Result<HANDLE> {
+    let path = OsStr::new(&format!(r"\\.\pipe\{name}"))
+        .encode_wide()
+        .chain(once(0))
+        .collect::<Vec<_>>();
+    let security_attributes = SECURITY_ATTRIBUTES {
+        nLength: size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: security_descriptor.0,
+        bInheritHandle: 0,
+    };
+    let mut open_mode = PIPE_ACCESS_DUPLEX;
+    if first {
+        open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+    }
+    let handle = unsafe {
+        CreateNamedPipeW(
+            path.as_ptr(),
+            open_mode,
+            PIPE_TYPE_MESSAGE | PIPE_READMODE_MESSAGE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            PIPE_BUFFER_SIZE,
+            PIPE_BUFFER_SIZE,
+            0,
+            &security_attributes,
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(handle)
+    }
+}
+
+// Blocks until a client connects to the pipe instance
+fn connect_pipe_instance(instance: HANDLE) -> io::Result<()> {
+    let connected = unsafe { ConnectNamedPipe(instance, null_mut()) } != 0;
+    // Clients which connected before this call are reported with `ERROR_PIPE_CONNECTED`
+    if connected || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+struct SecurityDescriptor(PSECURITY_DESCRIPTOR);
+
+// The descriptor is only read after creation
+unsafe impl Send for SecurityDescriptor {}
+
+impl SecurityDescriptor {
+    fn new(sddl: &str) -> io::Result<Self> {
+        let sddl = OsStr::new(sddl)
+            .encode_wide()
+            .chain(once(0))
+            .collect::<Vec<_>>();
+        let mut descriptor: PSECURITY_DESCRIPTOR = null_mut();
+        let success = unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                sddl.as_ptr(),
+                SDDL_REVISION_1,
+                &mut descriptor,
+                null_mut(),
+            )
+        } != 0;
+        if success {
+            Ok(Self(descriptor))
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+impl Drop for SecurityDescriptor {
+    fn drop(&mut self) {
+        unsafe { LocalFree(self.0 as _) };
+    }
+}
+
+fn handle_client<A: TigerApp + Api>(app: A, mut stream: DuplexMsgPipeStream) {
+    let mut buffer = vec![0; PIPE_BUFFER_SIZE as usize];
+    loop {
+        let message = match stream.read_msg(&mut buffer) {
+            Ok(Ok(size)) => buffer[..size].to_vec(),
+            Ok(Err(message)) => message,
+            Err(_) => return,
+        };
+        let Some(reply) = handle_message(&app, &message) else {
+            continue;
+        };
+        let reply = serde_json::to_vec(&reply).unwrap();
+        if let Err(e) = stream.write_all(&reply) {
+            error!("Error writing to automation client: {e}");
+            return;
+        }
+    }
+}
+
+// Returns `None` for notifications, which do not expect a reply
+fn handle_message<A: TigerApp + Api>(app: &A, message: &[u8]) -> Option<Reply> {
+    let (id, result) = match serde_json::from_slice::<Request>(message) {
+        Ok(request) if request.jsonrpc == "2.0" => {
+            let result = handle_request(app, request.method, request.params);
+            (request.id?, result)
+        }
+        _ => (Value::Null, Err(AutomationError::InvalidRequest)),
+    };
+    Some(match result {
+        Ok(value) => Reply {
+            jsonrpc: "2.0",
+            id,
+            result: Some(value),
+            error: None,
+        },
+        Err(e) => Reply {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(ReplyError {
+                code: e.code(),
+                message: e.to_string(),
+            }),
+        },
+    })
+}
+
+fn handle_request<A: TigerApp + Api>(
+    app: &A,
+    method: String,
+    params: Value,
+) -> Result<Value, AutomationError> {
+    if !app.state().lock().settings().automation_server {
+        return Err(AutomationError::Disabled);
+    }
+    let params = match params {
+        Value::Null => json!({}),
+        p => p,
+    };
+    let result = dispatch(app, &method, params)?;
+    // Changes made by scripts are not visible to the frontend otherwise
+    app.replace_state();
+    result.ok_or(AutomationError::OperationFailed(method))
+}
+
+fn to_value<T: Serialize>(result: Result<T, ()>) -> Result<Option<Value>, AutomationError> {
+    match result {
+        Ok(value) => Ok(Some(serde_json::to_value(value)?)),
+        Err(()) => Ok(None),
+    }
+}
+
+macro_rules! call {
+    ($app:expr, $params:expr, $method:ident($($arg:ident: $type:ty),* $(,)?)) => {{
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            $($arg: $type,)*
+        }
+        let Params { $($arg,)* } = serde_json::from_value::<Params>($params)?;
+        to_value(Api::$method($app, $($arg),*))
+    }};
+    ($app:expr, $params:expr, async $method:ident($($arg:ident: $type:ty),* $(,)?)) => {{
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Params {
+            $($arg: $type,)*
+        }
+        let Params { $($arg,)* } = serde_json::from_value::<Params>($params)?;
+        to_value(tauri::async_runtime::block_on(Api::$method($app, $($arg),*)))
+    }};
+}
+
+fn dispatch<A: TigerApp + Api>(
+    app: &A,
+    method: &str,
+    params: Value,
+) -> Result<Option<Value>, AutomationError> {
+    match method {
+        "acknowledge_error" => call!(app, params, acknowledge_error()),
+        "allow_exporter" => call!(app, params, allow_exporter(exporter: PathBuf)),
+        "apply_direction_preset" => call!(
+            app,
+            params,
+            apply_direction_preset(preset: dto::DirectionPreset)
+        ),
+        "begin_drag_and_drop_frame" => call!(
+            app,
+            params,
+            begin_drag_and_drop_frame(frame: PathBuf)
+        ),
+        "begin_drag_and_drop_keyframe" => call!(
+            app,
+            params,
+            begin_drag_and_drop_keyframe(direction: dto::Direction, index: usize)
+        ),
+        "begin_drag_keyframe_duration" => call!(
+            app,
+            params,
+            begin_drag_keyframe_duration(direction: dto::Direction, index: usize)
+        ),
+        "begin_export_as" => call!(app, params, begin_export_as()),
+        "begin_macro_recording" => call!(app, params, begin_macro_recording()),
+        "begin_nudge_hitbox" => call!(app, params, begin_nudge_hitbox(name: String)),
+        "begin_nudge_keyframe" => call!(
+            app,
+            params,
+            begin_nudge_keyframe(direction: dto::Direction, index: usize)
+        ),
+        "begin_relocate_frames" => call!(app, params, begin_relocate_frames()),
+        "begin_rename_animation" => call!(
+            app,
+            params,
+            begin_rename_animation(animation_name: String)
+        ),
+        "begin_rename_hitbox" => call!(app, params, begin_rename_hitbox(hitbox_name: String)),
+        "begin_rename_selection" => call!(app, params, begin_rename_selection()),
+        "begin_resize_hitbox" => call!(
+            app,
+            params,
+            begin_resize_hitbox(name: String, axis: dto::ResizeAxis)
+        ),
+        "browse_selection" => call!(
+            app,
+            params,
+            browse_selection(direction: dto::BrowseDirection, shift: bool)
+        ),
+        "browse_to_end" => call!(app, params, browse_to_end(shift: bool)),
+        "browse_to_start" => call!(app, params, browse_to_start(shift: bool)),
+        "cancel_close_document" => call!(app, params, cancel_close_document()),
+        "cancel_export" => call!(app, params, cancel_export(document: PathBuf)),
+        "cancel_export_as" => call!(app, params, cancel_export_as()),
+        "cancel_macro_recording" => call!(app, params, cancel_macro_recording()),
+        "cancel_relocate_frames" => call!(app, params, cancel_relocate_frames()),
+        "cancel_rename" => call!(app, params, cancel_rename()),
+        "center_workbench" => call!(app, params, center_workbench()),
+        "clear_selection" => call!(app, params, clear_selection()),
+        "close_about_dialog" => call!(app, params, close_about_dialog()),
+        "close_all_documents" => call!(app, params, close_all_documents()),
+        "close_current_document" => call!(app, params, close_current_document()),
+        "close_document" => call!(app, params, close_document(path: PathBuf)),
+        "close_without_saving" => call!(app, params, close_without_saving()),
+        "copy" => call!(app, params, copy()),
+        "create_animation" => call!(app, params, create_animation()),
+        "create_export_template_variable" => call!(app, params, create_export_template_variable()),
+        "create_hitbox" => call!(app, params, create_hitbox()),
+        "cut" => call!(app, params, cut()),
+        "delete_animation" => call!(app, params, delete_animation(name: String)),
+        "delete_export_template_variable" => {
+            call!(app, params, delete_export_template_variable(name: String))
+        }
+        "delete_frame" => call!(app, params, delete_frame(path: PathBuf)),
+        "delete_hitbox" => call!(app, params, delete_hitbox(name: String)),
+        "delete_macro" => call!(app, params, delete_macro(name: String)),
+        "delete_selected_animations" => call!(app, params, delete_selected_animations()),
+        "delete_selected_frames" => call!(app, params, delete_selected_frames()),
+        "delete_selected_hitboxes" => call!(app, params, delete_selected_hitboxes()),
+        "delete_selected_keyframes" => call!(app, params, delete_selected_keyframes()),
+        "delete_selection" => call!(app, params, delete_selection()),
+        "disable_sprite_darkening" => call!(app, params, disable_sprite_darkening()),
+        "discard_recoverable_document" => call!(
+            app,
+            params,
+            discard_recoverable_document(path: PathBuf)
+        ),
+        "drop_frame_on_timeline" => call!(
+            app,
+            params,
+            drop_frame_on_timeline(direction: dto::Direction, index: usize)
+        ),
+        "drop_keyframe_on_timeline" => call!(
+            app,
+            params,
+            drop_keyframe_on_timeline(direction: dto::Direction, index: usize)
+        ),
+        "edit_animation" => call!(app, params, edit_animation(name: String)),
+        "enable_sprite_darkening" => call!(app, params, enable_sprite_darkening()),
+        "end_drag_and_drop_frame" => call!(app, params, end_drag_and_drop_frame()),
+        "end_drag_and_drop_keyframe" => call!(app, params, end_drag_and_drop_keyframe()),
+        "end_drag_keyframe_duration" => call!(app, params, end_drag_keyframe_duration()),
+        "end_export_as" => call!(app, params, async end_export_as()),
+        "end_macro_recording" => call!(app, params, end_macro_recording(name: String)),
+        "end_nudge_hitbox" => call!(app, params, end_nudge_hitbox()),
+        "end_nudge_keyframe" => call!(app, params, end_nudge_keyframe()),
+        "end_relocate_frames" => call!(app, params, end_relocate_frames()),
+        "end_rename_animation" => call!(app, params, end_rename_animation(new_name: String)),
+        "end_rename_hitbox" => call!(app, params, end_rename_hitbox(new_name: String)),
+        "end_resize_hitbox" => call!(app, params, end_resize_hitbox()),
+        "export" => call!(app, params, async export()),
+        "filter_animations" => call!(app, params, filter_animations(search_query: String)),
+        "filter_frames" => call!(app, params, filter_frames(search_query: String)),
+        "finalize_startup" => call!(app, params, finalize_startup()),
+        "focus_document" => call!(app, params, focus_document(path: PathBuf)),
+        "focus_next_document" => call!(app, params, focus_next_document()),
+        "focus_previous_document" => call!(app, params, focus_previous_document()),
+        "get_state" => call!(app, params, get_state()),
+        "get_texture_cache_stats" => call!(app, params, get_texture_cache_stats()),
+        "hide_hitboxes" => call!(app, params, hide_hitboxes()),
+        "hide_origin" => call!(app, params, hide_origin()),
+        "hide_sprite" => call!(app, params, hide_sprite()),
+        "import_frames" => call!(app, params, import_frames(paths: Vec<PathBuf>)),
+        "jump_to_animation_end" => call!(app, params, jump_to_animation_end()),
+        "jump_to_animation_start" => call!(app, params, jump_to_animation_start()),
+        "jump_to_history_index" => call!(app, params, jump_to_history_index(index: usize)),
+        "jump_to_next_frame" => call!(app, params, jump_to_next_frame()),
+        "jump_to_previous_frame" => call!(app, params, jump_to_previous_frame()),
+        "keep_local_changes" => call!(app, params, keep_local_changes(path: PathBuf)),
+        "list_backups" => call!(app, params, list_backups(document: PathBuf)),
+        "lock_hitboxes" => call!(app, params, lock_hitboxes()),
+        "new_document" => call!(app, params, new_document(path: PathBuf)),
+        "nudge_selection" => call!(
+            app,
+            params,
+            nudge_selection(direction: dto::NudgeDirection, large_nudge: bool)
+        ),
+        "open_about_dialog" => call!(app, params, open_about_dialog()),
+        "open_documents" => call!(app, params, async open_documents(paths: Vec<PathBuf>)),
+        "open_startup_documents" => call!(app, params, async open_startup_documents()),
+        "pan" => call!(app, params, pan(delta: (f32, f32))),
+        "pan_timeline" => call!(app, params, pan_timeline(delta: f32)),
+        "paste" => call!(app, params, paste()),
+        "pause" => call!(app, params, pause()),
+        "play" => call!(app, params, play()),
+        "play_macro" => call!(app, params, play_macro(name: String)),
+        "play_macro_on_animations" => call!(
+            app,
+            params,
+            play_macro_on_animations(name: String, animations: Vec<String>)
+        ),
+        "preview_export" => call!(app, params, async preview_export()),
+        "recover_document" => call!(app, params, async recover_document(path: PathBuf)),
+        "redo" => call!(app, params, redo()),
+        "reload_document" => call!(app, params, reload_document(path: PathBuf)),
+        "relocate_frame" => call!(app, params, relocate_frame(from: PathBuf, to: PathBuf)),
+        "rename_export_template_variable" => call!(
+            app,
+            params,
+            rename_export_template_variable(old_name: String, new_name: String)
+        ),
+        "request_exit" => call!(app, params, request_exit()),
+        "request_install_update" => call!(app, params, request_install_update()),
+        "reset_timeline_zoom" => call!(app, params, reset_timeline_zoom()),
+        "reset_workbench_zoom" => call!(app, params, reset_workbench_zoom()),
+        "restore_backup" => call!(
+            app,
+            params,
+            async restore_backup(document: PathBuf, backup: PathBuf)
+        ),
+        "save" => call!(app, params, async save()),
+        "save_all" => call!(app, params, async save_all()),
+        "save_as" => call!(app, params, async save_as(new_path: PathBuf)),
+        "scrub_timeline" => call!(app, params, scrub_timeline(time_millis: u64)),
+        "select_all" => call!(app, params, select_all()),
+        "select_animation" => call!(
+            app,
+            params,
+            select_animation(name: String, shift: bool, ctrl: bool)
+        ),
+        "select_direction" => call!(app, params, select_direction(direction: dto::Direction)),
+        "select_frame" => call!(app, params, select_frame(path: PathBuf, shift: bool, ctrl: bool)),
+        "select_hitbox" => call!(app, params, select_hitbox(name: String, shift: bool, ctrl: bool)),
+        "select_keyframe" => call!(
+            app,
+            params,
+            select_keyframe(direction: dto::Direction, index: usize, shift: bool, ctrl: bool)
+        ),
+        "set_animation_looping" => call!(app, params, set_animation_looping(is_looping: bool)),
+        "set_animations_list_offset" => call!(app, params, set_animations_list_offset(offset: f64)),
+        "set_automation_server" => call!(app, params, set_automation_server(enabled: bool)),
+        "set_export_atlas_image_file" => call!(
+            app,
+            params,
+            set_export_atlas_image_file(file: PathBuf)
+        ),
+        "set_export_exporter_file" => call!(app, params, set_export_exporter_file(file: PathBuf)),
+        "set_export_format" => call!(app, params, set_export_format(format: dto::ExportFormat)),
+        "set_export_metadata_file" => call!(app, params, set_export_metadata_file(file: PathBuf)),
+        "set_export_metadata_paths_root" => call!(
+            app,
+            params,
+            set_export_metadata_paths_root(directory: PathBuf)
+        ),
+        "set_export_on_save" => call!(app, params, set_export_on_save(enabled: bool)),
+        "set_export_origin" => call!(app, params, set_export_origin(origin: dto::ExportOrigin)),
+        "set_export_template_file" => call!(app, params, set_export_template_file(file: PathBuf)),
+        "set_export_template_variable_value" => call!(
+            app,
+            params,
+            set_export_template_variable_value(name: String, value: String)
+        ),
+        "set_export_uv_coordinates" => call!(app, params, set_export_uv_coordinates(enabled: bool)),
+        "set_export_y_axis" => call!(app, params, set_export_y_axis(y_axis: dto::ExportYAxis)),
+        "set_frames_list_mode" => call!(
+            app,
+            params,
+            set_frames_list_mode(list_mode: dto::ListMode)
+        ),
+        "set_frames_list_offset" => call!(app, params, set_frames_list_offset(offset: f64)),
+        "set_history_limit" => call!(app, params, set_history_limit(history_limit: usize)),
+        "set_hitbox_height" => call!(app, params, set_hitbox_height(height: u32)),
+        "set_hitbox_position_x" => call!(app, params, set_hitbox_position_x(x: i32)),
+        "set_hitbox_position_y" => call!(app, params, set_hitbox_position_y(y: i32)),
+        "set_hitbox_width" => call!(app, params, set_hitbox_width(width: u32)),
+        "set_hitboxes_list_offset" => call!(app, params, set_hitboxes_list_offset(offset: f64)),
+        "set_keyframe_duration" => call!(app, params, set_keyframe_duration(duration_millis: u64)),
+        "set_keyframe_offset_x" => call!(app, params, set_keyframe_offset_x(x: i32)),
+        "set_keyframe_offset_y" => call!(app, params, set_keyframe_offset_y(y: i32)),
+        "set_keyframe_snapping_base_duration" => call!(
+            app,
+            params,
+            set_keyframe_snapping_base_duration(duration_millis: u64)
+        ),
+        "set_max_backups" => call!(app, params, set_max_backups(max_backups: usize)),
+        "set_persist_history" => call!(app, params, set_persist_history(persist_history: bool)),
+        "set_post_export_commands" => call!(
+            app,
+            params,
+            set_post_export_commands(commands: Vec<String>)
+        ),
+        "set_restore_views" => call!(app, params, set_restore_views(restore_views: bool)),
+        "set_snap_keyframe_durations" => call!(
+            app,
+            params,
+            set_snap_keyframe_durations(snap: bool)
+        ),
+        "set_snap_keyframes_to_multiples_of_duration" => call!(
+            app,
+            params,
+            set_snap_keyframes_to_multiples_of_duration(snap: bool)
+        ),
+        "set_snap_keyframes_to_other_keyframes" => call!(
+            app,
+            params,
+            set_snap_keyframes_to_other_keyframes(snap: bool)
+        ),
+        "set_texture_cache_budget_mb" => call!(
+            app,
+            params,
+            set_texture_cache_budget_mb(texture_cache_budget_mb: usize)
+        ),
+        "set_timeline_offset" => call!(app, params, set_timeline_offset(offset_millis: f32)),
+        "set_timeline_zoom_amount" => call!(app, params, set_timeline_zoom_amount(amount: f32)),
+        "set_workbench_zoom_factor" => call!(
+            app,
+            params,
+            set_workbench_zoom_factor(zoom_factor: u32)
+        ),
+        "show_error_message" => call!(
+            app,
+            params,
+            show_error_message(title: String, summary: String, details: String)
+        ),
+        "show_hitboxes" => call!(app, params, show_hitboxes()),
+        "show_origin" => call!(app, params, show_origin()),
+        "show_sprite" => call!(app, params, show_sprite()),
+        "tick" => call!(app, params, tick(delta_time_millis: f64)),
+        "toggle_preserve_aspect_ratio" => call!(app, params, toggle_preserve_aspect_ratio()),
+        "undo" => call!(app, params, undo()),
+        "unlock_hitboxes" => call!(app, params, unlock_hitboxes()),
+        "update_drag_keyframe_duration" => call!(
+            app,
+            params,
+            update_drag_keyframe_duration(delta_millis: i64)
+        ),
+        "update_nudge_hitbox" => call!(
+            app,
+            params,
+            update_nudge_hitbox(displacement: (i32, i32), both_axis: bool)
+        ),
+        "update_nudge_keyframe" => call!(
+            app,
+            params,
+            update_nudge_keyframe(displacement: (i32, i32), both_axis: bool)
+        ),
+        "update_resize_hitbox" => call!(
+            app,
+            params,
+            update_resize_hitbox(displacement: (i32, i32), preserve_aspect_ratio: bool)
+        ),
+        "zoom_in_timeline" => call!(app, params, zoom_in_timeline()),
+        "zoom_in_timeline_around" => call!(app, params, zoom_in_timeline_around(fixed_point: f32)),
+        "zoom_in_workbench" => call!(app, params, zoom_in_workbench()),
+        "zoom_in_workbench_around" => call!(
+            app,
+            params,
+            zoom_in_workbench_around(fixed_point: (f32, f32))
+        ),
+        "zoom_out_timeline" => call!(app, params, zoom_out_timeline()),
+        "zoom_out_timeline_around" => call!(
+            app,
+            params,
+            zoom_out_timeline_around(fixed_point: f32)
+        ),
+        "zoom_out_workbench" => call!(app, params, zoom_out_workbench()),
+        "zoom_out_workbench_around" => call!(
+            app,
+            params,
+            zoom_out_workbench_around(fixed_point: (f32, f32))
+        ),
+        _ => Err(AutomationError::MethodNotFound(method.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::mock::TigerAppMock;
+
+    fn request(app: &TigerAppMock, method: &str, params: Value) -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let reply = handle_message(app, message.to_string().as_bytes()).unwrap();
+        serde_json::to_value(reply).unwrap()
+    }
+
+    #[test]
+    fn rejects_requests_while_disabled() {
+        let app = TigerAppMock::new();
+        let reply = request(&app, "get_state", Value::Null);
+        assert_eq!(reply["error"]["code"], -32001);
+    }
+
+    #[test]
+    fn can_call_api_operations() {
+        let app = TigerAppMock::new();
+        app.set_automation_server(true);
+
+        request(&app, "new_document", json!({ "path": "tmp.tiger" }));
+        request(
+            &app,
+            "import_frames",
+            json!({ "paths": ["frame_1", "frame_2"] }),
+        );
+        let reply = request(&app, "get_state", Value::Null);
+        assert_eq!(reply["id"], 1);
+        assert_eq!(
+            reply["result"]["documents"][0]["sheet"]["frames"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn reports_invalid_requests() {
+        let app = TigerAppMock::new();
+        app.set_automation_server(true);
+
+        let reply = request(&app, "not_a_method", Value::Null);
+        assert_eq!(reply["error"]["code"], -32601);
+
+        let reply = request(&app, "new_document", json!({ "wrong": 0 }));
+        assert_eq!(reply["error"]["code"], -32602);
+
+        let reply = serde_json::to_value(handle_message(&app, b"not json").unwrap()).unwrap();
+        assert_eq!(reply["error"]["code"], -32600);
+    }
+
+    #[test]
+    fn does_not_reply_to_notifications() {
+        let app = TigerAppMock::new();
+        app.set_automation_server(true);
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": "new_document",
+            "params": { "path": "tmp.tiger" }
+        });
+        assert!(handle_message(&app, message.to_string().as_bytes()).is_none());
+        assert_eq!(app.client_state().documents.len(), 1);
+    }
+}
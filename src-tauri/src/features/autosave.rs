@@ -0,0 +1,308 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use uuid::Uuid;
+
+use crate::app::TigerApp;
+
+#[cfg(not(test))]
+static PERIOD: Duration = Duration::from_secs(30);
+#[cfg(test)]
+static PERIOD: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct RecoverableDocument {
+    pub document: PathBuf,
+    pub recovery_file: PathBuf,
+}
+
+struct Autosave {
+    recovery_file: PathBuf,
+    version: i32,
+}
+
+pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) {
+    let (recovery_directory, manifest_file) = {
+        let paths_handle = app.paths();
+        let paths = paths_handle.lock();
+        (
+            paths.recovery_directory.clone(),
+            paths.recovery_manifest_file.clone(),
+        )
+    };
+
+    match read_from_disk(&manifest_file) {
+        Ok(mut documents) => {
+            documents.retain(is_newer_than_disk);
+            app.state().lock().set_recoverable_documents(documents);
+        }
+        Err(e) => error!("Error while reading list of recoverable documents: {e}"),
+    };
+
+    thread::Builder::new()
+        .name("autosave-thread".to_owned())
+        .spawn(move || {
+            let mut autosaves = HashMap::<PathBuf, Autosave>::new();
+            let mut manifest = None;
+            loop {
+                thread::sleep(PERIOD);
+
+                let mut dirty_documents = HashSet::new();
+                let mut sheets_to_write = Vec::new();
+                let recoverable_documents = {
+                    let state_handle = app.state();
+                    let state = state_handle.lock();
+                    for document in state.documents_iter().filter(|d| !d.is_saved()) {
+                        dirty_documents.insert(document.path().to_owned());
+                        let autosaved_version = autosaves.get(document.path()).map(|a| a.version);
+                        if autosaved_version != Some(document.version()) {
+                            sheets_to_write.push((
+                                document.path().to_owned(),
+                                document.version(),
+                                document.sheet().clone(),
+                            ));
+                        }
+                    }
+                    state.recoverable_documents().cloned().collect::<Vec<_>>()
+                };
+
+                for (path, version, sheet) in sheets_to_write {
+                    let recovery_file = match autosaves.get(&path) {
+                        Some(autosave) => autosave.recovery_file.clone(),
+                        None => recovery_file_name(&recovery_directory, &path),
+                    };
+                    if let Err(e) = std::fs::create_dir_all(&recovery_directory) {
+                        error!("Error while creating recovery directory: {e}");
+                        continue;
+                    }
+                    let mut document_directory = path.clone();
+                    document_directory.pop();
+                    match sheet.write_relative_to(&recovery_file, document_directory) {
+                        Ok(()) => {
+                            autosaves.insert(
+                                path,
+                                Autosave {
+                                    recovery_file,
+                                    version,
+                                },
+                            );
+                        }
+                        Err(e) => error!("Error while autosaving `{}`: {e}", path.display()),
+                    }
+                }
+
+                autosaves.retain(|path, _| dirty_documents.contains(path));
+
+                let mut new_manifest = autosaves
+                    .iter()
+                    .map(|(path, autosave)| RecoverableDocument {
+                        document: path.to_owned(),
+                        recovery_file: autosave.recovery_file.clone(),
+                    })
+                    .chain(recoverable_documents)
+                    .collect::<Vec<_>>();
+                new_manifest.sort();
+
+                if manifest.as_ref() != Some(&new_manifest) {
+                    if let Err(e) = write_to_disk(&new_manifest, &manifest_file) {
+                        error!("Error while saving list of recoverable documents: {e}");
+                        continue;
+                    }
+                    remove_orphaned_files(&recovery_directory, &new_manifest);
+                    manifest = Some(new_manifest);
+                }
+            }
+        })
+        .unwrap();
+}
+
+fn recovery_file_name(recovery_directory: &Path, document: &Path) -> PathBuf {
+    let stem = document
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    recovery_directory.join(format!("{stem}-{}.tiger", Uuid::new_v4()))
+}
+
+fn is_newer_than_disk(recoverable_document: &RecoverableDocument) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (
+        modified(&recoverable_document.recovery_file),
+        modified(&recoverable_document.document),
+    ) {
+        (Some(recovery_time), Some(document_time)) => recovery_time > document_time,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+fn remove_orphaned_files(recovery_directory: &Path, manifest: &[RecoverableDocument]) {
+    let Ok(entries) = std::fs::read_dir(recovery_directory) else {
+        return;
+    };
+    let referenced_files = manifest
+        .iter()
+        .map(|d| d.recovery_file.as_path())
+        .collect::<HashSet<_>>();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !referenced_files.contains(path.as_path()) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                error!(
+                    "Error while removing recovery file `{}`: {e}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+fn write_to_disk(
+    documents: &Vec<RecoverableDocument>,
+    destination: &Path,
+) -> Result<(), std::io::Error> {
+    let file = File::create(destination)?;
+    serde_json::to_writer_pretty(file, documents)?;
+    Ok(())
+}
+
+fn read_from_disk(source: &Path) -> Result<Vec<RecoverableDocument>, std::io::Error> {
+    if !source.exists() {
+        return Ok(vec![]);
+    }
+    let file = File::open(source)?;
+    let documents: Vec<RecoverableDocument> = serde_json::from_reader(file)?;
+    Ok(documents)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use retry::{delay::Fixed, retry};
+    use sugar_path::SugarPath;
+
+    use super::*;
+    use crate::app::mock::{TigerAppMock, TigerAppMockBuilder};
+
+    #[tokio::test]
+    async fn autosaves_dirty_documents() {
+        let app = TigerAppMock::new();
+        let manifest_file = app.paths().lock().recovery_manifest_file.clone();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.delete_animation("attack");
+
+        let autosaved = retry(Fixed::from(PERIOD).take(100), || {
+            let Ok(documents) = read_from_disk(&manifest_file) else {
+                return Err("Read error");
+            };
+            match documents.as_slice() {
+                [d] if d.document == PathBuf::from("test-data/samurai.tiger") => Ok(d.clone()),
+                _ => Err("Content mismatch"),
+            }
+        });
+        let recoverable_document = autosaved.unwrap();
+        assert!(recoverable_document.recovery_file.exists());
+
+        app.undo();
+        let cleaned_up = retry(Fixed::from(PERIOD).take(100), || {
+            match read_from_disk(&manifest_file) {
+                Ok(documents) if documents.is_empty() => Ok(()),
+                _ => Err("Recovery file still listed"),
+            }
+        });
+        assert_eq!(cleaned_up, Ok(()));
+        assert!(!recoverable_document.recovery_file.exists());
+    }
+
+    #[tokio::test]
+    async fn can_recover_document() {
+        let document = PathBuf::from("test-output/can_recover_document.tiger").resolve();
+        std::fs::remove_file(&document).ok();
+
+        let app_builder = TigerAppMockBuilder::new();
+        let recovery_directory = app_builder.paths().recovery_directory.clone();
+        let recovery_file = recovery_directory.join("can_recover_document.tiger");
+        std::fs::create_dir_all(&recovery_directory).unwrap();
+        std::fs::copy("test-data/samurai.tiger", &recovery_file).unwrap();
+        let manifest_file = app_builder.paths().recovery_manifest_file.clone();
+        write_to_disk(
+            &vec![RecoverableDocument {
+                document: document.clone(),
+                recovery_file,
+            }],
+            &manifest_file,
+        )
+        .unwrap();
+
+        let app = app_builder.build();
+        assert_eq!(app.client_state().recoverable_documents.len(), 1);
+
+        app.recover_document(&document).await;
+        assert!(app.client_state().recoverable_documents.is_empty());
+        assert_eq!(app.document().path, document);
+        assert!(app.document().has_unsaved_changes);
+        assert!(!app.document().sheet.animations.is_empty());
+    }
+
+    #[tokio::test]
+    async fn can_discard_recoverable_document() {
+        let document = PathBuf::from("test-output/can_discard_recoverable_document.tiger");
+
+        let app_builder = TigerAppMockBuilder::new();
+        let recovery_directory = app_builder.paths().recovery_directory.clone();
+        let recovery_file = recovery_directory.join("can_discard_recoverable_document.tiger");
+        std::fs::create_dir_all(&recovery_directory).unwrap();
+        std::fs::copy("test-data/samurai.tiger", &recovery_file).unwrap();
+        let manifest_file = app_builder.paths().recovery_manifest_file.clone();
+        write_to_disk(
+            &vec![RecoverableDocument {
+                document: document.clone(),
+                recovery_file: recovery_file.clone(),
+            }],
+            &manifest_file,
+        )
+        .unwrap();
+
+        let app = app_builder.build();
+        assert_eq!(app.client_state().recoverable_documents.len(), 1);
+        app.discard_recoverable_document(&document);
+        assert!(app.client_state().recoverable_documents.is_empty());
+
+        let removed_file = retry(Fixed::from(PERIOD).take(100), || {
+            (!recovery_file.exists()).then_some(()).ok_or(())
+        });
+        assert!(removed_file.is_ok());
+    }
+
+    #[test]
+    fn ignores_recovery_files_older_than_document() {
+        let app_builder = TigerAppMockBuilder::new();
+        let recovery_directory = app_builder.paths().recovery_directory.clone();
+        let recovery_file = recovery_directory.join("ignores_recovery_files_older_than_document");
+        std::fs::create_dir_all(&recovery_directory).unwrap();
+        std::fs::copy("test-data/samurai.tiger", &recovery_file).unwrap();
+
+        let document = PathBuf::from("test-output/ignores_recovery_files_older_than_document");
+        thread::sleep(Duration::from_millis(50));
+        std::fs::copy("test-data/samurai.tiger", &document).unwrap();
+
+        let manifest_file = app_builder.paths().recovery_manifest_file.clone();
+        write_to_disk(
+            &vec![RecoverableDocument {
+                document,
+                recovery_file,
+            }],
+            &manifest_file,
+        )
+        .unwrap();
+
+        let app = app_builder.build();
+        assert!(app.client_state().recoverable_documents.is_empty());
+    }
+}
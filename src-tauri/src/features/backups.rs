@@ -0,0 +1,209 @@
+use std::{
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use sugar_path::SugarPath;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub timestamp_millis: u64,
+}
+
+pub fn create_backup<P: AsRef<Path>, Q: AsRef<Path>>(
+    document: P,
+    backups_directory: Q,
+    max_backups: usize,
+) -> Result<(), std::io::Error> {
+    if max_backups == 0 || !document.as_ref().exists() {
+        return Ok(());
+    }
+
+    let directory = document_backups_directory(&document, &backups_directory);
+    std::fs::create_dir_all(&directory)?;
+
+    let mut timestamp_millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    // Backups created within the same millisecond get later timestamps instead
+    // of overwriting each other
+    let mut backup = loop {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(directory.join(format!("{timestamp_millis}.tiger")))
+        {
+            Ok(file) => break file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => timestamp_millis += 1,
+            Err(e) => return Err(e),
+        }
+    };
+    std::io::copy(&mut File::open(document.as_ref())?, &mut backup)?;
+
+    for backup in list_backups(document, backups_directory)?
+        .into_iter()
+        .skip(max_backups)
+    {
+        std::fs::remove_file(backup.path)?;
+    }
+
+    Ok(())
+}
+
+pub fn list_backups<P: AsRef<Path>, Q: AsRef<Path>>(
+    document: P,
+    backups_directory: Q,
+) -> Result<Vec<Backup>, std::io::Error> {
+    let directory = document_backups_directory(document, backups_directory);
+    if !directory.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let Some(timestamp_millis) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        backups.push(Backup {
+            path,
+            timestamp_millis,
+        });
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.timestamp_millis));
+
+    Ok(backups)
+}
+
+pub fn is_backup_of<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
+    backup: P,
+    document: Q,
+    backups_directory: R,
+) -> bool {
+    let directory = document_backups_directory(document, backups_directory).resolve();
+    backup.as_ref().resolve().parent() == Some(directory.as_path())
+}
+
+fn document_backups_directory<P: AsRef<Path>, Q: AsRef<Path>>(
+    document: P,
+    backups_directory: Q,
+) -> PathBuf {
+    let name = document
+        .as_ref()
+        .resolve()
+        .to_string_lossy()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' => '_',
+            c => c,
+        })
+        .collect::<String>();
+    backups_directory.as_ref().join(name)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{thread, time::Duration};
+
+    use super::*;
+    use crate::app::mock::TigerAppMock;
+
+    #[test]
+    fn keeps_limited_number_of_backups() {
+        let document = PathBuf::from("test-output/keeps_limited_number_of_backups.tiger");
+        let backups_directory = PathBuf::from("test-output/keeps_limited_number_of_backups");
+        std::fs::remove_dir_all(&backups_directory).ok();
+        std::fs::copy("test-data/samurai.tiger", &document).unwrap();
+
+        for _ in 0..4 {
+            create_backup(&document, &backups_directory, 3).unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let backups = list_backups(&document, &backups_directory).unwrap();
+        assert_eq!(backups.len(), 3);
+        assert!(backups[0].timestamp_millis > backups[1].timestamp_millis);
+        assert!(backups[1].timestamp_millis > backups[2].timestamp_millis);
+    }
+
+    #[test]
+    fn backups_created_at_once_are_all_kept() {
+        let document = PathBuf::from("test-output/backups_created_at_once_are_all_kept.tiger");
+        let backups_directory = PathBuf::from("test-output/backups_created_at_once_are_all_kept");
+        std::fs::remove_dir_all(&backups_directory).ok();
+        std::fs::copy("test-data/samurai.tiger", &document).unwrap();
+
+        create_backup(&document, &backups_directory, 3).unwrap();
+        create_backup(&document, &backups_directory, 3).unwrap();
+
+        let backups = list_backups(&document, &backups_directory).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups[0].timestamp_millis > backups[1].timestamp_millis);
+    }
+
+    #[tokio::test]
+    async fn saving_creates_backup() {
+        let document = PathBuf::from("test-output/saving_creates_backup.tiger");
+        std::fs::copy("test-data/samurai.tiger", &document).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec![&document]).await;
+        app.delete_animation("attack");
+        app.save().await;
+
+        let backups = app.list_backups(&document);
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            std::fs::read(&backups[0].path).unwrap(),
+            std::fs::read("test-data/samurai.tiger").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn can_restore_backup() {
+        let document = PathBuf::from("test-output/can_restore_backup.tiger");
+        std::fs::copy("test-data/samurai.tiger", &document).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec![&document]).await;
+        let num_animations = app.document().sheet.animations.len();
+        app.delete_animation("attack");
+        app.save().await;
+        assert_eq!(app.document().sheet.animations.len(), num_animations - 1);
+
+        let backups = app.list_backups(&document);
+        app.restore_backup(&document, &backups[0].path).await;
+        assert_eq!(app.document().sheet.animations.len(), num_animations);
+        assert!(app.document().has_unsaved_changes);
+
+        app.undo();
+        assert_eq!(app.document().sheet.animations.len(), num_animations - 1);
+    }
+
+    #[tokio::test]
+    async fn cannot_restore_backup_of_another_document() {
+        let document = PathBuf::from("test-output/cannot_restore_backup_of_another_document.tiger");
+        let other = PathBuf::from("test-output/cannot_restore_backup_of_another_document_2.tiger");
+        std::fs::copy("test-data/samurai.tiger", &document).unwrap();
+        std::fs::copy("test-data/flame.tiger", &other).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec![&other, &document]).await;
+        app.save().await;
+        let backups = app.list_backups(&document);
+        assert_eq!(backups.len(), 1);
+
+        app.focus_document(&other);
+        let num_animations = app.document().sheet.animations.len();
+        app.restore_backup(&other, &backups[0].path).await;
+        assert_eq!(app.document().sheet.animations.len(), num_animations);
+        assert!(!app.document().has_unsaved_changes);
+    }
+}
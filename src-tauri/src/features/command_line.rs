@@ -0,0 +1,354 @@
+use json_patch::Patch;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use sugar_path::SugarPath;
+use thiserror::Error;
+
+use crate::{
+    api::{self, Api},
+    app::TigerApp,
+    dto,
+};
+
+// Usage: tiger [DOCUMENT]... [--animation NAME] [--direction DIRECTION] [--new PATH]
+//              [--frames IMAGE...] [--export]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CommandLine {
+    pub documents: Vec<PathBuf>,
+    pub animation: Option<String>,
+    pub direction: Option<dto::Direction>,
+    pub new_document: Option<PathBuf>,
+    pub frames: Vec<PathBuf>,
+    pub export: bool,
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CommandLineError {
+    #[error("Unknown argument `{0}`")]
+    UnknownArgument(String),
+    #[error("Missing value for `{0}`")]
+    MissingValue(String),
+    #[error("Unknown direction `{0}`")]
+    UnknownDirection(String),
+    #[error("`--frames` can only be used together with `--new`")]
+    FramesWithoutNewDocument,
+    #[error("`{0}` already exists")]
+    DocumentExists(PathBuf),
+    #[error("Could not open `{0}`")]
+    OpenFailed(PathBuf),
+    #[error("Could not save `{0}`")]
+    SaveFailed(PathBuf),
+    #[error("Animation `{0}` does not exist")]
+    AnimationNotFound(String),
+    #[error("Direction `{0:?}` does not exist in the current animation")]
+    DirectionNotFound(dto::Direction),
+    #[error("No document to export")]
+    NothingToExport,
+    #[error("Could not export `{0}`: {1}")]
+    ExportFailed(PathBuf, String),
+}
+
+impl CommandLine {
+    // Relative paths are resolved against the current directory, so that the
+    // command line can be forwarded to another instance.
+    pub fn parse<I: IntoIterator<Item = String>>(arguments: I) -> Result<Self, CommandLineError> {
+        let mut command_line = CommandLine::default();
+        let mut arguments = arguments.into_iter().peekable();
+
+        while let Some(argument) = arguments.next() {
+            let mut value = || {
+                arguments
+                    .next()
+                    .ok_or_else(|| CommandLineError::MissingValue(argument.clone()))
+            };
+            match argument.as_str() {
+                "--animation" => command_line.animation = Some(value()?),
+                "--direction" => command_line.direction = Some(parse_direction(&value()?)?),
+                "--new" => command_line.new_document = Some(PathBuf::from(value()?).resolve()),
+                "--export" => command_line.export = true,
+                "--frames" => {
+                    while let Some(frame) = arguments.next_if(|a| !a.starts_with("--")) {
+                        command_line.frames.push(PathBuf::from(frame).resolve());
+                    }
+                }
+                a if a.starts_with("--") => {
+                    return Err(CommandLineError::UnknownArgument(argument));
+                }
+                _ => command_line
+                    .documents
+                    .push(PathBuf::from(argument).resolve()),
+            }
+        }
+
+        if !command_line.frames.is_empty() && command_line.new_document.is_none() {
+            return Err(CommandLineError::FramesWithoutNewDocument);
+        }
+
+        Ok(command_line)
+    }
+
+    fn affected_documents(&self) -> Vec<PathBuf> {
+        let mut documents = self.documents.clone();
+        documents.extend(self.new_document.clone());
+        documents
+    }
+}
+
+fn parse_direction(direction: &str) -> Result<dto::Direction, CommandLineError> {
+    match direction.to_lowercase().replace(['-', '_'], "").as_str() {
+        "east" | "e" => Ok(dto::Direction::East),
+        "northeast" | "ne" => Ok(dto::Direction::NorthEast),
+        "north" | "n" => Ok(dto::Direction::North),
+        "northwest" | "nw" => Ok(dto::Direction::NorthWest),
+        "west" | "w" => Ok(dto::Direction::West),
+        "southwest" | "sw" => Ok(dto::Direction::SouthWest),
+        "south" | "s" => Ok(dto::Direction::South),
+        "southeast" | "se" => Ok(dto::Direction::SouthEast),
+        _ => Err(CommandLineError::UnknownDirection(direction.to_owned())),
+    }
+}
+
+// Applies a command line to the app. The returned patch contains all the changes
+// made, even when a later step failed.
+pub async fn run<A: TigerApp + Api>(
+    app: &A,
+    command_line: &CommandLine,
+) -> (Patch, Result<(), CommandLineError>) {
+    let mut patch = Patch(Vec::new());
+    let result = run_steps(app, command_line, &mut patch).await;
+    (patch, result)
+}
+
+async fn run_steps<A: TigerApp + Api>(
+    app: &A,
+    command_line: &CommandLine,
+    patch: &mut Patch,
+) -> Result<(), CommandLineError> {
+    let mut apply = |step: Result<Patch, ()>| {
+        if let Ok(step) = step {
+            patch.0.extend(step.0);
+        }
+    };
+
+    if !command_line.documents.is_empty() {
+        apply(Api::open_documents(app, command_line.documents.clone()).await);
+        for path in &command_line.documents {
+            if app.state().lock().document(path).is_none() {
+                return Err(CommandLineError::OpenFailed(path.clone()));
+            }
+        }
+    }
+
+    if let Some(path) = &command_line.new_document {
+        if path.exists() {
+            return Err(CommandLineError::DocumentExists(path.clone()));
+        }
+        apply(Api::new_document(app, path));
+        if !command_line.frames.is_empty() {
+            apply(Api::import_frames(app, command_line.frames.clone()));
+            apply(Api::save(app).await);
+            let is_saved = app
+                .state()
+                .lock()
+                .document(path)
+                .map(|d| d.is_saved())
+                .unwrap_or_default();
+            if !is_saved {
+                return Err(CommandLineError::SaveFailed(path.clone()));
+            }
+        }
+    }
+
+    if let Some(animation) = &command_line.animation {
+        apply(Api::edit_animation(app, animation));
+        let state_handle = app.state();
+        let state = state_handle.lock();
+        let current_animation = state
+            .current_document()
+            .and_then(|d| d.current_animation().as_ref());
+        if current_animation != Some(animation) {
+            return Err(CommandLineError::AnimationNotFound(animation.clone()));
+        }
+    }
+
+    if let Some(direction) = command_line.direction {
+        apply(Api::select_direction(app, direction));
+        let state_handle = app.state();
+        let state = state_handle.lock();
+        let current_direction = state.current_document().and_then(|d| *d.current_sequence());
+        if current_direction != Some(direction.into()) {
+            return Err(CommandLineError::DirectionNotFound(direction));
+        }
+    }
+
+    if command_line.export {
+        let documents = command_line.affected_documents();
+        if documents.is_empty() {
+            return Err(CommandLineError::NothingToExport);
+        }
+        for path in documents {
+            apply(Api::focus_document(app, &path));
+            if let Err(e) = api::export_document(app).await {
+                return Err(CommandLineError::ExportFailed(path, e.to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::mock::TigerAppMock;
+
+    fn parse(arguments: &[&str]) -> Result<CommandLine, CommandLineError> {
+        CommandLine::parse(arguments.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn can_parse_command_line() {
+        let command_line = parse(&[
+            "a.tiger",
+            "--animation",
+            "walk",
+            "--direction",
+            "north-east",
+            "b.tiger",
+            "--new",
+            "c.tiger",
+            "--frames",
+            "0.png",
+            "1.png",
+            "--export",
+        ])
+        .unwrap();
+        assert_eq!(
+            command_line,
+            CommandLine {
+                documents: vec![
+                    PathBuf::from("a.tiger").resolve(),
+                    PathBuf::from("b.tiger").resolve()
+                ],
+                animation: Some("walk".to_owned()),
+                direction: Some(dto::Direction::NorthEast),
+                new_document: Some(PathBuf::from("c.tiger").resolve()),
+                frames: vec![
+                    PathBuf::from("0.png").resolve(),
+                    PathBuf::from("1.png").resolve()
+                ],
+                export: true,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_command_line() {
+        assert_eq!(
+            parse(&["--animation"]),
+            Err(CommandLineError::MissingValue("--animation".to_owned()))
+        );
+        assert_eq!(
+            parse(&["--direction", "up"]),
+            Err(CommandLineError::UnknownDirection("up".to_owned()))
+        );
+        assert_eq!(
+            parse(&["--frames", "0.png"]),
+            Err(CommandLineError::FramesWithoutNewDocument)
+        );
+        assert_eq!(
+            parse(&["--verbose"]),
+            Err(CommandLineError::UnknownArgument("--verbose".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn can_open_document_at_animation_and_direction() {
+        let app = TigerAppMock::new();
+        app.set_command_line_arguments(vec![
+            "test-data/samurai.tiger",
+            "--animation",
+            "walk",
+            "--direction",
+            "west",
+        ]);
+        app.open_startup_documents().await;
+        assert_eq!(
+            app.document().current_animation_name,
+            Some("walk".to_owned())
+        );
+        assert_eq!(
+            app.document().current_sequence_direction,
+            Some(dto::Direction::West)
+        );
+        assert!(!app.is_closed());
+    }
+
+    #[tokio::test]
+    async fn can_create_sheet_from_frames() {
+        let path = PathBuf::from("test-output/can_create_sheet_from_frames.tiger");
+        std::fs::remove_file(&path).ok();
+
+        let app = TigerAppMock::new();
+        let command_line = parse(&[
+            "--new",
+            "test-output/can_create_sheet_from_frames.tiger",
+            "--frames",
+            "test-data/samurai/walk-east-0.png",
+            "test-data/samurai/walk-east-1.png",
+        ])
+        .unwrap();
+        let (_, result) = run(&app, &command_line).await;
+        assert_eq!(result, Ok(()));
+        assert!(path.exists());
+    }
+
+    #[tokio::test]
+    async fn cannot_create_sheet_over_existing_file() {
+        let path = PathBuf::from("test-output/cannot_create_sheet_over_existing_file.tiger");
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+        let original = std::fs::read(&path).unwrap();
+
+        let app = TigerAppMock::new();
+        let command_line = parse(&[
+            "--new",
+            "test-output/cannot_create_sheet_over_existing_file.tiger",
+            "--frames",
+            "test-data/samurai/walk-east-0.png",
+        ])
+        .unwrap();
+        let (_, result) = run(&app, &command_line).await;
+        assert_eq!(
+            result,
+            Err(CommandLineError::DocumentExists(path.resolve()))
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), original);
+    }
+
+    #[tokio::test]
+    async fn exits_after_export() {
+        let app = TigerAppMock::new();
+        app.set_command_line_arguments(vec!["test-data/samurai.tiger", "--export"]);
+        app.open_startup_documents().await;
+        assert!(app.is_closed());
+        assert_eq!(app.exit_code(), None);
+        assert!(app
+            .events()
+            .into_iter()
+            .any(|(event, _)| event.as_str() == dto::EVENT_EXPORT_SUCCESS));
+    }
+
+    #[tokio::test]
+    async fn exits_with_error_code_after_failed_export() {
+        let app = TigerAppMock::new();
+        app.set_command_line_arguments(vec![
+            "--new",
+            "test-output/exits_with_error_code_after_failed_export.tiger",
+            "--export",
+        ]);
+        app.open_startup_documents().await;
+        assert!(app.is_closed());
+        assert_eq!(app.exit_code(), Some(1));
+    }
+}
@@ -0,0 +1,198 @@
+use log::error;
+use parking_lot::RwLock;
+use std::{collections::HashSet, path::PathBuf, sync::Arc, thread, time::Duration};
+use sugar_path::SugarPath;
+
+use crate::{app::TigerApp, document::Document, dto::StateTrim, utils::file_watcher::FileWatcher};
+
+#[cfg(not(test))]
+static PERIOD: Duration = Duration::from_millis(1_000);
+#[cfg(test)]
+static PERIOD: Duration = Duration::from_millis(100);
+
+#[derive(Clone)]
+pub struct ExternalModificationsInfo {
+    #[cfg(test)]
+    file_watcher: Arc<RwLock<FileWatcher>>,
+}
+
+pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) -> ExternalModificationsInfo {
+    let (file_watcher, events_receiver) = FileWatcher::new({
+        let app = app.clone();
+        move || {
+            let state_handle = app.state();
+            let state = state_handle.lock();
+            state
+                .documents_iter()
+                .map(|d| d.path().resolve())
+                .collect::<HashSet<_>>()
+        }
+    });
+    let file_watcher = Arc::new(RwLock::new(file_watcher));
+
+    thread::Builder::new()
+        .name("external-modifications-update-watcher-thread".to_owned())
+        .spawn({
+            #[cfg(test)]
+            let file_watcher = file_watcher.clone();
+            move || loop {
+                file_watcher.write().update_watched_files();
+                thread::sleep(PERIOD);
+            }
+        })
+        .unwrap();
+
+    thread::Builder::new()
+        .name("external-modifications-reload-thread".to_owned())
+        .spawn(move || loop {
+            let Ok(Ok(events)) = events_receiver.recv() else {
+                continue;
+            };
+            let modified_files = events
+                .into_iter()
+                .map(|e| e.path)
+                .collect::<HashSet<PathBuf>>();
+            for file in modified_files {
+                let documents = {
+                    let state_handle = app.state();
+                    let state = state_handle.lock();
+                    state
+                        .documents_iter()
+                        .map(|d| d.path().to_owned())
+                        .filter(|p| p.resolve() == file)
+                        .collect::<Vec<_>>()
+                };
+                for path in documents {
+                    match Document::read_sheet(&path, &path) {
+                        Ok(sheet) => app.patch_state(StateTrim::Full, |state| {
+                            if let Some(document) = state.document_mut(&path) {
+                                document.notify_external_modification(sheet);
+                            }
+                        }),
+                        Err(e) => error!(
+                            "Error while reading externally modified document `{}`: {e}",
+                            path.display()
+                        ),
+                    }
+                }
+            }
+        })
+        .unwrap();
+
+    ExternalModificationsInfo {
+        #[cfg(test)]
+        file_watcher,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use retry::{delay::Fixed, retry};
+    use std::path::Path;
+
+    use super::*;
+    use crate::app::mock::TigerAppMock;
+
+    fn wait_until_watching(app: &TigerAppMock, path: &Path) {
+        let watching_changes = retry(Fixed::from(PERIOD).take(100), || {
+            match app
+                .external_modifications_info()
+                .file_watcher
+                .read()
+                .is_watching(path.resolve())
+            {
+                true => Ok(()),
+                false => Err(()),
+            }
+        });
+        assert!(watching_changes.is_ok());
+    }
+
+    fn modify_externally(path: &Path) {
+        let mut sheet = Document::read_sheet(path, path).unwrap();
+        sheet.delete_animation("attack");
+        sheet.write(path).unwrap();
+    }
+
+    fn wait_for_external_modification(app: &TigerAppMock) {
+        let detected = retry(Fixed::from(PERIOD).take(100), || {
+            match app.document().was_modified_externally {
+                true => Ok(()),
+                false => Err(()),
+            }
+        });
+        assert!(detected.is_ok());
+    }
+
+    #[tokio::test]
+    async fn can_reload_clean_document() {
+        let path = PathBuf::from("test-output/can_reload_clean_document.tiger");
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec![&path]).await;
+        wait_until_watching(&app, &path);
+
+        modify_externally(&path);
+        wait_for_external_modification(&app);
+        assert!(!app.document().has_unsaved_changes);
+
+        app.reload_document(&path);
+        assert!(!app.document().was_modified_externally);
+        assert!(!app.document().has_unsaved_changes);
+        assert!(!app
+            .document()
+            .sheet
+            .animations
+            .iter()
+            .any(|a| a.name == "attack"));
+    }
+
+    #[tokio::test]
+    async fn can_keep_local_changes() {
+        let path = PathBuf::from("test-output/can_keep_local_changes.tiger");
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec![&path]).await;
+        wait_until_watching(&app, &path);
+        app.delete_animation("dead");
+
+        modify_externally(&path);
+        wait_for_external_modification(&app);
+
+        app.keep_local_changes(&path);
+        assert!(!app.document().was_modified_externally);
+        assert!(app.document().has_unsaved_changes);
+        assert!(app
+            .document()
+            .sheet
+            .animations
+            .iter()
+            .any(|a| a.name == "attack"));
+        assert!(!app
+            .document()
+            .sheet
+            .animations
+            .iter()
+            .any(|a| a.name == "dead"));
+
+        app.undo();
+        assert!(app.document().has_unsaved_changes);
+    }
+
+    #[tokio::test]
+    async fn saving_is_not_an_external_modification() {
+        let path = PathBuf::from("test-output/saving_is_not_an_external_modification.tiger");
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec![&path]).await;
+        wait_until_watching(&app, &path);
+
+        app.delete_animation("attack");
+        app.save().await;
+        thread::sleep(PERIOD * 5);
+        assert!(!app.document().was_modified_externally);
+    }
+}
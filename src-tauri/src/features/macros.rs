@@ -0,0 +1,187 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use squeak::Response;
+use std::{collections::HashMap, fs::File, path::Path, sync::mpsc::channel, thread};
+
+use crate::{app::TigerApp, document::Macro};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+enum Version {
+    #[serde(rename = "1")]
+    Macros_1,
+}
+
+const CURRENT_VERSION: Version = Version::Macros_1;
+
+#[derive(Deserialize, Serialize)]
+struct MacrosFile {
+    version: Version,
+    macros: HashMap<String, Macro>,
+}
+
+pub fn init<A: TigerApp + Send>(app: A) {
+    let state_handle = app.state();
+    let mut state = state_handle.lock();
+    let macros_file = app.paths().lock().macros_file.clone();
+
+    match read_from_disk(&macros_file) {
+        Ok(macros) => state.set_macros(macros),
+        Err(e) => error!("Error while reading macros: {e}"),
+    };
+
+    let (tx, rx) = channel();
+    state.macros_delegate().subscribe(move |macros| {
+        tx.send(macros.clone()).ok();
+        Response::StaySubscribed
+    });
+
+    thread::Builder::new()
+        .name("macros-thread".to_owned())
+        .spawn(move || loop {
+            let Ok(macros) = rx.recv() else { break };
+            if let Err(e) = write_to_disk(&macros, &macros_file) {
+                error!("Error while saving macros: {e}");
+            }
+        })
+        .unwrap();
+}
+
+fn write_to_disk(
+    macros: &HashMap<String, Macro>,
+    destination: &Path,
+) -> Result<(), std::io::Error> {
+    let file = File::create(destination)?;
+    let macros_file = MacrosFile {
+        version: CURRENT_VERSION,
+        macros: macros.clone(),
+    };
+    serde_json::to_writer_pretty(file, &macros_file)?;
+    Ok(())
+}
+
+fn read_from_disk(source: &Path) -> Result<HashMap<String, Macro>, std::io::Error> {
+    if !source.exists() {
+        return Ok(HashMap::new());
+    }
+    let file = File::open(source)?;
+    let macros_file: MacrosFile = serde_json::from_reader(file)?;
+    Ok(macros_file.macros)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use retry::{delay::Fixed, retry};
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::app::mock::TigerAppMock;
+
+    fn is_looping(app: &TigerAppMock, name: &str) -> bool {
+        app.document()
+            .sheet
+            .animations
+            .iter()
+            .find(|a| a.name == name)
+            .unwrap()
+            .is_looping
+    }
+
+    #[tokio::test]
+    async fn can_record_and_play_macro() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.edit_animation("attack");
+        app.begin_macro_recording();
+        assert!(app.document().is_recording_macro);
+        app.set_animation_looping(true);
+        app.end_macro_recording("Enable Looping");
+        assert!(!app.document().is_recording_macro);
+        assert_eq!(app.client_state().macros, vec!["Enable Looping"]);
+
+        app.play_macro_on_animations("Enable Looping", vec!["dead", "idle"]);
+        assert!(is_looping(&app, "dead"));
+        assert!(is_looping(&app, "idle"));
+        assert_eq!(
+            app.document().undo_effect,
+            Some("Enable Looping".to_owned())
+        );
+
+        app.undo();
+        assert!(!is_looping(&app, "dead"));
+        assert!(!is_looping(&app, "idle"));
+    }
+
+    #[tokio::test]
+    async fn can_play_macro_on_selected_animations() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.edit_animation("attack");
+        app.begin_macro_recording();
+        app.set_animation_looping(true);
+        app.end_macro_recording("Enable Looping");
+
+        app.select_animation("dead", false, false);
+        app.select_animation("idle", false, true);
+        app.play_macro("Enable Looping");
+        assert!(is_looping(&app, "dead"));
+        assert!(is_looping(&app, "idle"));
+    }
+
+    #[tokio::test]
+    async fn can_delete_macro() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_macro_recording();
+        app.set_animation_looping(true);
+        app.end_macro_recording("Enable Looping");
+        app.delete_macro("Enable Looping");
+        assert!(app.client_state().macros.is_empty());
+    }
+
+    #[tokio::test]
+    async fn can_read_macros_file() {
+        let macros_file = PathBuf::from("test-output/can_read_macros_file.json");
+        std::fs::write(
+            &macros_file,
+            r#"{
+                "version": "1",
+                "macros": {
+                    "Enable Looping": {
+                        "steps": [{ "SetAnimationLooping": true }]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        let macros = read_from_disk(&macros_file).unwrap();
+        app.state().lock().set_macros(macros);
+        app.play_macro_on_animations("Enable Looping", vec!["dead"]);
+        assert!(is_looping(&app, "dead"));
+    }
+
+    #[tokio::test]
+    async fn writes_macros_to_disk() {
+        let app = TigerAppMock::new();
+        let macros_file = app.paths().lock().macros_file.clone();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_macro_recording();
+        app.set_animation_looping(true);
+        app.end_macro_recording("Enable Looping");
+
+        let wrote_to_disk = retry(Fixed::from_millis(500).take(10), || {
+            let Ok(macros) = read_from_disk(&macros_file) else {
+                return Err("Read error");
+            };
+            match macros.contains_key("Enable Looping") {
+                true => Ok(()),
+                false => Err("Content mismatch"),
+            }
+        });
+        assert_eq!(Ok(()), wrote_to_disk);
+    }
+}
@@ -0,0 +1,165 @@
+use log::error;
+use squeak::Response;
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    thread,
+};
+
+use crate::{app::TigerApp, document::SavedHistory};
+
+pub fn init<A: TigerApp + Send>(app: A) {
+    let state_handle = app.state();
+    let mut state = state_handle.lock();
+    let history_file = app.paths().lock().history_file.clone();
+
+    match read_from_disk(&history_file) {
+        Ok(mut saved_histories) => {
+            saved_histories.retain(|path, _| path.exists());
+            state.set_saved_histories(saved_histories);
+        }
+        Err(e) => error!("Error while reading saved document histories: {e}"),
+    };
+
+    let (tx, rx) = channel();
+    state
+        .saved_histories_delegate()
+        .subscribe(move |saved_histories| {
+            tx.send(saved_histories.clone()).ok();
+            Response::StaySubscribed
+        });
+
+    thread::Builder::new()
+        .name("saved-histories-thread".to_owned())
+        .spawn(move || loop {
+            let Ok(saved_histories) = rx.recv() else { break };
+            if let Err(e) = write_to_disk(&saved_histories, &history_file) {
+                error!("Error while saving document histories: {e}");
+            }
+        })
+        .unwrap();
+}
+
+fn write_to_disk(
+    saved_histories: &HashMap<PathBuf, SavedHistory>,
+    destination: &Path,
+) -> Result<(), std::io::Error> {
+    let file = File::create(destination)?;
+    serde_json::to_writer(file, saved_histories)?;
+    Ok(())
+}
+
+fn read_from_disk(source: &Path) -> Result<HashMap<PathBuf, SavedHistory>, std::io::Error> {
+    if !source.exists() {
+        return Ok(HashMap::new());
+    }
+    let file = File::open(source)?;
+    let saved_histories: HashMap<PathBuf, SavedHistory> = serde_json::from_reader(file)?;
+    Ok(saved_histories)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use retry::{delay::Fixed, retry};
+    use sugar_path::SugarPath;
+
+    use super::*;
+    use crate::{app::mock::TigerAppMock, document::Document};
+
+    fn has_animation(app: &TigerAppMock, name: &str) -> bool {
+        app.document()
+            .sheet
+            .animations
+            .iter()
+            .any(|a| a.name == name)
+    }
+
+    #[tokio::test]
+    async fn restores_history_of_reopened_document() {
+        let path = PathBuf::from("test-output/restores_history_of_reopened_document.tiger");
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+
+        let app = TigerAppMock::new();
+        app.set_persist_history(true);
+        app.open_documents(vec![&path]).await;
+        app.delete_animation("dead");
+        app.save().await;
+        app.close_without_saving();
+
+        app.open_documents(vec![&path]).await;
+        assert!(!has_animation(&app, "dead"));
+        assert!(!app.document().has_unsaved_changes);
+        assert_eq!(
+            app.document().undo_effect,
+            Some("Delete Animation".to_owned())
+        );
+
+        app.undo();
+        assert!(has_animation(&app, "dead"));
+        assert!(app.document().has_unsaved_changes);
+    }
+
+    #[tokio::test]
+    async fn ignores_history_of_externally_modified_document() {
+        let path =
+            PathBuf::from("test-output/ignores_history_of_externally_modified_document.tiger");
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+
+        let app = TigerAppMock::new();
+        app.set_persist_history(true);
+        app.open_documents(vec![&path]).await;
+        app.delete_animation("dead");
+        app.save().await;
+        app.close_without_saving();
+
+        let mut sheet = Document::read_sheet(&path, &path).unwrap();
+        sheet.delete_animation("attack");
+        sheet.write(&path).unwrap();
+
+        app.open_documents(vec![&path]).await;
+        assert!(!has_animation(&app, "attack"));
+        assert_eq!(app.document().undo_effect, None);
+    }
+
+    #[tokio::test]
+    async fn does_not_persist_history_by_default() {
+        let path = PathBuf::from("test-output/does_not_persist_history_by_default.tiger");
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec![&path]).await;
+        app.delete_animation("dead");
+        app.save().await;
+        app.close_without_saving();
+
+        app.open_documents(vec![&path]).await;
+        assert_eq!(app.document().undo_effect, None);
+    }
+
+    #[tokio::test]
+    async fn writes_saved_histories_to_disk() {
+        let path = PathBuf::from("test-output/writes_saved_histories_to_disk.tiger").resolve();
+        std::fs::copy("test-data/samurai.tiger", &path).unwrap();
+
+        let app = TigerAppMock::new();
+        let history_file = app.paths().lock().history_file.clone();
+        app.set_persist_history(true);
+        app.open_documents(vec![&path]).await;
+        app.delete_animation("dead");
+        app.close_without_saving();
+
+        let wrote_to_disk = retry(Fixed::from_millis(500).take(10), || {
+            let Ok(saved_histories) = read_from_disk(&history_file) else {
+                return Err("Read error");
+            };
+            match saved_histories.contains_key(&path) {
+                true => Ok(()),
+                false => Err("Content mismatch"),
+            }
+        });
+        assert_eq!(Ok(()), wrote_to_disk);
+    }
+}
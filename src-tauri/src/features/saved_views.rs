@@ -0,0 +1,129 @@
+use log::error;
+use squeak::Response;
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    thread,
+};
+
+use crate::{app::TigerApp, document::SavedView};
+
+pub fn init<A: TigerApp + Send>(app: A) {
+    let state_handle = app.state();
+    let mut state = state_handle.lock();
+    let saved_views_file = app.paths().lock().saved_views_file.clone();
+
+    match read_from_disk(&saved_views_file) {
+        Ok(mut saved_views) => {
+            saved_views.retain(|path, _| path.exists());
+            state.set_saved_views(saved_views);
+        }
+        Err(e) => error!("Error while reading saved document views: {e}"),
+    };
+
+    let (tx, rx) = channel();
+    state.saved_views_delegate().subscribe(move |saved_views| {
+        tx.send(saved_views.clone()).ok();
+        Response::StaySubscribed
+    });
+
+    thread::Builder::new()
+        .name("saved-views-thread".to_owned())
+        .spawn(move || loop {
+            let Ok(saved_views) = rx.recv() else { break };
+            if let Err(e) = write_to_disk(&saved_views, &saved_views_file) {
+                error!("Error while saving document views: {e}");
+            }
+        })
+        .unwrap();
+}
+
+fn write_to_disk(
+    saved_views: &HashMap<PathBuf, SavedView>,
+    destination: &Path,
+) -> Result<(), std::io::Error> {
+    let file = File::create(destination)?;
+    serde_json::to_writer_pretty(file, saved_views)?;
+    Ok(())
+}
+
+fn read_from_disk(source: &Path) -> Result<HashMap<PathBuf, SavedView>, std::io::Error> {
+    if !source.exists() {
+        return Ok(HashMap::new());
+    }
+    let file = File::open(source)?;
+    let saved_views: HashMap<PathBuf, SavedView> = serde_json::from_reader(file)?;
+    Ok(saved_views)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use retry::{delay::Fixed, retry};
+    use sugar_path::SugarPath;
+
+    use super::*;
+    use crate::app::mock::TigerAppMock;
+
+    #[tokio::test]
+    async fn restores_view_of_reopened_document() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.edit_animation("dead");
+        app.filter_animations("de");
+        app.set_workbench_zoom_factor(16);
+        app.set_timeline_zoom_amount(0.1);
+        app.close_without_saving();
+
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        assert_eq!(
+            app.document().current_animation_name,
+            Some("dead".to_owned())
+        );
+        assert_eq!(app.document().animations_filter, "de");
+        assert_eq!(app.document().workbench_zoom, 16.0);
+        assert_eq!(app.document().timeline_zoom_amount, 0.1);
+        assert!(!app.document().has_unsaved_changes);
+    }
+
+    #[tokio::test]
+    async fn can_disable_view_restoration() {
+        let app = TigerAppMock::new();
+        app.set_restore_views(false);
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.edit_animation("dead");
+        app.set_workbench_zoom_factor(16);
+        app.close_without_saving();
+
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        assert_eq!(
+            app.document().current_animation_name,
+            Some("attack".to_owned())
+        );
+        assert_eq!(app.document().workbench_zoom, 8.0);
+    }
+
+    #[tokio::test]
+    async fn writes_saved_views_to_disk() {
+        let samurai_file = PathBuf::from("test-data/samurai.tiger").resolve();
+
+        let app = TigerAppMock::new();
+        let saved_views_file = app.paths().lock().saved_views_file.clone();
+        app.open_documents(vec![&samurai_file]).await;
+        app.edit_animation("dead");
+        app.close_without_saving();
+
+        let wrote_to_disk = retry(Fixed::from_millis(500).take(10), || {
+            let Ok(saved_views) = read_from_disk(&saved_views_file) else {
+                return Err("Read error");
+            };
+            match saved_views.contains_key(&samurai_file) {
+                true => Ok(()),
+                false => Err("Content mismatch"),
+            }
+        });
+        assert_eq!(Ok(()), wrote_to_disk);
+    }
+}
@@ -0,0 +1,126 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use squeak::Response;
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    thread,
+};
+
+use crate::{
+    app::TigerApp, document::DEFAULT_HISTORY_LIMIT,
+    features::texture_cache::DEFAULT_TEXTURE_BUDGET_MB,
+};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    // Exporter executables the user agreed to run. Sheet files can point to any
+    // executable, so exporters only run once the user allowed them here.
+    pub allowed_exporters: Vec<PathBuf>,
+    pub automation_server: bool,
+    pub history_limit: usize,
+    pub max_backups: usize,
+    pub persist_history: bool,
+    pub post_export_commands: Vec<String>,
+    pub restore_views: bool,
+    pub texture_cache_budget_mb: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            allowed_exporters: Vec::new(),
+            automation_server: false,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            max_backups: 5,
+            persist_history: false,
+            post_export_commands: Vec::new(),
+            restore_views: true,
+            texture_cache_budget_mb: DEFAULT_TEXTURE_BUDGET_MB,
+        }
+    }
+}
+
+pub fn init<A: TigerApp + Send>(app: A) {
+    let state_handle = app.state();
+    let mut state = state_handle.lock();
+    let settings_file = app.paths().lock().settings_file.clone();
+
+    match read_from_disk(&settings_file) {
+        Ok(settings) => state.set_settings(settings),
+        Err(e) => error!("Error while reading settings file: {e}"),
+    };
+
+    let (tx, rx) = channel();
+    state.settings_delegate().subscribe(move |settings| {
+        tx.send(settings.clone()).ok();
+        Response::StaySubscribed
+    });
+
+    thread::Builder::new()
+        .name("settings-thread".to_owned())
+        .spawn(move || loop {
+            let Ok(settings) = rx.recv() else { break };
+            if let Err(e) = write_to_disk(&settings, &settings_file) {
+                error!("Error while saving settings: {e}");
+            }
+        })
+        .unwrap();
+}
+
+fn write_to_disk(settings: &Settings, destination: &Path) -> Result<(), std::io::Error> {
+    let file = File::create(destination)?;
+    serde_json::to_writer_pretty(file, settings)?;
+    Ok(())
+}
+
+fn read_from_disk(source: &Path) -> Result<Settings, std::io::Error> {
+    if !source.exists() {
+        return Ok(Settings::default());
+    }
+    let file = File::open(source)?;
+    let settings: Settings = serde_json::from_reader(file)?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use retry::{delay::Fixed, retry};
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::app::mock::{TigerAppMock, TigerAppMockBuilder};
+
+    #[test]
+    fn reads_settings_from_disk() {
+        let settings_file = PathBuf::from("test-output/reads_settings_from_disk.json");
+        std::fs::write(&settings_file, r#"{ "max_backups": 12 }"#).unwrap();
+
+        let mut app_builder = TigerAppMockBuilder::new();
+        app_builder.paths_mut().settings_file = settings_file;
+        let app = app_builder.build();
+
+        assert_eq!(app.client_state().settings.max_backups, 12);
+    }
+
+    #[test]
+    fn writes_settings_to_disk() {
+        let app = TigerAppMock::new();
+        let settings_file = app.paths().lock().settings_file.clone();
+        app.set_max_backups(3);
+
+        let wrote_to_disk = retry(Fixed::from_millis(500).take(10), || {
+            let Ok(settings) = read_from_disk(&settings_file) else {
+                return Err("Read error");
+            };
+            match settings.max_backups == 3 {
+                true => Ok(()),
+                false => Err("Content mismatch"),
+            }
+        });
+        assert_eq!(Ok(()), wrote_to_disk);
+    }
+}
@@ -1,16 +1,27 @@
 use interprocess::{
-    os::windows::named_pipe::{
-        MsgReaderPipeStream, MsgWriterPipeStream, PipeListenerOptions, PipeMode,
-    },
+    os::windows::named_pipe::{DuplexMsgPipeStream, PipeListenerOptions, PipeMode},
     ReliableReadMsg,
 };
 use log::error;
 use named_lock::NamedLock;
 use parking_lot::{Condvar, Mutex};
 use serde::{Deserialize, Serialize};
-use std::{ffi::OsStr, io::Write, sync::Arc, thread};
+use std::{
+    ffi::OsStr,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
 
-use crate::{api::Api, app::TigerApp, utils::handle};
+use crate::{
+    api::{self, Api},
+    app::TigerApp,
+    document::Command,
+    dto,
+    features::command_line::{self, CommandLine},
+    utils::handle,
+};
 
 static PIPE_NAME: &str = "tiger-named-pipe";
 static LOCK_NAME: &str = "tiger-startup-mutex";
@@ -21,10 +32,36 @@ pub struct StartupGuard {
 
 pub type StartupGuardHandle = handle::Handle<Option<StartupGuard>>;
 
+// Every message sent to the primary instance is answered with a `Reply`.
+// Paths in messages should be absolute, since the primary instance may have
+// a different working directory.
 #[derive(Debug, Deserialize, Serialize)]
-enum Message {
+pub enum Message {
     FocusWindow,
-    OpenDocuments(Vec<String>),
+    RunCommandLine(CommandLine),
+    OpenDocumentAt(DocumentLocation),
+    ExportDocument(PathBuf),
+    SaveAll,
+    ListOpenDocuments,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DocumentLocation {
+    pub path: PathBuf,
+    pub animation: Option<String>,
+    pub direction: Option<dto::Direction>,
+    pub keyframe: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Reply {
+    Done,
+    Failed(String),
+    OpenDocuments(Vec<PathBuf>),
+}
+
+pub struct PrimaryInstance {
+    pipe: DuplexMsgPipeStream,
 }
 
 pub fn acquire_startup_guard() -> StartupGuard {
@@ -78,19 +115,48 @@ impl Drop for StartupGuard {
     }
 }
 
+// Returns `None` if there is no primary instance to forward the command line to
 pub fn attach_to_primary_instance(
-    command_line_arguments: Vec<String>,
-    _startup_guard: &StartupGuard,
-) -> Result<bool, ()> {
-    let Ok(mut write_pipe) = MsgWriterPipeStream::connect(OsStr::new(PIPE_NAME)) else {
-        return Ok(false);
+    command_line: &CommandLine,
+    startup_guard: &StartupGuard,
+) -> Result<Option<Reply>, ()> {
+    let Some(mut primary_instance) = PrimaryInstance::connect(startup_guard) else {
+        return Ok(None);
     };
-    send_message(&mut write_pipe, &Message::FocusWindow)?;
-    send_message(
-        &mut write_pipe,
-        &Message::OpenDocuments(command_line_arguments),
-    )?;
-    Ok(true)
+    if !command_line.export {
+        primary_instance.send(Message::FocusWindow)?;
+    }
+    primary_instance
+        .send(Message::RunCommandLine(command_line.clone()))
+        .map(Some)
+}
+
+impl PrimaryInstance {
+    pub fn connect(_startup_guard: &StartupGuard) -> Option<Self> {
+        DuplexMsgPipeStream::connect(OsStr::new(PIPE_NAME))
+            .ok()
+            .map(|pipe| Self { pipe })
+    }
+
+    pub fn send(&mut self, message: Message) -> Result<Reply, ()> {
+        let message = serde_json::to_vec(&message).unwrap();
+        if let Err(e) = self.pipe.write_all(&message) {
+            error!("Error writing to single instance named pipe: {e}");
+            return Err(());
+        }
+        let mut buffer = vec![0; 64 * 1024];
+        let reply = match self.pipe.read_msg(&mut buffer) {
+            Ok(Ok(size)) => buffer[..size].to_vec(),
+            Ok(Err(reply)) => reply,
+            Err(e) => {
+                error!("Error reading from single instance named pipe: {e}");
+                return Err(());
+            }
+        };
+        serde_json::from_slice(&reply).map_err(|e| {
+            error!("Received invalid reply from primary instance: {e}");
+        })
+    }
 }
 
 pub fn become_primary_instance<A: TigerApp + Api + Clone + Send + Sync + 'static>(
@@ -100,13 +166,13 @@ pub fn become_primary_instance<A: TigerApp + Api + Clone + Send + Sync + 'static
     thread::Builder::new()
         .name("single-instance-message-reader-thread".to_owned())
         .spawn(move || {
-            let read_pipe = PipeListenerOptions::new()
+            let listener = PipeListenerOptions::new()
                 .mode(PipeMode::Messages)
                 .name(OsStr::new(PIPE_NAME))
-                .create::<MsgReaderPipeStream>()
+                .create::<DuplexMsgPipeStream>()
                 .unwrap();
 
-            for incoming in read_pipe.incoming() {
+            for incoming in listener.incoming() {
                 match incoming {
                     Ok(stream) => {
                         thread::Builder::new()
@@ -127,51 +193,141 @@ pub fn become_primary_instance<A: TigerApp + Api + Clone + Send + Sync + 'static
         .unwrap();
 }
 
-fn handle_attached_process<A: TigerApp + Api + Clone>(app: A, mut stream: MsgReaderPipeStream) {
-    let mut buffer = Vec::<u8>::new();
+fn handle_attached_process<A: TigerApp + Api>(app: A, mut stream: DuplexMsgPipeStream) {
+    let mut buffer = vec![0; 10 * 1024];
     loop {
-        match stream.try_read_msg(buffer.as_mut_slice()) {
-            Ok(Ok(size)) => {
-                if let Ok(message) = serde_json::from_slice::<Message>(&buffer[..size]) {
-                    receive_message(app.clone(), message);
-                }
-            }
-            Ok(Err(size)) => {
-                if size < 10 * 1024 {
-                    buffer.resize(size, 0);
-                } else {
-                    error!(
-                        "Single instance named pipe received an oversized message ({size} bytes)"
-                    );
-                    return;
-                }
+        let reply = match stream.read_msg(&mut buffer) {
+            Ok(Ok(size)) => match serde_json::from_slice::<Message>(&buffer[..size]) {
+                Ok(message) => receive_message(&app, message),
+                Err(e) => Reply::Failed(format!("Invalid message: {e}")),
+            },
+            Ok(Err(message)) => {
+                error!(
+                    "Single instance named pipe received an oversized message ({} bytes)",
+                    message.len()
+                );
+                return;
             }
             Err(_) => {
                 return;
             }
+        };
+        let reply = serde_json::to_vec(&reply).unwrap();
+        if let Err(e) = stream.write_all(&reply) {
+            error!("Error writing to single instance named pipe: {e}");
+            return;
         }
     }
 }
 
-fn receive_message<A: TigerApp + Api>(app: A, message: Message) {
-    match message {
-        Message::FocusWindow => app.focus_window(),
-        Message::OpenDocuments(d) => tauri::async_runtime::block_on(async {
-            Api::open_documents(&app, d).await.ok();
-            app.replace_state();
-        }),
+fn receive_message<A: TigerApp + Api>(app: &A, message: Message) -> Reply {
+    let reply = tauri::async_runtime::block_on(async {
+        match message {
+            Message::FocusWindow => {
+                app.focus_window();
+                Reply::Done
+            }
+            Message::RunCommandLine(c) => match command_line::run(app, &c).await {
+                (_, Ok(())) => Reply::Done,
+                (_, Err(e)) => Reply::Failed(e.to_string()),
+            },
+            Message::OpenDocumentAt(location) => open_document_at(app, location).await,
+            Message::ExportDocument(path) => export_document(app, &path).await,
+            Message::SaveAll => save_all(app).await,
+            Message::ListOpenDocuments => Reply::OpenDocuments(
+                app.state()
+                    .lock()
+                    .documents_iter()
+                    .map(|d| d.path().to_owned())
+                    .collect(),
+            ),
+        }
+    });
+    app.replace_state();
+    reply
+}
+
+async fn focus_or_open_document<A: TigerApp + Api>(app: &A, path: &Path) -> Result<(), String> {
+    let is_open = app.state().lock().document(path).is_some();
+    if is_open {
+        Api::focus_document(app, path).ok();
+    } else {
+        Api::open_documents(app, vec![path]).await.ok();
+    }
+    let state_handle = app.state();
+    let state = state_handle.lock();
+    match state.current_document() {
+        Some(d) if d.path() == path => Ok(()),
+        _ => Err(format!("Could not open `{}`", path.display())),
     }
 }
 
-fn send_message(write_pipe: &mut MsgWriterPipeStream, message: &Message) -> Result<(), ()> {
-    let message = serde_json::to_string(&message).unwrap();
-    match write_pipe.write_all(message.as_bytes()) {
-        Ok(()) => Ok(()),
-        Err(e) => {
-            error!("Error writing to single instance named pipe: {e}");
-            Err(())
+async fn open_document_at<A: TigerApp + Api>(app: &A, location: DocumentLocation) -> Reply {
+    if let Err(e) = focus_or_open_document(app, &location.path).await {
+        return Reply::Failed(e);
+    }
+
+    let state_handle = app.state();
+    let mut state = state_handle.lock();
+    let Some(document) = state.current_document_mut() else {
+        return Reply::Failed("No document is open".to_owned());
+    };
+
+    let mut commands = Vec::new();
+    commands.extend(location.animation.map(Command::EditAnimation));
+    commands.extend(
+        location
+            .direction
+            .map(|direction| Command::SelectDirection(direction.into())),
+    );
+    for command in commands {
+        if let Err(e) = document.process_command(command) {
+            return Reply::Failed(e.to_string());
+        }
+    }
+
+    if let Some(index) = location.keyframe {
+        let Some(direction) = *document.current_sequence() else {
+            return Reply::Failed("No direction to select a keyframe in".to_owned());
+        };
+        let command = Command::SelectKeyframe {
+            direction,
+            index,
+            shift: false,
+            ctrl: false,
+        };
+        if let Err(e) = document.process_command(command) {
+            return Reply::Failed(e.to_string());
         }
     }
+
+    Reply::Done
+}
+
+async fn export_document<A: TigerApp + Api>(app: &A, path: &Path) -> Reply {
+    if let Err(e) = focus_or_open_document(app, path).await {
+        return Reply::Failed(e);
+    }
+    match api::export_document(app).await {
+        Ok(()) => Reply::Done,
+        Err(e) => Reply::Failed(e.to_string()),
+    }
+}
+
+async fn save_all<A: TigerApp + Api>(app: &A) -> Reply {
+    Api::save_all(app).await.ok();
+    let unsaved_documents = app
+        .state()
+        .lock()
+        .documents_iter()
+        .filter(|d| !d.is_saved())
+        .map(|d| d.path().display().to_string())
+        .collect::<Vec<_>>();
+    if unsaved_documents.is_empty() {
+        Reply::Done
+    } else {
+        Reply::Failed(format!("Could not save {}", unsaved_documents.join(", ")))
+    }
 }
 
 #[cfg(test)]
@@ -179,6 +335,7 @@ mod tests {
 
     use retry::{delay::Fixed, retry};
     use std::time::Duration;
+    use sugar_path::SugarPath;
 
     use super::*;
     use crate::app::mock::{TigerAppMock, TigerAppMockBuilder};
@@ -219,20 +376,19 @@ mod tests {
     async fn cross_instance_communication() {
         let guard = acquire_startup_guard();
         let app = TigerAppMock::new();
+        let command_line = CommandLine::parse(vec!["test-data/samurai.tiger".to_owned()]).unwrap();
 
-        assert_eq!(
-            attach_to_primary_instance(vec!["test-data/samurai.tiger".into()], &guard),
-            Ok(false)
-        );
+        assert_eq!(attach_to_primary_instance(&command_line, &guard), Ok(None));
         assert!(!app.is_focused());
 
         become_primary_instance(app.clone(), &guard);
 
         let attached = retry(
             Fixed::from_millis(100).take(100),
-            || match attach_to_primary_instance(vec!["test-data/samurai.tiger".into()], &guard) {
-                Ok(true) => Ok(()),
-                Ok(false) => Err("Nothing to attach to".into()),
+            || match attach_to_primary_instance(&command_line, &guard) {
+                Ok(Some(Reply::Done)) => Ok(()),
+                Ok(Some(reply)) => Err(format!("Unexpected reply: {reply:?}")),
+                Ok(None) => Err("Nothing to attach to".into()),
                 Err(e) => Err(format!("Failed to attach: {e:?}")),
             },
         );
@@ -249,5 +405,46 @@ mod tests {
                 .ok_or(())
         });
         assert!(opened_document.is_ok());
+
+        let path = PathBuf::from("test-data/samurai.tiger").resolve();
+        let mut primary_instance = PrimaryInstance::connect(&guard).unwrap();
+        assert_eq!(
+            primary_instance.send(Message::OpenDocumentAt(DocumentLocation {
+                path: path.clone(),
+                animation: Some("walk".to_owned()),
+                direction: Some(dto::Direction::North),
+                keyframe: Some(2),
+            })),
+            Ok(Reply::Done)
+        );
+        assert_eq!(app.document().path, path);
+        assert_eq!(
+            app.document().current_animation_name,
+            Some("walk".to_owned())
+        );
+        assert_eq!(
+            app.document().current_sequence_direction,
+            Some(dto::Direction::North)
+        );
+        assert_eq!(app.document().current_keyframe_index, Some(2));
+
+        assert!(matches!(
+            primary_instance.send(Message::OpenDocumentAt(DocumentLocation {
+                path: path.clone(),
+                animation: Some("missing".to_owned()),
+                ..Default::default()
+            })),
+            Ok(Reply::Failed(_))
+        ));
+
+        assert!(matches!(
+            primary_instance.send(Message::ListOpenDocuments),
+            Ok(Reply::OpenDocuments(d)) if d.contains(&path)
+        ));
+
+        assert!(matches!(
+            primary_instance.send(Message::ExportDocument(path.with_extension("missing"))),
+            Ok(Reply::Failed(_))
+        ));
     }
 }
@@ -1,7 +1,13 @@
 use parking_lot::RwLock;
-use std::{collections::HashSet, sync::Arc, thread, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
 
-use crate::{app::TigerApp, sheet, utils::file_watcher::FileWatcher};
+use crate::{app::TigerApp, dto, export, sheet, utils::file_watcher::FileWatcher};
 
 #[cfg(not(test))]
 static PERIOD: Duration = Duration::from_millis(1_000);
@@ -18,13 +24,10 @@ pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) -> TemplateHotR
     let (file_watcher, events_receiver) = FileWatcher::new({
         let app = app.clone();
         move || {
-            let state_handle = app.state();
-            let state = state_handle.lock();
-            state
-                .documents_iter()
-                .flat_map(|d| d.export_settings_edit())
-                .map(|s| match s {
-                    sheet::ExportSettings::Template(s) => s.template_file().to_owned(),
+            list_partials(&app)
+                .into_iter()
+                .flat_map(|(template_file, partials)| {
+                    std::iter::once(template_file).chain(partials)
                 })
                 .collect::<HashSet<_>>()
         }
@@ -34,11 +37,26 @@ pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) -> TemplateHotR
     thread::Builder::new()
         .name("template-hot-reload-update-watcher-thread".to_owned())
         .spawn({
+            let app = app.clone();
             #[cfg(test)]
             let file_watcher = file_watcher.clone();
-            move || loop {
-                file_watcher.write().update_watched_files();
-                thread::sleep(PERIOD);
+            move || {
+                // Filesystem events only cover directories which already contain template files,
+                // so partials being added or removed are detected by listing them periodically.
+                let mut partials = list_partials(&app);
+                loop {
+                    let new_partials = list_partials(&app);
+                    let partials_changed = new_partials.iter().any(|(template_file, files)| {
+                        matches!(partials.get(template_file), Some(old_files) if old_files != files)
+                    });
+                    if partials_changed {
+                        app.replace_state();
+                        app.emit_all(dto::EVENT_EXPORT_TEMPLATE_CHANGED, ());
+                    }
+                    partials = new_partials;
+                    file_watcher.write().update_watched_files();
+                    thread::sleep(PERIOD);
+                }
             }
         })
         .unwrap();
@@ -48,6 +66,7 @@ pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) -> TemplateHotR
         .spawn(move || loop {
             if let Ok(Ok(_)) = events_receiver.recv() {
                 app.replace_state();
+                app.emit_all(dto::EVENT_EXPORT_TEMPLATE_CHANGED, ());
             }
         })
         .unwrap();
@@ -58,6 +77,23 @@ pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) -> TemplateHotR
     }
 }
 
+// Lists the partials of every template being edited, keyed by template file
+fn list_partials<A: TigerApp>(app: &A) -> HashMap<PathBuf, Vec<PathBuf>> {
+    let state_handle = app.state();
+    let state = state_handle.lock();
+    state
+        .documents_iter()
+        .flat_map(|d| d.export_settings_edit())
+        .filter_map(|s| match s {
+            sheet::ExportSettings::Template(s) => Some((
+                s.template_file().to_owned(),
+                export::template_partials(s.template_file()),
+            )),
+            sheet::ExportSettings::Exporter(_) => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use retry::{delay::Fixed, retry};
@@ -134,5 +170,135 @@ mod tests {
             template_file_error,
             Ok(ExportSettingsError::TemplateError(_))
         ));
+
+        let notified_change = retry(Fixed::from(PERIOD).take(100), || {
+            app.events()
+                .into_iter()
+                .any(|(event, _)| event.as_str() == dto::EVENT_EXPORT_TEMPLATE_CHANGED)
+                .then_some(())
+                .ok_or(())
+        });
+        assert!(notified_change.is_ok());
+    }
+
+    #[tokio::test]
+    async fn detects_partial_errors_on_file_change() {
+        let test_directory = PathBuf::from("test-output/detects_partial_errors_on_file_change");
+        let test_template_path = test_directory.join("main.template").resolve();
+        let test_partial_path = test_directory.join("partials/frame.template").resolve();
+        std::fs::create_dir_all(test_partial_path.parent().unwrap()).unwrap();
+        std::fs::copy("test-data/templates/partials.template", &test_template_path).unwrap();
+        std::fs::copy(
+            "test-data/templates/partials/frame.template",
+            &test_partial_path,
+        )
+        .unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_template_file(&test_template_path);
+
+        assert!(app
+            .document()
+            .export_settings_validation
+            .as_ref()
+            .unwrap()
+            .template_file_error
+            .is_none());
+
+        let watching_changes = retry(Fixed::from(PERIOD).take(100), || {
+            if app
+                .template_hot_reload_info()
+                .file_watcher
+                .read()
+                .is_watching(&test_partial_path)
+            {
+                Ok(())
+            } else {
+                Err(())
+            }
+        });
+        assert!(watching_changes.is_ok());
+
+        std::fs::copy("test-data/malformed.template", &test_partial_path).unwrap();
+        let template_file_error = retry(Fixed::from(PERIOD).take(100), || {
+            match app
+                .document()
+                .export_settings_validation
+                .as_ref()
+                .unwrap()
+                .template_file_error
+            {
+                None => Err("No template file error"),
+                Some(ref e) => Ok(e.clone()),
+            }
+        });
+
+        assert!(matches!(
+            template_file_error,
+            Ok(ExportSettingsError::TemplateError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn detects_new_partials() {
+        let test_directory = PathBuf::from("test-output/detects_new_partials");
+        let test_template_path = test_directory.join("main.template").resolve();
+        let test_partials_directory = test_directory.join("partials").resolve();
+        std::fs::remove_dir_all(&test_partials_directory).ok();
+        std::fs::create_dir_all(&test_directory).unwrap();
+        std::fs::copy("test-data/templates/partials.template", &test_template_path).unwrap();
+
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        app.begin_export_as();
+        app.set_export_template_file(&test_template_path);
+
+        let watching_changes = retry(Fixed::from(PERIOD).take(100), || {
+            if app
+                .template_hot_reload_info()
+                .file_watcher
+                .read()
+                .is_watching(&test_template_path)
+            {
+                Ok(())
+            } else {
+                Err(())
+            }
+        });
+        assert!(watching_changes.is_ok());
+
+        assert!(app
+            .document()
+            .export_settings_validation
+            .as_ref()
+            .unwrap()
+            .template_file_error
+            .is_none());
+
+        std::fs::create_dir_all(&test_partials_directory).unwrap();
+        std::fs::copy(
+            "test-data/malformed.template",
+            test_partials_directory.join("frame.template"),
+        )
+        .unwrap();
+        let template_file_error = retry(Fixed::from(PERIOD).take(100), || {
+            match app
+                .document()
+                .export_settings_validation
+                .as_ref()
+                .unwrap()
+                .template_file_error
+            {
+                None => Err("No template file error"),
+                Some(ref e) => Ok(e.clone()),
+            }
+        });
+
+        assert!(matches!(
+            template_file_error,
+            Ok(ExportSettingsError::TemplateError(_))
+        ));
     }
 }
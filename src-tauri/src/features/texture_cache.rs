@@ -1,10 +1,12 @@
-use image::DynamicImage;
+use image::{imageops::FilterType, DynamicImage, ImageResult};
 use log::error;
 use parking_lot::RwLock;
+use rayon::prelude::*;
+use squeak::Response;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{mpsc::channel, Arc},
     thread,
     time::Duration,
 };
@@ -19,7 +21,198 @@ static PERIOD: Duration = Duration::from_millis(1_000);
 #[cfg(test)]
 static PERIOD: Duration = Duration::from_millis(100);
 
-pub type Handle = handle::Handle<HashMap<PathBuf, DynamicImage>>;
+pub type Handle = handle::Handle<TextureCache>;
+
+pub const DEFAULT_TEXTURE_BUDGET_MB: usize = 1_024;
+const THUMBNAIL_BUDGET_MB: usize = 64;
+const THUMBNAIL_SIZE: u32 = 128;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes_used: usize,
+    pub budget_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TextureCacheStats {
+    pub textures: CacheStats,
+    pub thumbnails: CacheStats,
+}
+
+// Full resolution textures of open documents, and downscaled versions of
+// them for list views. Each is kept within a memory budget by evicting the
+// least recently used bitmaps.
+pub struct TextureCache {
+    textures: LruCache,
+    thumbnails: LruCache,
+    // Textures evicted to stay within budget. They are not preloaded again until
+    // they are requested or change on disk.
+    evicted: HashSet<PathBuf>,
+}
+
+impl Default for TextureCache {
+    fn default() -> Self {
+        Self {
+            textures: LruCache::new(DEFAULT_TEXTURE_BUDGET_MB * 1024 * 1024),
+            thumbnails: LruCache::new(THUMBNAIL_BUDGET_MB * 1024 * 1024),
+            evicted: HashSet::new(),
+        }
+    }
+}
+
+impl TextureCache {
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.textures.contains(path.as_ref())
+    }
+
+    pub fn get<P: AsRef<Path>>(&mut self, path: P) -> Option<Arc<DynamicImage>> {
+        let texture = self.textures.get(path.as_ref());
+        if texture.is_none() {
+            self.evicted.remove(path.as_ref());
+        }
+        texture
+    }
+
+    pub fn insert(&mut self, path: PathBuf, texture: Arc<DynamicImage>) {
+        self.evicted.remove(&path);
+        let evicted = self.textures.insert(path, texture);
+        self.evicted.extend(evicted);
+    }
+
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) {
+        self.textures.remove(path.as_ref());
+        self.thumbnails.remove(path.as_ref());
+        self.evicted.remove(path.as_ref());
+    }
+
+    // Textures which are either cached or were evicted to stay within budget
+    pub fn tracked_textures(&self) -> HashSet<PathBuf> {
+        self.textures
+            .paths()
+            .chain(self.evicted.iter())
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_budget(&mut self, bytes: usize) {
+        let evicted = self.textures.set_budget(bytes);
+        self.evicted.extend(evicted);
+    }
+
+    pub fn stats(&self) -> TextureCacheStats {
+        TextureCacheStats {
+            textures: self.textures.stats(),
+            thumbnails: self.thumbnails.stats(),
+        }
+    }
+}
+
+struct LruEntry {
+    bitmap: Arc<DynamicImage>,
+    last_used: u64,
+}
+
+struct LruCache {
+    entries: HashMap<PathBuf, LruEntry>,
+    recency: BTreeMap<u64, PathBuf>,
+    clock: u64,
+    bytes_used: usize,
+    budget_bytes: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl LruCache {
+    fn new(budget_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            bytes_used: 0,
+            budget_bytes,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.entries.keys()
+    }
+
+    fn get(&mut self, path: &Path) -> Option<Arc<DynamicImage>> {
+        self.clock += 1;
+        let Some(entry) = self.entries.get_mut(path) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(self.clock, path.to_owned());
+        entry.last_used = self.clock;
+        Some(entry.bitmap.clone())
+    }
+
+    // Returns the paths of the bitmaps evicted to make room for the new one
+    fn insert(&mut self, path: PathBuf, bitmap: Arc<DynamicImage>) -> Vec<PathBuf> {
+        self.remove(&path);
+        self.clock += 1;
+        self.bytes_used += bitmap.as_bytes().len();
+        self.recency.insert(self.clock, path.clone());
+        self.entries.insert(
+            path,
+            LruEntry {
+                bitmap,
+                last_used: self.clock,
+            },
+        );
+        self.evict()
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.recency.remove(&entry.last_used);
+            self.bytes_used -= entry.bitmap.as_bytes().len();
+        }
+    }
+
+    fn set_budget(&mut self, budget_bytes: usize) -> Vec<PathBuf> {
+        self.budget_bytes = budget_bytes;
+        self.evict()
+    }
+
+    // The most recently used bitmap is never evicted, even if it alone exceeds the budget
+    fn evict(&mut self) -> Vec<PathBuf> {
+        let mut evicted = Vec::new();
+        while self.bytes_used > self.budget_bytes && self.entries.len() > 1 {
+            let oldest = self.recency.keys().next().copied();
+            let Some(path) = oldest.and_then(|t| self.recency.remove(&t)) else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&path) {
+                self.bytes_used -= entry.bitmap.as_bytes().len();
+            }
+            evicted.push(path);
+        }
+        evicted
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            bytes_used: self.bytes_used,
+            budget_bytes: self.budget_bytes,
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct TextureCacheInfo {
@@ -52,6 +245,30 @@ pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) -> TextureCache
 
     let texture_cache = app.texture_cache();
 
+    let (tx, rx) = channel();
+    {
+        let state_handle = app.state();
+        let state = state_handle.lock();
+        tx.send(state.settings().texture_cache_budget_mb).ok();
+        state.settings_delegate().subscribe(move |settings| {
+            tx.send(settings.texture_cache_budget_mb).ok();
+            Response::StaySubscribed
+        });
+    }
+
+    thread::Builder::new()
+        .name("texture-cache-budget-thread".to_owned())
+        .spawn({
+            let texture_cache = texture_cache.clone();
+            move || loop {
+                let Ok(budget_mb) = rx.recv() else { break };
+                texture_cache
+                    .lock()
+                    .set_budget(budget_mb.saturating_mul(1024 * 1024));
+            }
+        })
+        .unwrap();
+
     thread::Builder::new()
         .name("texture-cache-eviction-thread".to_owned())
         .spawn({
@@ -70,10 +287,7 @@ pub fn init<A: TigerApp + Send + Sync + Clone + 'static>(app: A) -> TextureCache
     thread::Builder::new()
         .name("texture-cache-update-thread".to_owned())
         .spawn(move || loop {
-            let current_entries: HashSet<PathBuf> = {
-                let cache = texture_cache.lock();
-                cache.keys().cloned().collect()
-            };
+            let current_entries = texture_cache.lock().tracked_textures();
             let desired_entries = {
                 let state_handle = app.state();
                 let state = state_handle.lock();
@@ -106,7 +320,7 @@ fn remove<P: AsRef<Path>>(textures: &HashSet<P>, texture_cache: &Handle) {
     }
 }
 
-fn add<P: AsRef<Path>>(textures: &HashSet<P>, texture_cache: &Handle) {
+fn add<P: AsRef<Path> + Sync>(textures: &HashSet<P>, texture_cache: &Handle) {
     {
         let mut cache = texture_cache.lock();
         for texture in textures {
@@ -114,27 +328,105 @@ fn add<P: AsRef<Path>>(textures: &HashSet<P>, texture_cache: &Handle) {
         }
     }
 
-    let mut new_textures = HashMap::<PathBuf, DynamicImage>::new();
-    for path in textures {
-        match image::open(path) {
-            Ok(i) => {
-                new_textures.insert(path.as_ref().to_owned(), i);
+    let new_textures = decode(&textures.iter().collect::<Vec<_>>(), &|| false);
+
+    let mut cache = texture_cache.lock();
+    for (path, texture) in new_textures {
+        match texture {
+            Ok(texture) => {
+                cache.insert(path, Arc::new(texture));
             }
             Err(e) => {
-                error!(
-                    "Error while preloading `{0}`: {e}",
-                    path.as_ref().to_string_lossy()
-                );
+                error!("Error while preloading `{0}`: {e}", path.to_string_lossy());
             }
-        };
+        }
     }
+}
 
+// Returns bitmaps for the requested textures. Textures which are not in the cache
+// are decoded without holding the cache lock, and then added to the cache.
+// Returns `None` if `is_cancelled` starts returning true while decoding.
+pub fn get_or_decode<P, C>(
+    texture_cache: &Handle,
+    textures: &[P],
+    is_cancelled: C,
+) -> Option<HashMap<PathBuf, ImageResult<Arc<DynamicImage>>>>
+where
+    P: AsRef<Path> + Sync,
+    C: Fn() -> bool + Sync,
+{
+    let mut bitmaps = HashMap::new();
+    let mut missing_textures = Vec::new();
     {
         let mut cache = texture_cache.lock();
-        for (path, texture) in new_textures {
-            cache.insert(path.to_owned(), texture);
+        for texture in textures {
+            match cache.get(texture) {
+                Some(bitmap) => {
+                    bitmaps.insert(texture.as_ref().to_owned(), Ok(bitmap.clone()));
+                }
+                None => missing_textures.push(texture.as_ref()),
+            }
+        }
+    }
+
+    let decoded = decode(&missing_textures, &is_cancelled);
+    if is_cancelled() {
+        return None;
+    }
+
+    let mut cache = texture_cache.lock();
+    for (path, bitmap) in decoded {
+        let bitmap = bitmap.map(Arc::new);
+        if let Ok(bitmap) = &bitmap {
+            cache.insert(path.clone(), bitmap.clone());
         }
+        bitmaps.insert(path, bitmap);
     }
+    Some(bitmaps)
+}
+
+// Returns a downscaled version of a texture, suitable for list views
+pub fn get_or_create_thumbnail<P: AsRef<Path>>(
+    texture_cache: &Handle,
+    path: P,
+) -> ImageResult<Arc<DynamicImage>> {
+    let texture = {
+        let mut cache = texture_cache.lock();
+        if let Some(thumbnail) = cache.thumbnails.get(path.as_ref()) {
+            return Ok(thumbnail);
+        }
+        cache.textures.get(path.as_ref())
+    };
+
+    let texture = match texture {
+        Some(texture) => texture,
+        None => Arc::new(image::open(path.as_ref())?),
+    };
+    let thumbnail = if texture.width() <= THUMBNAIL_SIZE && texture.height() <= THUMBNAIL_SIZE {
+        texture
+    } else {
+        Arc::new(texture.resize(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Nearest))
+    };
+
+    texture_cache
+        .lock()
+        .thumbnails
+        .insert(path.as_ref().to_owned(), thumbnail.clone());
+    Ok(thumbnail)
+}
+
+// Textures which have not started decoding when `is_cancelled` starts returning
+// true are skipped.
+fn decode<P, C>(textures: &[P], is_cancelled: &C) -> Vec<(PathBuf, ImageResult<DynamicImage>)>
+where
+    P: AsRef<Path> + Sync,
+    C: Fn() -> bool + Sync,
+{
+    textures
+        .par_iter()
+        .filter(|_| !is_cancelled())
+        .map(|path| (path.as_ref().to_owned(), image::open(path)))
+        .collect()
 }
 
 #[cfg(test)]
@@ -152,7 +444,7 @@ mod tests {
         let frame_path = PathBuf::from("test-data/samurai/dead-all.png").resolve();
         let app = TigerAppMock::new();
 
-        let is_cached = |app: &TigerAppMock| app.texture_cache().lock().contains_key(&frame_path);
+        let is_cached = |app: &TigerAppMock| app.texture_cache().lock().contains(&frame_path);
 
         let check_cached = |app: &TigerAppMock| {
             retry(Fixed::from(PERIOD).take(100), || {
@@ -192,7 +484,7 @@ mod tests {
                         let Ok(reference_image) = image::open(reference_image) else {
                             return Err("Could not open reference_image");
                         };
-                        if image == &reference_image {
+                        if *image == reference_image {
                             Ok(())
                         } else {
                             Err("Image mismatch")
@@ -219,4 +511,119 @@ mod tests {
         let has_new_version = validate_cached_image(&frame, &after_frame);
         assert_eq!(has_new_version, Ok(()));
     }
+
+    #[test]
+    fn shares_cached_bitmaps() {
+        let cached_frame = PathBuf::from("test-data/samurai/dead-all.png").resolve();
+        let other_frame = PathBuf::from("test-data/samurai/attack-north.png").resolve();
+        let missing_frame = PathBuf::from("test-data/samurai/missing.png").resolve();
+
+        let texture_cache = Handle::default();
+        let cached_bitmap = Arc::new(image::open(&cached_frame).unwrap());
+        texture_cache
+            .lock()
+            .insert(cached_frame.clone(), cached_bitmap.clone());
+
+        let bitmaps = get_or_decode(
+            &texture_cache,
+            &[&cached_frame, &other_frame, &missing_frame],
+            || false,
+        )
+        .unwrap();
+        assert!(Arc::ptr_eq(
+            bitmaps[&cached_frame].as_ref().unwrap(),
+            &cached_bitmap
+        ));
+        assert_eq!(
+            *bitmaps[&other_frame].as_ref().unwrap().as_ref(),
+            image::open(&other_frame).unwrap()
+        );
+        assert!(bitmaps[&missing_frame].is_err());
+        assert!(Arc::ptr_eq(
+            bitmaps[&other_frame].as_ref().unwrap(),
+            &texture_cache.lock().get(&other_frame).unwrap()
+        ));
+        assert!(!texture_cache.lock().contains(&missing_frame));
+    }
+
+    #[test]
+    fn can_cancel_decoding() {
+        let frame = PathBuf::from("test-data/samurai/dead-all.png").resolve();
+        let texture_cache = Handle::default();
+        assert!(get_or_decode(&texture_cache, &[&frame], || true).is_none());
+        assert!(!texture_cache.lock().contains(&frame));
+    }
+
+    #[test]
+    fn evicts_least_recently_used_textures() {
+        let texture = Arc::new(DynamicImage::new_rgba8(16, 16));
+        let texture_size = texture.as_bytes().len();
+
+        let mut cache = TextureCache::default();
+        cache.set_budget(2 * texture_size);
+        cache.insert(PathBuf::from("a.png"), texture.clone());
+        cache.insert(PathBuf::from("b.png"), texture.clone());
+        assert!(cache.get("a.png").is_some());
+        cache.insert(PathBuf::from("c.png"), texture.clone());
+
+        assert!(cache.contains("a.png"));
+        assert!(!cache.contains("b.png"));
+        assert!(cache.contains("c.png"));
+        assert!(cache.tracked_textures().contains(Path::new("b.png")));
+        assert!(cache.get("b.png").is_none());
+        assert!(!cache.tracked_textures().contains(Path::new("b.png")));
+
+        let stats = cache.stats().textures;
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.bytes_used, 2 * texture_size);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+
+        cache.set_budget(0);
+        assert_eq!(cache.stats().textures.entries, 1);
+    }
+
+    #[test]
+    fn can_create_thumbnails() {
+        let large_frame = PathBuf::from("test-output/can_create_thumbnails.png");
+        DynamicImage::new_rgba8(512, 256)
+            .save(&large_frame)
+            .unwrap();
+        let small_frame = PathBuf::from("test-data/samurai/dead-all.png");
+
+        let texture_cache = Handle::default();
+        let thumbnail = get_or_create_thumbnail(&texture_cache, &large_frame).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (128, 64));
+
+        let cached_thumbnail = get_or_create_thumbnail(&texture_cache, &large_frame).unwrap();
+        assert!(Arc::ptr_eq(&thumbnail, &cached_thumbnail));
+
+        let small_thumbnail = get_or_create_thumbnail(&texture_cache, &small_frame).unwrap();
+        assert_eq!(*small_thumbnail, image::open(&small_frame).unwrap());
+
+        let stats = texture_cache.lock().stats().thumbnails;
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.hits, 1);
+        assert!(!texture_cache.lock().contains(&large_frame));
+    }
+
+    #[tokio::test]
+    async fn applies_texture_budget_from_settings() {
+        let app = TigerAppMock::new();
+        app.open_documents(vec!["test-data/samurai.tiger"]).await;
+        let is_preloaded = retry(Fixed::from(PERIOD).take(100), || {
+            let stats = app.get_texture_cache_stats().textures;
+            (stats.entries > 1).then_some(()).ok_or(())
+        });
+        assert!(is_preloaded.is_ok());
+
+        app.set_texture_cache_budget_mb(0);
+        let is_within_budget = retry(Fixed::from(PERIOD).take(100), || {
+            let stats = app.get_texture_cache_stats().textures;
+            (stats.entries == 1 && stats.budget_bytes == 0)
+                .then_some(())
+                .ok_or(())
+        });
+        assert!(is_within_budget.is_ok());
+    }
 }
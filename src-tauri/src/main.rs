@@ -10,8 +10,9 @@ use tauri::Manager;
 
 use app::TigerApp;
 use features::{
+    command_line::CommandLine,
     single_instance::{
-        acquire_startup_guard, attach_to_primary_instance, become_primary_instance,
+        acquire_startup_guard, attach_to_primary_instance, become_primary_instance, Reply,
         StartupGuardHandle,
     },
     texture_cache,
@@ -29,19 +30,29 @@ mod state;
 mod utils;
 
 fn main() {
+    let command_line = match CommandLine::parse(std::env::args().skip(1)) {
+        Ok(command_line) => command_line,
+        Err(e) => {
+            print_error(e);
+            std::process::exit(2);
+        }
+    };
+
     let startup_guard = acquire_startup_guard();
-    let command_line_arguments = std::env::args().skip(1).collect::<Vec<_>>();
-    if matches!(
-        attach_to_primary_instance(command_line_arguments, &startup_guard),
-        Ok(true),
-    ) {
-        std::process::exit(0);
+    match attach_to_primary_instance(&command_line, &startup_guard) {
+        Ok(Some(Reply::Failed(e))) => {
+            print_error(e);
+            std::process::exit(1);
+        }
+        Ok(Some(_)) => std::process::exit(0),
+        Ok(None) | Err(()) => (),
     }
 
     tauri::Builder::default()
         .manage(StartupGuardHandle::new(Some(startup_guard)))
         .manage(state::Handle::default())
         .manage(texture_cache::Handle::default())
+        .register_uri_scheme_protocol("thumbnail", app::tauri::thumbnail_protocol)
         .setup({
             move |tauri_app| {
                 init_window_shadow(tauri_app);
@@ -59,10 +70,18 @@ fn main() {
                 }
 
                 features::app_updates::init(tauri_app.handle());
+                #[cfg(windows)]
+                features::automation::init(tauri_app.handle());
+                features::autosave::init(tauri_app.handle());
                 features::clipboard_analysis::init(tauri_app.handle());
+                features::external_modifications::init(tauri_app.handle());
+                features::macros::init(tauri_app.handle());
                 features::missing_textures::init(tauri_app.handle());
                 features::onboarding::init(tauri_app.handle());
                 features::recent_documents::init(tauri_app.handle());
+                features::saved_histories::init(tauri_app.handle());
+                features::saved_views::init(tauri_app.handle());
+                features::settings::init(tauri_app.handle());
                 features::template_hot_reload::init(tauri_app.handle());
                 features::texture_cache::init(tauri_app.handle());
                 features::texture_hot_reload::init(tauri_app.handle());
@@ -74,24 +93,41 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             // App
             app::tauri::acknowledge_error,
+            app::tauri::allow_exporter,
             app::tauri::cancel_close_document,
             app::tauri::close_about_dialog,
             app::tauri::close_all_documents,
             app::tauri::close_current_document,
             app::tauri::close_document,
+            app::tauri::delete_macro,
+            app::tauri::discard_recoverable_document,
             app::tauri::finalize_startup,
             app::tauri::focus_document,
             app::tauri::focus_next_document,
             app::tauri::focus_previous_document,
             app::tauri::get_state,
+            app::tauri::get_texture_cache_stats,
+            app::tauri::keep_local_changes,
+            app::tauri::list_backups,
             app::tauri::new_document,
             app::tauri::open_about_dialog,
             app::tauri::open_documents,
             app::tauri::open_startup_documents,
+            app::tauri::preview_export,
+            app::tauri::recover_document,
+            app::tauri::reload_document,
             app::tauri::request_exit,
             app::tauri::request_install_update,
+            app::tauri::restore_backup,
             app::tauri::reveal_in_explorer,
             app::tauri::save_all,
+            app::tauri::set_automation_server,
+            app::tauri::set_history_limit,
+            app::tauri::set_max_backups,
+            app::tauri::set_persist_history,
+            app::tauri::set_post_export_commands,
+            app::tauri::set_restore_views,
+            app::tauri::set_texture_cache_budget_mb,
             app::tauri::show_error_message,
             // Document
             app::tauri::apply_direction_preset,
@@ -99,6 +135,7 @@ fn main() {
             app::tauri::begin_drag_and_drop_keyframe,
             app::tauri::begin_drag_keyframe_duration,
             app::tauri::begin_export_as,
+            app::tauri::begin_macro_recording,
             app::tauri::begin_nudge_hitbox,
             app::tauri::begin_nudge_keyframe,
             app::tauri::begin_rename_animation,
@@ -108,7 +145,9 @@ fn main() {
             app::tauri::browse_selection,
             app::tauri::browse_to_end,
             app::tauri::browse_to_start,
+            app::tauri::cancel_export,
             app::tauri::cancel_export_as,
+            app::tauri::cancel_macro_recording,
             app::tauri::cancel_rename,
             app::tauri::center_workbench,
             app::tauri::clear_selection,
@@ -134,6 +173,7 @@ fn main() {
             app::tauri::end_drag_and_drop_keyframe,
             app::tauri::end_drag_keyframe_duration,
             app::tauri::end_export_as,
+            app::tauri::end_macro_recording,
             app::tauri::end_nudge_hitbox,
             app::tauri::end_nudge_keyframe,
             app::tauri::end_rename_animation,
@@ -148,6 +188,7 @@ fn main() {
             app::tauri::import_frames,
             app::tauri::jump_to_animation_end,
             app::tauri::jump_to_animation_start,
+            app::tauri::jump_to_history_index,
             app::tauri::jump_to_next_frame,
             app::tauri::jump_to_previous_frame,
             app::tauri::lock_hitboxes,
@@ -157,6 +198,8 @@ fn main() {
             app::tauri::paste,
             app::tauri::pause,
             app::tauri::play,
+            app::tauri::play_macro,
+            app::tauri::play_macro_on_animations,
             app::tauri::redo,
             app::tauri::begin_relocate_frames,
             app::tauri::relocate_frame,
@@ -177,7 +220,17 @@ fn main() {
             app::tauri::set_animations_list_offset,
             app::tauri::set_export_metadata_file,
             app::tauri::set_export_metadata_paths_root,
+            app::tauri::set_export_on_save,
+            app::tauri::set_export_origin,
+            app::tauri::set_export_y_axis,
+            app::tauri::set_export_uv_coordinates,
             app::tauri::set_export_template_file,
+            app::tauri::create_export_template_variable,
+            app::tauri::rename_export_template_variable,
+            app::tauri::set_export_template_variable_value,
+            app::tauri::delete_export_template_variable,
+            app::tauri::set_export_exporter_file,
+            app::tauri::set_export_format,
             app::tauri::set_export_atlas_image_file,
             app::tauri::set_frames_list_mode,
             app::tauri::set_frames_list_offset,
@@ -251,6 +304,17 @@ fn handle_window_event(event: tauri::GlobalWindowEvent) {
     }
 }
 
+// Release builds on Windows have no console of their own, so errors are written
+// to the console of the process which launched Tiger (if any).
+fn print_error<E: std::fmt::Display>(error: E) {
+    #[cfg(all(not(debug_assertions), target_os = "windows"))]
+    unsafe {
+        use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+    eprintln!("{error}");
+}
+
 fn init_window_shadow(tauri_app: &mut tauri::App) {
     let Some(window) = tauri_app.get_window("main") else {
         error!("Could not access app window to initialize shadow");
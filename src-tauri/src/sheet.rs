@@ -4,7 +4,7 @@ use euclid::rect;
 use pathdiff::diff_paths;
 use regex::Regex;
 use serde::{ser::SerializeMap, Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
 use std::ops::Range;
@@ -19,6 +19,7 @@ use euclid::vec2;
 
 pub(in crate::sheet) mod version_0_5_0;
 pub(in crate::sheet) mod version_1_0_0;
+pub(in crate::sheet) mod version_1_1_0;
 
 #[allow(non_camel_case_types)]
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -28,6 +29,8 @@ enum Version {
     Tiger_0_5_0,
     #[serde(rename = "1.0.0")]
     Tiger_1_0_0,
+    #[serde(rename = "1.1.0")]
+    Tiger_1_1_0,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -52,8 +55,8 @@ impl<P: AsRef<Path>> From<P> for Relative {
     }
 }
 
-const CURRENT_VERSION: Version = Version::Tiger_1_0_0;
-pub use self::version_1_0_0::*;
+const CURRENT_VERSION: Version = Version::Tiger_1_1_0;
+pub use self::version_1_1_0::*;
 
 #[derive(Error, Debug)]
 pub enum SheetError {
@@ -71,6 +74,10 @@ pub enum SheetError {
     HitboxNotFound(String),
     #[error("A hitbox with the name `{0}` already exists")]
     HitboxNameAlreadyExists(String),
+    #[error("Could not find a template variable named `{0}`")]
+    VariableNotFound(String),
+    #[error("A template variable with the name `{0}` already exists")]
+    VariableNameAlreadyExists(String),
     #[error("Error converting an absolute path to a relative path\nAbsolute path: `{0}`\nRelative path root: `{1}`")]
     AbsoluteToRelativePath(PathBuf, PathBuf),
     #[error("Animation is missing a keyframe at index `{0}`")]
@@ -284,6 +291,17 @@ impl Sheet<Any> {
 
 impl Sheet<Absolute> {
     pub fn write<T: AsRef<Path>>(self, destination: T) -> Result<(), SheetError> {
+        let destination = destination.as_ref().resolve();
+        let mut directory = destination.clone();
+        directory.pop();
+        self.write_relative_to(destination, directory)
+    }
+
+    pub fn write_relative_to<T: AsRef<Path>, U: AsRef<Path>>(
+        self,
+        destination: T,
+        relative_to: U,
+    ) -> Result<(), SheetError> {
         #[derive(Serialize)]
         struct VersionedSheet {
             version: Version,
@@ -291,17 +309,22 @@ impl Sheet<Absolute> {
         }
 
         let destination = destination.as_ref().resolve();
-        let mut directory = destination.clone();
-        directory.pop();
-
         let versioned_sheet = VersionedSheet {
             version: CURRENT_VERSION,
-            sheet: self.with_relative_paths(directory)?,
+            sheet: self.with_relative_paths(relative_to.as_ref().resolve())?,
         };
 
-        let file = File::create(&destination).map_err(|e| SheetError::IoError(destination, e))?;
-        serde_json::to_writer_pretty(BufWriter::new(file), &versioned_sheet)?;
-        Ok(())
+        // Content is written to a temporary file in the destination directory and then moved
+        // into place, so that a failed write never leaves a truncated sheet behind.
+        let temporary_file = temporary_file_path(&destination);
+        let result = write_synced(&temporary_file, &versioned_sheet).and_then(|_| {
+            std::fs::rename(&temporary_file, &destination)
+                .map_err(|e| SheetError::IoError(destination, e))
+        });
+        if result.is_err() {
+            std::fs::remove_file(&temporary_file).ok();
+        }
+        result
     }
 
     pub fn with_relative_paths<T: AsRef<Path>>(
@@ -329,6 +352,101 @@ impl Sheet<Absolute> {
     }
 }
 
+// Changes between two versions of a sheet, tracked per animation
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SheetDiff<P: Paths> {
+    frames: Option<Change<Vec<Frame<P>>>>,
+    animations: Vec<(String, Change<Option<Animation<P>>>)>,
+    export_settings: Option<Change<Option<ExportSettings<P>>>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Change<T> {
+    before: T,
+    after: T,
+}
+
+impl<P: Paths + Clone> Sheet<P> {
+    pub fn diff(&self, newer: &Sheet<P>) -> SheetDiff<P> {
+        let frames = (self.frames != newer.frames).then(|| Change {
+            before: self.frames.clone(),
+            after: newer.frames.clone(),
+        });
+
+        let animation_names = self
+            .animations
+            .keys()
+            .chain(newer.animations.keys())
+            .collect::<HashSet<_>>();
+        let animations = animation_names
+            .into_iter()
+            .filter_map(|name| {
+                let before = self.animations.get(name);
+                let after = newer.animations.get(name);
+                (before != after).then(|| {
+                    (
+                        name.clone(),
+                        Change {
+                            before: before.cloned(),
+                            after: after.cloned(),
+                        },
+                    )
+                })
+            })
+            .collect();
+
+        let export_settings = (self.export_settings != newer.export_settings).then(|| Change {
+            before: self.export_settings.clone(),
+            after: newer.export_settings.clone(),
+        });
+
+        SheetDiff {
+            frames,
+            animations,
+            export_settings,
+        }
+    }
+
+    pub fn apply(&mut self, diff: &SheetDiff<P>) {
+        self.apply_changes(diff, true);
+    }
+
+    pub fn revert(&mut self, diff: &SheetDiff<P>) {
+        self.apply_changes(diff, false);
+    }
+
+    fn apply_changes(&mut self, diff: &SheetDiff<P>, forward: bool) {
+        if let Some(change) = &diff.frames {
+            self.frames = change.side(forward).clone();
+        }
+        for (name, change) in &diff.animations {
+            match change.side(forward) {
+                Some(animation) => self.animations.insert(name.clone(), animation.clone()),
+                None => self.animations.remove(name),
+            };
+        }
+        if let Some(change) = &diff.export_settings {
+            self.export_settings = change.side(forward).clone();
+        }
+    }
+}
+
+impl<T> Change<T> {
+    fn side(&self, forward: bool) -> &T {
+        if forward {
+            &self.after
+        } else {
+            &self.before
+        }
+    }
+}
+
+impl<P: Paths> SheetDiff<P> {
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_none() && self.animations.is_empty() && self.export_settings.is_none()
+    }
+}
+
 impl<P: Paths> Frame<P> {
     pub fn new<T: AsRef<Path>>(path: T) -> Self {
         Self {
@@ -481,6 +599,22 @@ impl Animation<Any> {
     }
 }
 
+impl Direction {
+    // Angle in degrees, measured counter-clockwise from East
+    pub fn angle(&self) -> u32 {
+        match self {
+            Direction::East => 0,
+            Direction::NorthEast => 45,
+            Direction::North => 90,
+            Direction::NorthWest => 135,
+            Direction::West => 180,
+            Direction::SouthWest => 225,
+            Direction::South => 270,
+            Direction::SouthEast => 315,
+        }
+    }
+}
+
 impl DirectionPreset {
     pub fn from_directions<T: Iterator<Item = Direction>>(directions: T) -> Option<Self> {
         let directions_set: HashSet<Direction> = directions.collect();
@@ -871,18 +1005,71 @@ impl Default for Hitbox {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ExportFormat {
+    Template,
+    Exporter,
+}
+
 impl Default for ExportSettings<Any> {
     fn default() -> Self {
         Self::Template(TemplateExportSettings::<Any>::default())
     }
 }
 
+impl<P: Paths> ExportSettings<P> {
+    pub fn format(&self) -> ExportFormat {
+        match self {
+            ExportSettings::Template(_) => ExportFormat::Template,
+            ExportSettings::Exporter(_) => ExportFormat::Exporter,
+        }
+    }
+
+    pub fn atlas_image_file(&self) -> &Path {
+        match self {
+            ExportSettings::Template(settings) => settings.atlas_image_file(),
+            ExportSettings::Exporter(settings) => settings.atlas_image_file(),
+        }
+    }
+
+    pub fn metadata_file(&self) -> &Path {
+        match self {
+            ExportSettings::Template(settings) => settings.metadata_file(),
+            ExportSettings::Exporter(settings) => settings.metadata_file(),
+        }
+    }
+
+    pub fn metadata_paths_root(&self) -> &Path {
+        match self {
+            ExportSettings::Template(settings) => settings.metadata_paths_root(),
+            ExportSettings::Exporter(settings) => settings.metadata_paths_root(),
+        }
+    }
+
+    pub fn export_on_save(&self) -> bool {
+        match self {
+            ExportSettings::Template(settings) => settings.export_on_save(),
+            ExportSettings::Exporter(settings) => settings.export_on_save(),
+        }
+    }
+
+    pub fn coordinates(&self) -> ExportCoordinates {
+        match self {
+            ExportSettings::Template(settings) => settings.coordinates(),
+            ExportSettings::Exporter(settings) => settings.coordinates(),
+        }
+    }
+}
+
 impl ExportSettings<Relative> {
     pub fn with_absolute_paths<T: AsRef<Path>>(self, relative_to: T) -> ExportSettings<Absolute> {
         match self {
             ExportSettings::Template(settings) => {
                 ExportSettings::Template(settings.with_absolute_paths(relative_to))
             }
+            ExportSettings::Exporter(settings) => {
+                ExportSettings::Exporter(settings.with_absolute_paths(relative_to))
+            }
         }
     }
 }
@@ -896,6 +1083,9 @@ impl ExportSettings<Absolute> {
             ExportSettings::Template(settings) => {
                 ExportSettings::Template(settings.with_relative_paths(relative_to)?)
             }
+            ExportSettings::Exporter(settings) => {
+                ExportSettings::Exporter(settings.with_relative_paths(relative_to)?)
+            }
         })
     }
 
@@ -904,6 +1094,9 @@ impl ExportSettings<Absolute> {
             ExportSettings::Template(settings) => {
                 ExportSettings::Template(settings.with_any_paths())
             }
+            ExportSettings::Exporter(settings) => {
+                ExportSettings::Exporter(settings.with_any_paths())
+            }
         }
     }
 }
@@ -914,6 +1107,9 @@ impl ExportSettings<Any> {
             ExportSettings::Template(settings) => {
                 ExportSettings::Template(settings.with_absolute_paths()?)
             }
+            ExportSettings::Exporter(settings) => {
+                ExportSettings::Exporter(settings.with_absolute_paths()?)
+            }
         })
     }
 
@@ -922,8 +1118,114 @@ impl ExportSettings<Any> {
             ExportSettings::Template(settings) => {
                 ExportSettings::Template(settings.with_relative_paths()?)
             }
+            ExportSettings::Exporter(settings) => {
+                ExportSettings::Exporter(settings.with_relative_paths()?)
+            }
         })
     }
+
+    // Switches to another format, keeping the settings both formats have in common
+    pub fn with_format(self, format: ExportFormat) -> ExportSettings<Any> {
+        if self.format() == format {
+            return self;
+        }
+        let atlas_image_file = self.atlas_image_file().to_owned();
+        let metadata_file = self.metadata_file().to_owned();
+        let metadata_paths_root = self.metadata_paths_root().to_owned();
+        let export_on_save = self.export_on_save();
+        let coordinates = self.coordinates();
+        let variables = match &self {
+            ExportSettings::Template(settings) => settings.variables.clone(),
+            ExportSettings::Exporter(settings) => settings.variables.clone(),
+        };
+        match format {
+            ExportFormat::Template => ExportSettings::Template(TemplateExportSettings {
+                template_file: PathBuf::new(),
+                atlas_image_file,
+                metadata_file,
+                metadata_paths_root,
+                export_on_save,
+                coordinates,
+                variables,
+                paths: std::marker::PhantomData,
+            }),
+            ExportFormat::Exporter => ExportSettings::Exporter(ExporterExportSettings {
+                exporter_file: PathBuf::new(),
+                atlas_image_file,
+                metadata_file,
+                metadata_paths_root,
+                export_on_save,
+                coordinates,
+                variables,
+                paths: std::marker::PhantomData,
+            }),
+        }
+    }
+
+    pub fn set_atlas_image_file<T: AsRef<Path>>(&mut self, path: T) {
+        match self {
+            ExportSettings::Template(settings) => settings.set_atlas_image_file(path),
+            ExportSettings::Exporter(settings) => settings.set_atlas_image_file(path),
+        }
+    }
+
+    pub fn set_metadata_file<T: AsRef<Path>>(&mut self, path: T) {
+        match self {
+            ExportSettings::Template(settings) => settings.set_metadata_file(path),
+            ExportSettings::Exporter(settings) => settings.set_metadata_file(path),
+        }
+    }
+
+    pub fn set_metadata_paths_root<T: AsRef<Path>>(&mut self, path: T) {
+        match self {
+            ExportSettings::Template(settings) => settings.set_metadata_paths_root(path),
+            ExportSettings::Exporter(settings) => settings.set_metadata_paths_root(path),
+        }
+    }
+
+    pub fn set_export_on_save(&mut self, enabled: bool) {
+        match self {
+            ExportSettings::Template(settings) => settings.set_export_on_save(enabled),
+            ExportSettings::Exporter(settings) => settings.set_export_on_save(enabled),
+        }
+    }
+
+    pub fn set_coordinates(&mut self, coordinates: ExportCoordinates) {
+        match self {
+            ExportSettings::Template(settings) => settings.set_coordinates(coordinates),
+            ExportSettings::Exporter(settings) => settings.set_coordinates(coordinates),
+        }
+    }
+}
+
+impl ExportCoordinates {
+    pub fn origin(&self) -> ExportOrigin {
+        self.origin
+    }
+
+    pub fn y_axis(&self) -> ExportYAxis {
+        self.y_axis
+    }
+
+    pub fn uv_coordinates(&self) -> bool {
+        self.uv_coordinates
+    }
+
+    pub fn set_origin(&mut self, origin: ExportOrigin) {
+        self.origin = origin;
+    }
+
+    pub fn set_y_axis(&mut self, y_axis: ExportYAxis) {
+        self.y_axis = y_axis;
+    }
+
+    pub fn set_uv_coordinates(&mut self, enabled: bool) {
+        self.uv_coordinates = enabled;
+    }
+
+    pub(in crate::sheet) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 impl<P: Paths> TemplateExportSettings<P> {
@@ -942,6 +1244,18 @@ impl<P: Paths> TemplateExportSettings<P> {
     pub fn metadata_paths_root(&self) -> &Path {
         self.metadata_paths_root.as_path()
     }
+
+    pub fn export_on_save(&self) -> bool {
+        self.export_on_save
+    }
+
+    pub fn coordinates(&self) -> ExportCoordinates {
+        self.coordinates
+    }
+
+    pub fn variables(&self) -> &BTreeMap<String, String> {
+        &self.variables
+    }
 }
 
 impl TemplateExportSettings<Absolute> {
@@ -954,6 +1268,9 @@ impl TemplateExportSettings<Absolute> {
             atlas_image_file: absolute_to_relative(self.atlas_image_file, &relative_to)?,
             metadata_file: absolute_to_relative(self.metadata_file, &relative_to)?,
             metadata_paths_root: absolute_to_relative(self.metadata_paths_root, &relative_to)?,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
             paths: std::marker::PhantomData,
         })
     }
@@ -964,6 +1281,9 @@ impl TemplateExportSettings<Absolute> {
             atlas_image_file: self.atlas_image_file,
             metadata_file: self.metadata_file,
             metadata_paths_root: self.metadata_paths_root,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
             paths: std::marker::PhantomData,
         }
     }
@@ -982,6 +1302,9 @@ impl TemplateExportSettings<Relative> {
                 .as_ref()
                 .join(&self.metadata_paths_root)
                 .resolve(),
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables.clone(),
             paths: std::marker::PhantomData,
         }
     }
@@ -1004,12 +1327,68 @@ impl TemplateExportSettings<Any> {
         self.metadata_paths_root = path.as_ref().to_owned();
     }
 
+    pub fn set_export_on_save(&mut self, enabled: bool) {
+        self.export_on_save = enabled;
+    }
+
+    pub fn set_coordinates(&mut self, coordinates: ExportCoordinates) {
+        self.coordinates = coordinates;
+    }
+
+    pub fn create_variable<T: AsRef<str>>(&mut self, proposed_name: T) -> String {
+        let name =
+            generate_unique_name(proposed_name.as_ref(), |n| !self.variables.contains_key(n));
+        self.variables.insert(name.clone(), String::new());
+        name
+    }
+
+    pub fn set_variable<T: AsRef<str>, U: AsRef<str>>(
+        &mut self,
+        name: T,
+        value: U,
+    ) -> Result<(), SheetError> {
+        let variable = self
+            .variables
+            .get_mut(name.as_ref())
+            .ok_or_else(|| SheetError::VariableNotFound(name.as_ref().to_owned()))?;
+        *variable = value.as_ref().to_owned();
+        Ok(())
+    }
+
+    pub fn rename_variable<T: AsRef<str>, U: AsRef<str>>(
+        &mut self,
+        old_name: T,
+        new_name: U,
+    ) -> Result<(), SheetError> {
+        if old_name.as_ref() == new_name.as_ref() {
+            return Ok(());
+        }
+        if self.variables.contains_key(new_name.as_ref()) {
+            return Err(SheetError::VariableNameAlreadyExists(
+                new_name.as_ref().to_owned(),
+            ));
+        }
+        let value = self
+            .variables
+            .remove(old_name.as_ref())
+            .ok_or_else(|| SheetError::VariableNotFound(old_name.as_ref().to_owned()))?;
+        self.variables.insert(new_name.as_ref().to_owned(), value);
+        Ok(())
+    }
+
+    pub fn delete_variable<T: AsRef<str>>(&mut self, name: T) {
+        self.variables.remove(name.as_ref());
+    }
+
     pub fn with_absolute_paths(self) -> Result<TemplateExportSettings<Absolute>, SheetError> {
         Ok(TemplateExportSettings {
             template_file: absolute_or_err(self.template_file)?,
             atlas_image_file: absolute_or_err(self.atlas_image_file)?,
             metadata_file: absolute_or_err(self.metadata_file)?,
             metadata_paths_root: absolute_or_err(self.metadata_paths_root)?,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
             paths: std::marker::PhantomData,
         })
     }
@@ -1020,6 +1399,139 @@ impl TemplateExportSettings<Any> {
             atlas_image_file: relative_or_err(self.atlas_image_file)?,
             metadata_file: relative_or_err(self.metadata_file)?,
             metadata_paths_root: relative_or_err(self.metadata_paths_root)?,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
+            paths: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<P: Paths> ExporterExportSettings<P> {
+    pub fn exporter_file(&self) -> &Path {
+        self.exporter_file.as_path()
+    }
+
+    pub fn atlas_image_file(&self) -> &Path {
+        self.atlas_image_file.as_path()
+    }
+
+    pub fn metadata_file(&self) -> &Path {
+        self.metadata_file.as_path()
+    }
+
+    pub fn metadata_paths_root(&self) -> &Path {
+        self.metadata_paths_root.as_path()
+    }
+
+    pub fn export_on_save(&self) -> bool {
+        self.export_on_save
+    }
+
+    pub fn coordinates(&self) -> ExportCoordinates {
+        self.coordinates
+    }
+}
+
+impl ExporterExportSettings<Absolute> {
+    pub fn with_relative_paths<T: AsRef<Path>>(
+        self,
+        relative_to: T,
+    ) -> Result<ExporterExportSettings<Relative>, SheetError> {
+        Ok(ExporterExportSettings {
+            exporter_file: absolute_to_relative(self.exporter_file, &relative_to)?,
+            atlas_image_file: absolute_to_relative(self.atlas_image_file, &relative_to)?,
+            metadata_file: absolute_to_relative(self.metadata_file, &relative_to)?,
+            metadata_paths_root: absolute_to_relative(self.metadata_paths_root, &relative_to)?,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
+            paths: std::marker::PhantomData,
+        })
+    }
+
+    pub fn with_any_paths(self) -> ExporterExportSettings<Any> {
+        ExporterExportSettings {
+            exporter_file: self.exporter_file,
+            atlas_image_file: self.atlas_image_file,
+            metadata_file: self.metadata_file,
+            metadata_paths_root: self.metadata_paths_root,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
+            paths: std::marker::PhantomData,
+        }
+    }
+}
+
+impl ExporterExportSettings<Relative> {
+    pub fn with_absolute_paths<T: AsRef<Path>>(
+        &self,
+        relative_to: T,
+    ) -> ExporterExportSettings<Absolute> {
+        ExporterExportSettings {
+            exporter_file: relative_to.as_ref().join(&self.exporter_file).resolve(),
+            atlas_image_file: relative_to.as_ref().join(&self.atlas_image_file).resolve(),
+            metadata_file: relative_to.as_ref().join(&self.metadata_file).resolve(),
+            metadata_paths_root: relative_to
+                .as_ref()
+                .join(&self.metadata_paths_root)
+                .resolve(),
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables.clone(),
+            paths: std::marker::PhantomData,
+        }
+    }
+}
+
+impl ExporterExportSettings<Any> {
+    pub fn set_exporter_file<T: AsRef<Path>>(&mut self, path: T) {
+        self.exporter_file = path.as_ref().to_owned();
+    }
+
+    pub fn set_atlas_image_file<T: AsRef<Path>>(&mut self, path: T) {
+        self.atlas_image_file = path.as_ref().to_owned();
+    }
+
+    pub fn set_metadata_file<T: AsRef<Path>>(&mut self, path: T) {
+        self.metadata_file = path.as_ref().to_owned();
+    }
+
+    pub fn set_metadata_paths_root<T: AsRef<Path>>(&mut self, path: T) {
+        self.metadata_paths_root = path.as_ref().to_owned();
+    }
+
+    pub fn set_export_on_save(&mut self, enabled: bool) {
+        self.export_on_save = enabled;
+    }
+
+    pub fn set_coordinates(&mut self, coordinates: ExportCoordinates) {
+        self.coordinates = coordinates;
+    }
+
+    pub fn with_absolute_paths(self) -> Result<ExporterExportSettings<Absolute>, SheetError> {
+        Ok(ExporterExportSettings {
+            exporter_file: absolute_or_err(self.exporter_file)?,
+            atlas_image_file: absolute_or_err(self.atlas_image_file)?,
+            metadata_file: absolute_or_err(self.metadata_file)?,
+            metadata_paths_root: absolute_or_err(self.metadata_paths_root)?,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
+            paths: std::marker::PhantomData,
+        })
+    }
+
+    pub fn with_relative_paths(self) -> Result<ExporterExportSettings<Relative>, SheetError> {
+        Ok(ExporterExportSettings {
+            exporter_file: relative_or_err(self.exporter_file)?,
+            atlas_image_file: relative_or_err(self.atlas_image_file)?,
+            metadata_file: relative_or_err(self.metadata_file)?,
+            metadata_paths_root: relative_or_err(self.metadata_paths_root)?,
+            export_on_save: self.export_on_save,
+            coordinates: self.coordinates,
+            variables: self.variables,
             paths: std::marker::PhantomData,
         })
     }
@@ -1103,6 +1615,25 @@ fn portable_path<S: serde::Serializer>(value: &Path, serializer: S) -> Result<S:
     cleaned.serialize(serializer)
 }
 
+fn temporary_file_path(destination: &Path) -> PathBuf {
+    let file_name = destination
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    destination.with_file_name(format!(".{file_name}.{}.tmp", Uuid::new_v4()))
+}
+
+fn write_synced<T: Serialize>(path: &Path, content: &T) -> Result<(), SheetError> {
+    let file = File::create(path).map_err(|e| SheetError::IoError(path.to_owned(), e))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, content)?;
+    let file = writer
+        .into_inner()
+        .map_err(|e| SheetError::IoError(path.to_owned(), e.into_error()))?;
+    file.sync_all()
+        .map_err(|e| SheetError::IoError(path.to_owned(), e))
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1123,6 +1654,100 @@ mod tests {
         assert_eq!(original, copy);
     }
 
+    #[test]
+    fn write_replaces_destination_without_leftovers() {
+        let directory = PathBuf::from("test-output/write_replaces_destination_without_leftovers");
+        std::fs::remove_dir_all(&directory).ok();
+        std::fs::create_dir_all(&directory).unwrap();
+        let destination = directory.join("sheet.tiger");
+        std::fs::write(&destination, "previous content").unwrap();
+
+        let sheet = Sheet::<Any>::read("test-data/samurai.tiger")
+            .and_then(|s| s.with_relative_paths("test-data"))
+            .unwrap()
+            .with_absolute_paths();
+        sheet.clone().write(&destination).unwrap();
+
+        let files = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![destination.clone()]);
+
+        let written = Sheet::<Any>::read(&destination)
+            .and_then(|s| s.with_relative_paths(&directory))
+            .unwrap()
+            .with_absolute_paths();
+        assert_eq!(sheet, written);
+    }
+
+    #[test]
+    fn can_upgrade_sheet_from_version_1_0_0() {
+        let directory = PathBuf::from("test-output/can_upgrade_sheet_from_version_1_0_0");
+        std::fs::remove_dir_all(&directory).ok();
+        std::fs::create_dir_all(&directory).unwrap();
+        let source = directory.join("source.tiger");
+        std::fs::write(
+            &source,
+            r#"{
+                "version": "1.0.0",
+                "sheet": {
+                    "frames": [],
+                    "animations": {},
+                    "export_settings": {
+                        "Template": {
+                            "template_file": "sheet.template",
+                            "atlas_image_file": "sheet.png",
+                            "metadata_file": "sheet.json",
+                            "metadata_paths_root": "."
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let sheet = Sheet::<Any>::read(&source)
+            .and_then(|s| s.with_relative_paths(&directory))
+            .unwrap()
+            .with_absolute_paths();
+        let Some(ExportSettings::Template(settings)) = sheet.export_settings() else {
+            panic!("Expected template export settings");
+        };
+        assert_eq!(
+            settings.template_file(),
+            directory.join("sheet.template").resolve()
+        );
+
+        let destination = directory.join("destination.tiger");
+        sheet.write(&destination).unwrap();
+        let written = std::fs::read_to_string(&destination).unwrap();
+        assert!(written.contains(r#""version": "1.1.0""#));
+    }
+
+    #[test]
+    fn can_apply_and_revert_diff() {
+        let original = Sheet::<Any>::read("test-data/samurai.tiger")
+            .and_then(|s| s.with_relative_paths("test-data"))
+            .unwrap()
+            .with_absolute_paths();
+
+        let mut modified = original.clone();
+        modified.delete_animation("attack");
+        modified.create_animation("new");
+        modified.add_frame("test-data/new.png");
+
+        let diff = original.diff(&modified);
+        assert!(!diff.is_empty());
+        assert!(original.diff(&original).is_empty());
+
+        let mut sheet = original.clone();
+        sheet.apply(&diff);
+        assert_eq!(sheet, modified);
+        sheet.revert(&diff);
+        assert_eq!(sheet, original);
+    }
+
     #[test]
     fn can_add_and_remove_sheet_frame() {
         let mut sheet = Sheet::<Any>::default();
@@ -1513,6 +2138,9 @@ mod tests {
             atlas_image_file: PathBuf::from("a/b/c/sheet.png").resolve(),
             metadata_file: PathBuf::from("a/b/c/sheet.lua").resolve(),
             metadata_paths_root: PathBuf::from("a/b").resolve(),
+            export_on_save: false,
+            variables: BTreeMap::from([("ppu".to_owned(), "16".to_owned())]),
+            coordinates: ExportCoordinates::default(),
             paths: std::marker::PhantomData,
         }
         .with_absolute_paths()
@@ -1552,6 +2180,74 @@ mod tests {
         assert_eq!(settings.metadata_paths_root(), path);
     }
 
+    #[test]
+    fn template_export_settings_can_edit_variables() {
+        let mut settings = TemplateExportSettings::<Any>::default();
+        assert_eq!(settings.create_variable("variable_1"), "variable_1");
+        assert_eq!(settings.create_variable("variable_1"), "variable_2");
+
+        settings.set_variable("variable_1", "16").unwrap();
+        assert!(matches!(
+            settings.set_variable("missing", "16"),
+            Err(SheetError::VariableNotFound(_))
+        ));
+        settings.rename_variable("variable_1", "ppu").unwrap();
+        assert_eq!(
+            settings.variables().get("ppu").map(String::as_str),
+            Some("16")
+        );
+
+        assert!(matches!(
+            settings.rename_variable("ppu", "variable_2"),
+            Err(SheetError::VariableNameAlreadyExists(_))
+        ));
+        assert!(matches!(
+            settings.rename_variable("missing", "other"),
+            Err(SheetError::VariableNotFound(_))
+        ));
+
+        settings.delete_variable("variable_2");
+        assert_eq!(settings.variables().len(), 1);
+    }
+
+    #[test]
+    fn export_settings_can_switch_format() {
+        let mut template_settings = TemplateExportSettings::<Any>::default();
+        template_settings.create_variable("ppu");
+        template_settings.set_variable("ppu", "16").unwrap();
+        let mut settings = ExportSettings::Template(template_settings);
+        settings.set_atlas_image_file("atlas_image_file");
+        settings.set_metadata_file("metadata_file");
+        settings.set_metadata_paths_root("metadata_paths_root");
+        settings.set_export_on_save(true);
+        let mut coordinates = ExportCoordinates::default();
+        coordinates.set_origin(ExportOrigin::BottomCenter);
+        coordinates.set_y_axis(ExportYAxis::Up);
+        settings.set_coordinates(coordinates);
+
+        let settings = settings.with_format(ExportFormat::Exporter);
+        assert_eq!(settings.format(), ExportFormat::Exporter);
+        assert_eq!(settings.atlas_image_file(), Path::new("atlas_image_file"));
+        assert_eq!(settings.metadata_file(), Path::new("metadata_file"));
+        assert_eq!(
+            settings.metadata_paths_root(),
+            Path::new("metadata_paths_root")
+        );
+        assert!(settings.export_on_save());
+        assert_eq!(settings.coordinates(), coordinates);
+
+        let settings = settings.with_format(ExportFormat::Template);
+        assert_eq!(settings.format(), ExportFormat::Template);
+        assert_eq!(settings.atlas_image_file(), Path::new("atlas_image_file"));
+        let ExportSettings::Template(template_settings) = settings else {
+            panic!("Expected template export settings");
+        };
+        assert_eq!(
+            template_settings.variables().get("ppu").map(String::as_str),
+            Some("16")
+        );
+    }
+
     #[test]
     fn generate_unique_name_respects_suggestions() {
         assert_eq!("oink", generate_unique_name("oink", |_| true));
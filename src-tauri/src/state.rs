@@ -1,14 +1,20 @@
 use json_patch::Patch;
 use log::error;
 use squeak::{Delegate, Observable};
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 use sugar_path::SugarPath;
 use thiserror::Error;
 
 use crate::{
-    document::{ClipboardManifest, Document, DocumentError},
+    document::{ClipboardManifest, Document, DocumentError, Macro, SavedHistory, SavedView},
     dto::{self, StateTrim},
-    features::{app_updates::UpdateStep, onboarding::OnboardingStep},
+    features::{
+        app_updates::UpdateStep, autosave::RecoverableDocument, onboarding::OnboardingStep,
+        settings::Settings,
+    },
     utils::handle,
 };
 
@@ -27,10 +33,15 @@ pub struct State {
     documents: Vec<Document>,
     current_document: Option<PathBuf>,
     recent_documents: Observable<'static, Vec<PathBuf>>,
+    recoverable_documents: Vec<RecoverableDocument>,
+    saved_views: Observable<'static, HashMap<PathBuf, SavedView>>,
+    saved_histories: Observable<'static, HashMap<PathBuf, SavedHistory>>,
+    macros: Observable<'static, HashMap<String, Macro>>,
     errors: Vec<UserFacingError>,
     startup_finalized: bool,
     clipboard_manifest: Option<ClipboardManifest>,
     onboarding_step: Observable<'static, OnboardingStep>,
+    settings: Observable<'static, Settings>,
     update_step: UpdateStep,
     about_dialog_open: bool,
     opened_startup_documents: bool,
@@ -67,6 +78,14 @@ impl State {
     fn after_change(&mut self) {
         self.advance_onboarding();
         self.advance_exit();
+        self.apply_history_limit();
+    }
+
+    fn apply_history_limit(&mut self) {
+        let history_limit = self.settings.history_limit;
+        for document in &mut self.documents {
+            document.set_history_limit(history_limit);
+        }
     }
 
     pub fn documents_iter(&self) -> impl Iterator<Item = &Document> {
@@ -179,7 +198,20 @@ impl State {
             .iter()
             .position(|d| d.path() == path.as_ref())
         {
-            self.documents.remove(index);
+            let document = self.documents.remove(index);
+            if self.settings.restore_views {
+                self.saved_views.mutate(|v| {
+                    v.insert(document.path().resolve(), document.saved_view());
+                });
+            }
+            if self.settings.persist_history {
+                self.saved_histories.mutate(|h| {
+                    match document.saved_history() {
+                        Some(history) => h.insert(document.path().resolve(), history),
+                        None => h.remove(&document.path().resolve()),
+                    };
+                });
+            }
             self.current_document = if self.documents.is_empty() {
                 None
             } else {
@@ -286,6 +318,37 @@ impl State {
         self.recent_documents.delegate()
     }
 
+    pub fn recoverable_documents(&self) -> impl Iterator<Item = &RecoverableDocument> {
+        self.recoverable_documents.iter()
+    }
+
+    pub fn set_recoverable_documents(&mut self, documents: Vec<RecoverableDocument>) {
+        self.recoverable_documents = documents;
+    }
+
+    pub fn recoverable_document<T: AsRef<Path>>(&self, path: T) -> Option<&RecoverableDocument> {
+        self.recoverable_documents
+            .iter()
+            .find(|d| d.document == path.as_ref())
+    }
+
+    pub fn discard_recoverable_document<T: AsRef<Path>>(&mut self, path: T) {
+        self.recoverable_documents
+            .retain(|d| d.document != path.as_ref());
+    }
+
+    pub fn open_recovered_document(&mut self, document: Document) {
+        let path = document.path().to_owned();
+        self.discard_recoverable_document(&path);
+        match self.document_mut(&path) {
+            Some(d) => *d = document,
+            None => self.documents.push(document),
+        }
+        self.focus_document(&path).unwrap();
+        self.add_recent_document(path);
+        self.set_onboarding_step(OnboardingStep::Completed);
+    }
+
     pub fn finalize_startup(&mut self) {
         self.startup_finalized = true;
     }
@@ -316,6 +379,151 @@ impl State {
         self.onboarding_step.delegate()
     }
 
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, new_settings: Settings) {
+        if new_settings != *self.settings {
+            self.settings.mutate(|s| *s = new_settings);
+        }
+    }
+
+    pub fn settings_delegate(&self) -> &Delegate<'static, Settings> {
+        self.settings.delegate()
+    }
+
+    pub fn allow_exporter<P: AsRef<Path>>(&mut self, exporter: P) {
+        let exporter = exporter.as_ref().resolve();
+        if self.settings().allowed_exporters.contains(&exporter) {
+            return;
+        }
+        let mut allowed_exporters = self.settings().allowed_exporters.clone();
+        allowed_exporters.push(exporter);
+        self.set_settings(Settings {
+            allowed_exporters,
+            ..self.settings().clone()
+        });
+    }
+
+    pub fn is_exporter_allowed<P: AsRef<Path>>(&self, exporter: P) -> bool {
+        self.settings()
+            .allowed_exporters
+            .contains(&exporter.as_ref().resolve())
+    }
+
+    pub fn set_automation_server(&mut self, automation_server: bool) {
+        self.set_settings(Settings {
+            automation_server,
+            ..self.settings().clone()
+        });
+    }
+
+    pub fn set_max_backups(&mut self, max_backups: usize) {
+        self.set_settings(Settings {
+            max_backups,
+            ..self.settings().clone()
+        });
+    }
+
+    pub fn set_history_limit(&mut self, history_limit: usize) {
+        self.set_settings(Settings {
+            history_limit,
+            ..self.settings().clone()
+        });
+    }
+
+    pub fn set_persist_history(&mut self, persist_history: bool) {
+        self.set_settings(Settings {
+            persist_history,
+            ..self.settings().clone()
+        });
+        if !persist_history {
+            self.saved_histories.mutate(|h| h.clear());
+        }
+    }
+
+    pub fn set_post_export_commands(&mut self, post_export_commands: Vec<String>) {
+        self.set_settings(Settings {
+            post_export_commands,
+            ..self.settings().clone()
+        });
+    }
+
+    pub fn set_restore_views(&mut self, restore_views: bool) {
+        self.set_settings(Settings {
+            restore_views,
+            ..self.settings().clone()
+        });
+    }
+
+    pub fn set_texture_cache_budget_mb(&mut self, texture_cache_budget_mb: usize) {
+        self.set_settings(Settings {
+            texture_cache_budget_mb,
+            ..self.settings().clone()
+        });
+    }
+
+    pub fn saved_view<T: AsRef<Path>>(&self, path: T) -> Option<&SavedView> {
+        if !self.settings.restore_views {
+            return None;
+        }
+        self.saved_views.get(&path.as_ref().resolve())
+    }
+
+    pub fn set_saved_views(&mut self, saved_views: HashMap<PathBuf, SavedView>) {
+        self.saved_views.mutate(|v| *v = saved_views);
+    }
+
+    pub fn saved_views_delegate(&self) -> &Delegate<'static, HashMap<PathBuf, SavedView>> {
+        self.saved_views.delegate()
+    }
+
+    pub fn saved_history<T: AsRef<Path>>(&self, path: T) -> Option<&SavedHistory> {
+        if !self.settings.persist_history {
+            return None;
+        }
+        self.saved_histories.get(&path.as_ref().resolve())
+    }
+
+    pub fn set_saved_histories(&mut self, saved_histories: HashMap<PathBuf, SavedHistory>) {
+        self.saved_histories.mutate(|h| *h = saved_histories);
+    }
+
+    pub fn saved_histories_delegate(&self) -> &Delegate<'static, HashMap<PathBuf, SavedHistory>> {
+        self.saved_histories.delegate()
+    }
+
+    pub fn macro_named<T: AsRef<str>>(&self, name: T) -> Option<&Macro> {
+        self.macros.get(name.as_ref())
+    }
+
+    pub fn macro_names(&self) -> Vec<String> {
+        let mut names = self.macros.keys().cloned().collect::<Vec<_>>();
+        names.sort_by_key(|n| n.to_lowercase());
+        names
+    }
+
+    pub fn save_macro<T: Into<String>>(&mut self, name: T, recorded_macro: Macro) {
+        self.macros.mutate(|m| {
+            m.insert(name.into(), recorded_macro);
+        });
+    }
+
+    pub fn delete_macro<T: AsRef<str>>(&mut self, name: T) {
+        self.macros.mutate(|m| {
+            m.remove(name.as_ref());
+        });
+    }
+
+    pub fn set_macros(&mut self, macros: HashMap<String, Macro>) {
+        self.macros.mutate(|m| *m = macros);
+    }
+
+    pub fn macros_delegate(&self) -> &Delegate<'static, HashMap<String, Macro>> {
+        self.macros.delegate()
+    }
+
     pub fn request_update(&mut self) {
         if self.update_step == UpdateStep::UpdateAvailable {
             self.update_step = UpdateStep::UpdateRequested;
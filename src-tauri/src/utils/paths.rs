@@ -6,9 +6,16 @@ pub type Handle = handle::Handle<Paths>;
 
 #[derive(Clone, Debug)]
 pub struct Paths {
+    pub backups_directory: PathBuf,
+    pub history_file: PathBuf,
     pub log_file: PathBuf,
+    pub macros_file: PathBuf,
     pub recent_documents_file: PathBuf,
     pub onboarding_file: PathBuf,
+    pub recovery_directory: PathBuf,
+    pub recovery_manifest_file: PathBuf,
+    pub saved_views_file: PathBuf,
+    pub settings_file: PathBuf,
     pub updates_file: PathBuf,
 }
 
@@ -17,15 +24,34 @@ impl Paths {
         std::fs::create_dir_all(&local_app_data_dir).unwrap();
         let suffix = suffix.as_ref();
         Self {
+            backups_directory: local_app_data_dir.as_ref().join(format!("backups{suffix}")),
+            history_file: local_app_data_dir
+                .as_ref()
+                .join(format!("history{suffix}.json")),
             log_file: local_app_data_dir
                 .as_ref()
                 .join(format!("tiger{suffix}.log")),
+            macros_file: local_app_data_dir
+                .as_ref()
+                .join(format!("macros{suffix}.json")),
             recent_documents_file: local_app_data_dir
                 .as_ref()
                 .join(format!("recent-documents{suffix}.json")),
             onboarding_file: local_app_data_dir
                 .as_ref()
                 .join(format!("onboarding{suffix}.json")),
+            recovery_directory: local_app_data_dir
+                .as_ref()
+                .join(format!("recovery{suffix}")),
+            recovery_manifest_file: local_app_data_dir
+                .as_ref()
+                .join(format!("recovery{suffix}.json")),
+            saved_views_file: local_app_data_dir
+                .as_ref()
+                .join(format!("views{suffix}.json")),
+            settings_file: local_app_data_dir
+                .as_ref()
+                .join(format!("settings{suffix}.json")),
             updates_file: local_app_data_dir
                 .as_ref()
                 .join(format!("updates{suffix}.json")),
@@ -34,9 +60,16 @@ impl Paths {
 
     #[cfg(test)]
     pub fn remove_all(&self) {
+        std::fs::remove_dir_all(&self.backups_directory).ok();
+        std::fs::remove_file(&self.history_file).ok();
         std::fs::remove_file(&self.log_file).ok();
+        std::fs::remove_file(&self.macros_file).ok();
         std::fs::remove_file(&self.recent_documents_file).ok();
         std::fs::remove_file(&self.onboarding_file).ok();
+        std::fs::remove_dir_all(&self.recovery_directory).ok();
+        std::fs::remove_file(&self.recovery_manifest_file).ok();
+        std::fs::remove_file(&self.saved_views_file).ok();
+        std::fs::remove_file(&self.settings_file).ok();
         std::fs::remove_file(&self.updates_file).ok();
     }
 }
@@ -0,0 +1,3 @@
+@echo off
+rem Copies its input (exporter metadata) to its output
+findstr "^"
@@ -0,0 +1,3 @@
+@echo off
+echo Unsupported sheet 1>&2
+exit /b 1
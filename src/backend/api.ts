@@ -3,13 +3,19 @@ import {
   save as saveFileDialog,
 } from "@tauri-apps/api/dialog";
 import {
+  Backup,
   BrowseDirection,
   Direction,
   DirectionPreset,
+  ExportFormat,
+  ExportOrigin,
+  ExportPreview,
+  ExportYAxis,
   ListMode,
   NudgeDirection,
   Patch,
   ResizeAxis,
+  TextureCacheStats,
 } from "@/backend/dto";
 import { useStateStore } from "@/stores/state";
 import { invoke } from "@tauri-apps/api";
@@ -19,6 +25,10 @@ export async function getState(): Promise<void> {
   appStore.$state = await invoke("get_state");
 }
 
+export async function getTextureCacheStats(): Promise<TextureCacheStats> {
+  return invoke("get_texture_cache_stats");
+}
+
 export async function showErrorMessage(
   title: string,
   summary: string,
@@ -44,6 +54,11 @@ export async function acknowledgeError(): Promise<void> {
   appStore.patch(await invoke("acknowledge_error"));
 }
 
+export async function allowExporter(exporter: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("allow_exporter", { exporter: exporter }));
+}
+
 export async function openAboutDialog(): Promise<void> {
   const appStore = useStateStore();
   appStore.patch(await invoke("open_about_dialog"));
@@ -92,6 +107,80 @@ export async function openStartupDocuments() {
   appStore.patch(await invoke("open_startup_documents"));
 }
 
+export async function recoverDocument(path: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("recover_document", { path: path }));
+}
+
+export async function deleteMacro(name: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("delete_macro", { name: name }));
+}
+
+export async function discardRecoverableDocument(path: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("discard_recoverable_document", { path: path }));
+}
+
+export async function listBackups(document: string): Promise<Backup[]> {
+  return invoke("list_backups", { document: document });
+}
+
+export async function restoreBackup(
+  document: string,
+  backup: string
+): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(
+    await invoke("restore_backup", { document: document, backup: backup })
+  );
+}
+
+export async function setAutomationServer(enabled: boolean): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_automation_server", { enabled: enabled }));
+}
+
+export async function setHistoryLimit(historyLimit: number): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_history_limit", { historyLimit: historyLimit }));
+}
+
+export async function setMaxBackups(maxBackups: number): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_max_backups", { maxBackups: maxBackups }));
+}
+
+export async function setPersistHistory(persistHistory: boolean): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_persist_history", { persistHistory: persistHistory }));
+}
+
+export async function setPostExportCommands(commands: string[]): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_post_export_commands", { commands: commands }));
+}
+
+export async function setRestoreViews(restoreViews: boolean): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_restore_views", { restoreViews: restoreViews }));
+}
+
+export async function setTextureCacheBudgetMb(textureCacheBudgetMb: number): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_texture_cache_budget_mb", { textureCacheBudgetMb: textureCacheBudgetMb }));
+}
+
+export async function reloadDocument(path: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("reload_document", { path: path }));
+}
+
+export async function keepLocalChanges(path: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("keep_local_changes", { path: path }));
+}
+
 export async function finalizeStartup() {
   const appStore = useStateStore();
   appStore.patch(await invoke("finalize_startup"));
@@ -177,6 +266,11 @@ export async function redo(): Promise<void> {
   appStore.patch(await invoke("redo"));
 }
 
+export async function jumpToHistoryIndex(index: number): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("jump_to_history_index", { index: index }));
+}
+
 export async function cut(): Promise<void> {
   const appStore = useStateStore();
   appStore.patch(await invoke("cut"));
@@ -920,11 +1014,59 @@ export async function beginExportAs(): Promise<void> {
   appStore.patch(await invoke("begin_export_as"));
 }
 
+export async function setExportFormat(format: ExportFormat): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_export_format", { format: format }));
+}
+
 export async function setExportTemplateFile(file: string): Promise<void> {
   const appStore = useStateStore();
   appStore.patch(await invoke("set_export_template_file", { file: file }));
 }
 
+export async function createExportTemplateVariable(): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("create_export_template_variable"));
+}
+
+export async function renameExportTemplateVariable(
+  oldName: string,
+  newName: string
+): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(
+    await invoke("rename_export_template_variable", {
+      oldName: oldName,
+      newName: newName,
+    })
+  );
+}
+
+export async function setExportTemplateVariableValue(
+  name: string,
+  value: string
+): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(
+    await invoke("set_export_template_variable_value", {
+      name: name,
+      value: value,
+    })
+  );
+}
+
+export async function deleteExportTemplateVariable(name: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(
+    await invoke("delete_export_template_variable", { name: name })
+  );
+}
+
+export async function setExportExporterFile(file: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_export_exporter_file", { file: file }));
+}
+
 export async function setExportAtlasImageFile(file: string): Promise<void> {
   const appStore = useStateStore();
   appStore.patch(await invoke("set_export_atlas_image_file", { file: file }));
@@ -944,6 +1086,33 @@ export async function setExportMetadataPathsRoot(
   );
 }
 
+export async function setExportOnSave(enabled: boolean): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_export_on_save", { enabled: enabled }));
+}
+
+export async function setExportOrigin(origin: ExportOrigin): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_export_origin", { origin: origin }));
+}
+
+export async function setExportYAxis(yAxis: ExportYAxis): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("set_export_y_axis", { yAxis: yAxis }));
+}
+
+export async function setExportUvCoordinates(enabled: boolean): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(
+    await invoke("set_export_uv_coordinates", { enabled: enabled })
+  );
+}
+
+export async function cancelExport(document: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("cancel_export", { document: document }));
+}
+
 export async function cancelExportAs(): Promise<void> {
   const appStore = useStateStore();
   appStore.patch(await invoke("cancel_export_as"));
@@ -953,3 +1122,32 @@ export async function endExportAs(): Promise<void> {
   const appStore = useStateStore();
   appStore.patch(await invoke("end_export_as"));
 }
+
+export async function previewExport(): Promise<ExportPreview> {
+  return invoke("preview_export");
+}
+
+export async function beginMacroRecording(): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("begin_macro_recording"));
+}
+
+export async function cancelMacroRecording(): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("cancel_macro_recording"));
+}
+
+export async function endMacroRecording(name: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("end_macro_recording", { name: name }));
+}
+
+export async function playMacro(name: string): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("play_macro", { name: name }));
+}
+
+export async function playMacroOnAnimations(name: string, animations: string[]): Promise<void> {
+  const appStore = useStateStore();
+  appStore.patch(await invoke("play_macro_on_animations", { name: name, animations: animations }));
+}
@@ -6,6 +6,7 @@ export type State = {
   documents: Document[];
   currentDocumentPath: string | null;
   recentDocumentPaths: RecentDocument[];
+  recoverableDocuments: RecoverableDocument[];
   clipboardManifest: ClipboardManifest | null;
   isReleaseBuild: boolean;
   error: UserFacingError | null;
@@ -14,6 +15,38 @@ export type State = {
   updateStep: UpdateStep;
   aboutDialogOpen: boolean;
   commitHash: String | null;
+  settings: Settings;
+  macros: string[];
+};
+
+export type Settings = {
+  allowedExporters: string[];
+  automationServer: boolean;
+  historyLimit: number;
+  maxBackups: number;
+  persistHistory: boolean;
+  postExportCommands: string[];
+  restoreViews: boolean;
+  textureCacheBudgetMb: number;
+};
+
+export type CacheStats = {
+  entries: number;
+  bytesUsed: number;
+  budgetBytes: number;
+  hits: number;
+  misses: number;
+};
+
+export type TextureCacheStats = {
+  textures: CacheStats;
+  thumbnails: CacheStats;
+};
+
+export type Backup = {
+  path: string;
+  name: string;
+  timestampMillis: number;
 };
 
 export type RecentDocument = {
@@ -21,6 +54,11 @@ export type RecentDocument = {
   name: string;
 };
 
+export type RecoverableDocument = {
+  path: string;
+  name: string;
+};
+
 export type UserFacingError = {
   key: string;
   title: string;
@@ -55,7 +93,10 @@ export type Document = {
   hasUnsavedChanges: boolean;
   undoEffect: string | null;
   redoEffect: string | null;
+  history: HistoryEntry[];
+  historyIndex: number;
   wasCloseRequested: boolean;
+  wasModifiedExternally: boolean;
   sheet: Sheet;
   framesListMode: ListMode;
   framesListOffset: number;
@@ -87,6 +128,7 @@ export type Document = {
   lockHitboxes: boolean;
   preserveAspectRatio: boolean;
   isDraggingKeyframeDuration: boolean;
+  isRecordingMacro: boolean;
   animationBeingRenamed: string | null;
   hitboxBeingRenamed: string | null;
   framesBeingDragged: string[];
@@ -98,6 +140,10 @@ export type Document = {
   exportSettingsValidation: ExportSettingsValidation | null;
 };
 
+export type HistoryEntry = {
+  effect: string | null;
+};
+
 export type Sheet = {
   frames: Frame[];
   animations: Animation[];
@@ -194,11 +240,40 @@ export enum BrowseDirection {
   Right = "Right",
 }
 
+export enum ExportFormat {
+  Template = "Template",
+  Exporter = "Exporter",
+}
+
 export type ExportSettings = {
+  format: ExportFormat;
   templateFile: string;
+  exporterFile: string;
   atlasImageFile: string;
   metadataFile: string;
   metadataPathsRoot: string;
+  exportOnSave: boolean;
+  templateVariables: TemplateVariable[];
+  origin: ExportOrigin;
+  yAxis: ExportYAxis;
+  uvCoordinates: boolean;
+};
+
+export enum ExportOrigin {
+  TopLeft = "TopLeft",
+  BottomLeft = "BottomLeft",
+  Center = "Center",
+  BottomCenter = "BottomCenter",
+}
+
+export enum ExportYAxis {
+  Down = "Down",
+  Up = "Up",
+}
+
+export type TemplateVariable = {
+  name: string;
+  value: string;
 };
 
 export type TemplateError = {
@@ -215,22 +290,62 @@ export type ExportSettingsError =
 export type ExportSettingsValidation = {
   validSettings: boolean;
   templateFileError: ExportSettingsError | null;
+  exporterFileError: ExportSettingsError | null;
   atlasImageFileError: ExportSettingsError | null;
   metadataFileError: ExportSettingsError | null;
   metadataPathsRootError: ExportSettingsError | null;
 };
 
+export type ExportCancelled = {
+  documentName: string;
+  documentPath: string;
+};
+
 export type ExportError = {
   documentName: string;
+  documentPath: string;
   error: string;
 };
 
+export enum ExportStage {
+  LoadingTextures = "LoadingTextures",
+  Packing = "Packing",
+  Compositing = "Compositing",
+  RenderingTemplate = "RenderingTemplate",
+  Writing = "Writing",
+}
+
+export type ExportProgress = {
+  documentName: string;
+  documentPath: string;
+  stage: ExportStage;
+};
+
 export type ExportSuccess = {
   documentName: string;
+  documentPath: string;
   atlasImageFilePath: string;
   atlasImageFileName: string;
   metadataFilePath: string;
   metadataFileName: string;
+  postExportCommands: PostExportCommandOutput[];
+};
+
+export type ExportPreview =
+  | { output: ExportPreviewOutput }
+  | { error: string };
+
+export type ExportPreviewOutput = {
+  metadata: string;
+  atlasImageWidth: number;
+  atlasImageHeight: number;
+  atlasImage: string;
+};
+
+export type PostExportCommandOutput = {
+  command: string;
+  exitCode: number | null;
+  output: string;
 };
 
 export type UpdateError = {
@@ -19,6 +19,11 @@ export const useSpriteStore = defineStore("sprite", {
         convertFileSrc(path) +
         `?invalidation=${state.invalidationCounts.get(path) || 0}`;
     },
+    getThumbnailURL: (state) => {
+      return (path: string) =>
+        convertFileSrc(path, "thumbnail") +
+        `?invalidation=${state.invalidationCounts.get(path) || 0}`;
+    },
   },
 });
 
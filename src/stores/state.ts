@@ -18,6 +18,7 @@ export const useStateStore = defineStore("state", {
       documents: [],
       currentDocumentPath: null,
       recentDocumentPaths: [],
+      recoverableDocuments: [],
       clipboardManifest: null,
       isReleaseBuild: false,
       error: null,
@@ -26,6 +27,17 @@ export const useStateStore = defineStore("state", {
       updateStep: UpdateStep.Idle,
       aboutDialogOpen: false,
       commitHash: null,
+      settings: {
+        allowedExporters: [],
+        automationServer: false,
+        historyLimit: 100,
+        maxBackups: 5,
+        persistHistory: false,
+        postExportCommands: [],
+        restoreViews: true,
+        textureCacheBudgetMb: 1024,
+      },
+      macros: [],
     } as State),
   actions: {
     patch(patch: Patch) {